@@ -0,0 +1,372 @@
+//! JSON-RPC 2.0 specification conformance suite.
+//!
+//! The suite is a set of raw requests together with the exact responses the specification
+//! requires (including batches, invalid ids and versions, parse errors, unicode and huge numbers).
+//! It can be pointed at any `MetaIoHandler` or at a running server through one of the `Transport`s.
+//! Methods used by the cases have to be registered with `register` first.
+//!
+//! ```
+//! extern crate jsonrpc_core;
+//! extern crate jsonrpc_conformance as conformance;
+//!
+//! fn main() {
+//!   let mut io = jsonrpc_core::IoHandler::new();
+//!   conformance::register(&mut io);
+//!
+//!   conformance::assert_conforms(&mut conformance::Handler::new(&io));
+//! }
+//! ```
+
+#![warn(missing_docs)]
+
+extern crate jsonrpc_core as core;
+extern crate serde_json;
+
+mod transports;
+
+use std::fmt;
+
+use core::{Error, Metadata, MetaIoHandler, Middleware, Params, Value};
+
+pub use transports::{Handler, Http, Stream, Ws};
+
+/// A single conformance check.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Case {
+	/// Short description of the case.
+	pub name: &'static str,
+	/// Raw request sent to the server (never contains new lines).
+	pub request: &'static str,
+	/// Expected response (`None` if the server must not respond).
+	pub response: Option<&'static str>,
+}
+
+/// Delivers raw requests to the server under test.
+pub trait Transport {
+	/// Sends a raw request and returns the raw response (`None` if there was no response).
+	fn send(&mut self, request: &str) -> Option<String>;
+}
+
+/// Response that didn't match the expectation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mismatch {
+	/// The failing case.
+	pub case: Case,
+	/// Response returned by the server.
+	pub response: Option<String>,
+}
+
+impl fmt::Display for Mismatch {
+	fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+		writeln!(fmt, "{}", self.case.name)?;
+		writeln!(fmt, "  request:  {}", self.case.request)?;
+		writeln!(fmt, "  expected: {}", self.case.response.unwrap_or("<no response>"))?;
+		write!(fmt, "  got:      {}", self.response.as_ref().map(|r| r.as_str()).unwrap_or("<no response>"))
+	}
+}
+
+/// Registers methods used by the conformance cases.
+///
+/// Method names follow examples of the specification:
+/// `subtract`, `sum`, `get_data` and `echo` methods
+/// and `update`, `notify_hello` and `notify_sum` notifications.
+pub fn register<T: Metadata, S: Middleware<T>>(io: &mut MetaIoHandler<T, S>) {
+	io.add_method("subtract", |params: Params| {
+		let (minuend, subtrahend) = match params {
+			Params::Array(ref args) if args.len() == 2 => (args[0].as_i64(), args[1].as_i64()),
+			Params::Map(ref args) => (
+				args.get("minuend").and_then(Value::as_i64),
+				args.get("subtrahend").and_then(Value::as_i64),
+			),
+			_ => (None, None),
+		};
+		match (minuend, subtrahend) {
+			(Some(minuend), Some(subtrahend)) => minuend.checked_sub(subtrahend)
+				.map(Value::from)
+				.ok_or_else(|| Error::invalid_params("Result out of range.")),
+			_ => Err(Error::invalid_params("Expected minuend and subtrahend.")),
+		}
+	});
+	io.add_method("sum", |params: Params| {
+		params.parse::<Vec<i64>>()?.into_iter()
+			.fold(Some(0i64), |sum, number| sum.and_then(|sum| sum.checked_add(number)))
+			.map(Value::from)
+			.ok_or_else(|| Error::invalid_params("Result out of range."))
+	});
+	io.add_method("get_data", |_params| {
+		Ok(Value::Array(vec!["hello".into(), 5.into()]))
+	});
+	io.add_method("echo", |params: Params| params.parse::<Value>());
+	io.add_notification("update", |_params| {});
+	io.add_notification("notify_hello", |_params| {});
+	io.add_notification("notify_sum", |_params| {});
+}
+
+/// Returns all conformance cases.
+///
+/// NOTE: String ids used by the cases are never numeric,
+/// since `Id` deserializes strings like `"1"` as numbers.
+pub fn cases() -> &'static [Case] {
+	CASES
+}
+
+/// Runs all cases against given transport and returns the ones that failed.
+pub fn check<T: Transport>(transport: &mut T) -> Vec<Mismatch> {
+	CASES.iter().filter_map(|case| {
+		let response = transport.send(case.request);
+		if matches(case.response, response.as_ref().map(|r| r.as_str())) {
+			None
+		} else {
+			Some(Mismatch { case: *case, response })
+		}
+	}).collect()
+}
+
+/// Runs all cases against given transport and panics if any of them failed.
+pub fn assert_conforms<T: Transport>(transport: &mut T) {
+	let mismatches = check(transport);
+	if !mismatches.is_empty() {
+		let report = mismatches.iter().map(|m| m.to_string()).collect::<Vec<_>>().join("\n\n");
+		panic!("{} of {} conformance cases failed:\n\n{}", mismatches.len(), CASES.len(), report);
+	}
+}
+
+/// Compares responses as JSON values.
+/// Batch responses may come in any order (as allowed by the specification).
+fn matches(expected: Option<&str>, response: Option<&str>) -> bool {
+	let (expected, response) = match (expected, response) {
+		(None, None) => return true,
+		(Some(expected), Some(response)) => (expected, response),
+		_ => return false,
+	};
+
+	let expected: Value = serde_json::from_str(expected).expect("Expected responses are valid JSON; qed");
+	let response: Value = match serde_json::from_str(response) {
+		Ok(response) => response,
+		Err(_) => return false,
+	};
+
+	match (expected, response) {
+		(Value::Array(expected), Value::Array(mut response)) => {
+			expected.len() == response.len() && expected.iter().all(|output| {
+				match response.iter().position(|r| r == output) {
+					Some(idx) => {
+						response.remove(idx);
+						true
+					},
+					None => false,
+				}
+			})
+		},
+		(expected, response) => expected == response,
+	}
+}
+
+const CASES: &[Case] = &[
+	Case {
+		name: "call with positional params",
+		request: r#"{"jsonrpc": "2.0", "method": "subtract", "params": [42, 23], "id": 1}"#,
+		response: Some(r#"{"jsonrpc": "2.0", "result": 19, "id": 1}"#),
+	},
+	Case {
+		name: "call with positional params in reverse order",
+		request: r#"{"jsonrpc": "2.0", "method": "subtract", "params": [23, 42], "id": 2}"#,
+		response: Some(r#"{"jsonrpc": "2.0", "result": -19, "id": 2}"#),
+	},
+	Case {
+		name: "call with named params",
+		request: r#"{"jsonrpc": "2.0", "method": "subtract", "params": {"subtrahend": 23, "minuend": 42}, "id": 3}"#,
+		response: Some(r#"{"jsonrpc": "2.0", "result": 19, "id": 3}"#),
+	},
+	Case {
+		name: "call with named params in different order",
+		request: r#"{"jsonrpc": "2.0", "method": "subtract", "params": {"minuend": 42, "subtrahend": 23}, "id": 4}"#,
+		response: Some(r#"{"jsonrpc": "2.0", "result": 19, "id": 4}"#),
+	},
+	Case {
+		name: "call with null id",
+		request: r#"{"jsonrpc": "2.0", "method": "subtract", "params": [42, 23], "id": null}"#,
+		response: Some(r#"{"jsonrpc": "2.0", "result": 19, "id": null}"#),
+	},
+	Case {
+		name: "call with largest numeric id",
+		request: r#"{"jsonrpc": "2.0", "method": "subtract", "params": [42, 23], "id": 18446744073709551615}"#,
+		response: Some(r#"{"jsonrpc": "2.0", "result": 19, "id": 18446744073709551615}"#),
+	},
+	Case {
+		name: "call with unicode string id",
+		request: r#"{"jsonrpc": "2.0", "method": "subtract", "params": [42, 23], "id": "zażółć-😀"}"#,
+		response: Some(r#"{"jsonrpc": "2.0", "result": 19, "id": "zażółć-😀"}"#),
+	},
+	Case {
+		name: "notification",
+		request: r#"{"jsonrpc": "2.0", "method": "update", "params": [1, 2, 3, 4, 5]}"#,
+		response: None,
+	},
+	Case {
+		name: "notification of non-existent method",
+		request: r#"{"jsonrpc": "2.0", "method": "foobar"}"#,
+		response: None,
+	},
+	Case {
+		name: "call of non-existent method",
+		request: r#"{"jsonrpc": "2.0", "method": "foobar", "id": "a1"}"#,
+		response: Some(r#"{"jsonrpc": "2.0", "error": {"code": -32601, "message": "Method not found"}, "id": "a1"}"#),
+	},
+	Case {
+		name: "call of non-existent method with unicode name",
+		request: r#"{"jsonrpc": "2.0", "method": "gęślą_jaźń", "id": 1}"#,
+		response: Some(r#"{"jsonrpc": "2.0", "error": {"code": -32601, "message": "Method not found"}, "id": 1}"#),
+	},
+	Case {
+		name: "call with invalid JSON",
+		request: r#"{"jsonrpc": "2.0", "method": "foobar, "params": "bar", "baz]"#,
+		response: Some(r#"{"jsonrpc": "2.0", "error": {"code": -32700, "message": "Parse error"}, "id": null}"#),
+	},
+	Case {
+		name: "call with invalid request object",
+		request: r#"{"jsonrpc": "2.0", "method": 1, "params": "bar"}"#,
+		response: Some(r#"{"jsonrpc": "2.0", "error": {"code": -32600, "message": "Invalid request"}, "id": null}"#),
+	},
+	Case {
+		name: "call with unstructured params",
+		request: r#"{"jsonrpc": "2.0", "method": "subtract", "params": 42, "id": 1}"#,
+		response: Some(r#"{"jsonrpc": "2.0", "error": {"code": -32600, "message": "Invalid request"}, "id": 1}"#),
+	},
+	Case {
+		name: "call with invalid version",
+		request: r#"{"jsonrpc": "1.5", "method": "subtract", "params": [42, 23], "id": 1}"#,
+		response: Some(r#"{"jsonrpc": "2.0", "error": {"code": -32600, "message": "Invalid request"}, "id": 1}"#),
+	},
+	Case {
+		name: "call with numeric version",
+		request: r#"{"jsonrpc": 2.0, "method": "subtract", "params": [42, 23], "id": 1}"#,
+		response: Some(r#"{"jsonrpc": "2.0", "error": {"code": -32600, "message": "Invalid request"}, "id": 1}"#),
+	},
+	Case {
+		name: "call with object id",
+		request: r#"{"jsonrpc": "2.0", "method": "subtract", "params": [42, 23], "id": {"a": 1}}"#,
+		response: Some(r#"{"jsonrpc": "2.0", "error": {"code": -32600, "message": "Invalid request"}, "id": null}"#),
+	},
+	Case {
+		name: "call with array id",
+		request: r#"{"jsonrpc": "2.0", "method": "subtract", "params": [42, 23], "id": [1]}"#,
+		response: Some(r#"{"jsonrpc": "2.0", "error": {"code": -32600, "message": "Invalid request"}, "id": null}"#),
+	},
+	Case {
+		name: "call with boolean id",
+		request: r#"{"jsonrpc": "2.0", "method": "subtract", "params": [42, 23], "id": true}"#,
+		response: Some(r#"{"jsonrpc": "2.0", "error": {"code": -32600, "message": "Invalid request"}, "id": null}"#),
+	},
+	Case {
+		name: "call with fractional id",
+		request: r#"{"jsonrpc": "2.0", "method": "subtract", "params": [42, 23], "id": 1.5}"#,
+		response: Some(r#"{"jsonrpc": "2.0", "error": {"code": -32600, "message": "Invalid request"}, "id": null}"#),
+	},
+	Case {
+		name: "call with unicode params",
+		request: r#"{"jsonrpc": "2.0", "method": "echo", "params": ["zażółć gęślą jaźń", "日本語", "\u0000\t\"\\", "😀"], "id": 1}"#,
+		response: Some(r#"{"jsonrpc": "2.0", "result": ["zażółć gęślą jaźń", "日本語", "\u0000\t\"\\", "😀"], "id": 1}"#),
+	},
+	Case {
+		name: "call with huge numbers",
+		request: r#"{"jsonrpc": "2.0", "method": "echo", "params": [18446744073709551615, -9223372036854775808, 1.7976931348623157e308, 5e-324], "id": 1}"#,
+		response: Some(r#"{"jsonrpc": "2.0", "result": [18446744073709551615, -9223372036854775808, 1.7976931348623157e308, 5e-324], "id": 1}"#),
+	},
+	Case {
+		name: "batch with invalid JSON",
+		request: r#"[{"jsonrpc": "2.0", "method": "sum", "params": [1, 2, 4], "id": "a1"}, {"jsonrpc": "2.0", "method"]"#,
+		response: Some(r#"{"jsonrpc": "2.0", "error": {"code": -32700, "message": "Parse error"}, "id": null}"#),
+	},
+	Case {
+		name: "empty batch",
+		request: r#"[]"#,
+		response: Some(r#"{"jsonrpc": "2.0", "error": {"code": -32600, "message": "Invalid request"}, "id": null}"#),
+	},
+	Case {
+		name: "batch with invalid call",
+		request: r#"[1]"#,
+		response: Some(r#"[{"jsonrpc": "2.0", "error": {"code": -32600, "message": "Invalid request"}, "id": null}]"#),
+	},
+	Case {
+		name: "batch with invalid calls",
+		request: r#"[1, 2, 3]"#,
+		response: Some(r#"[
+			{"jsonrpc": "2.0", "error": {"code": -32600, "message": "Invalid request"}, "id": null},
+			{"jsonrpc": "2.0", "error": {"code": -32600, "message": "Invalid request"}, "id": null},
+			{"jsonrpc": "2.0", "error": {"code": -32600, "message": "Invalid request"}, "id": null}
+		]"#),
+	},
+	Case {
+		name: "batch with calls and notifications",
+		request: r#"[{"jsonrpc": "2.0", "method": "sum", "params": [1, 2, 4], "id": "a1"}, {"jsonrpc": "2.0", "method": "notify_hello", "params": [7]}, {"jsonrpc": "2.0", "method": "subtract", "params": [42, 23], "id": "a2"}, {"foo": "boo"}, {"jsonrpc": "2.0", "method": "foo.get", "params": {"name": "myself"}, "id": "a5"}, {"jsonrpc": "2.0", "method": "get_data", "id": "a9"}]"#,
+		response: Some(r#"[
+			{"jsonrpc": "2.0", "result": 7, "id": "a1"},
+			{"jsonrpc": "2.0", "result": 19, "id": "a2"},
+			{"jsonrpc": "2.0", "error": {"code": -32600, "message": "Invalid request"}, "id": null},
+			{"jsonrpc": "2.0", "error": {"code": -32601, "message": "Method not found"}, "id": "a5"},
+			{"jsonrpc": "2.0", "result": ["hello", 5], "id": "a9"}
+		]"#),
+	},
+	Case {
+		name: "batch of notifications",
+		request: r#"[{"jsonrpc": "2.0", "method": "notify_sum", "params": [1, 2, 4]}, {"jsonrpc": "2.0", "method": "notify_hello", "params": [7]}]"#,
+		response: None,
+	},
+];
+
+#[cfg(test)]
+mod tests {
+	use core::IoHandler;
+	use super::{register, check, matches, Handler};
+
+	#[test]
+	fn should_conform_to_the_spec() {
+		// given
+		let mut io = IoHandler::new();
+		register(&mut io);
+
+		// when
+		let mismatches = check(&mut Handler::new(&io));
+
+		// then
+		assert_eq!(mismatches, vec![]);
+	}
+
+	#[test]
+	fn should_report_mismatches() {
+		// given
+		let mut io = IoHandler::new();
+		register(&mut io);
+		io.add_method("sum", |_| Ok(0.into()));
+
+		// when
+		let mismatches = check(&mut Handler::new(&io));
+
+		// then
+		let names: Vec<_> = mismatches.iter().map(|m| m.case.name).collect();
+		assert_eq!(names, vec!["batch with calls and notifications"]);
+	}
+
+	#[test]
+	fn should_reject_sum_out_of_range() {
+		// given
+		let mut io = IoHandler::new();
+		register(&mut io);
+
+		// when
+		let request = r#"{"jsonrpc": "2.0", "method": "sum", "params": [9223372036854775807, 1], "id": 1}"#;
+		let response = io.handle_request_sync(request);
+
+		// then
+		assert_eq!(response, Some(r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Result out of range."},"id":1}"#.into()));
+	}
+
+	#[test]
+	fn should_match_batches_in_any_order() {
+		assert!(matches(Some(r#"[{"id": 1}, {"id": 2}]"#), Some(r#"[{"id":2},{"id":1}]"#)));
+		assert!(!matches(Some(r#"[{"id": 1}, {"id": 1}]"#), Some(r#"[{"id":1},{"id":2}]"#)));
+		assert!(!matches(Some(r#"{"id": 1}"#), None));
+		assert!(!matches(Some(r#"{"id": 1}"#), Some("{")));
+	}
+}
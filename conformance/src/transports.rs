@@ -0,0 +1,308 @@
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpStream};
+
+use core::{Metadata, MetaIoHandler, Middleware, Value};
+use serde_json;
+
+use Transport;
+
+/// Request sent after every case over streaming transports.
+/// The response to it marks the end of responses to the case, so that
+/// missing responses (to notifications) are detected without timeouts.
+/// This requires the server to respond in order (e.g. with `Execution::Ordered`).
+const SENTINEL: &str = r#"{"jsonrpc":"2.0","method":"rpc.conformance.sentinel","id":"rpc.conformance.sentinel"}"#;
+
+fn is_sentinel(response: &str) -> bool {
+	serde_json::from_str::<Value>(response)
+		.map(|response| response["id"] == "rpc.conformance.sentinel")
+		.unwrap_or(false)
+}
+
+/// Passes requests directly to `MetaIoHandler`.
+pub struct Handler<'a, T: Metadata, S: Middleware<T> + 'a> {
+	io: &'a MetaIoHandler<T, S>,
+	meta: T,
+}
+
+impl<'a, T: Metadata + Default, S: Middleware<T>> Handler<'a, T, S> {
+	/// Creates new transport using default metadata.
+	pub fn new(io: &'a MetaIoHandler<T, S>) -> Self {
+		Self::with_meta(io, Default::default())
+	}
+}
+
+impl<'a, T: Metadata, S: Middleware<T>> Handler<'a, T, S> {
+	/// Creates new transport passing given metadata with every request.
+	pub fn with_meta(io: &'a MetaIoHandler<T, S>, meta: T) -> Self {
+		Handler { io, meta }
+	}
+}
+
+impl<'a, T: Metadata, S: Middleware<T>> Transport for Handler<'a, T, S> {
+	fn send(&mut self, request: &str) -> Option<String> {
+		self.io.handle_request_sync(request, self.meta.clone())
+	}
+}
+
+/// Sends requests to HTTP server (a new connection for every request).
+pub struct Http {
+	address: SocketAddr,
+}
+
+impl Http {
+	/// Creates new transport for server listening on given address.
+	pub fn new(address: SocketAddr) -> Self {
+		Http { address }
+	}
+
+	fn post(&self, request: &str) -> io::Result<String> {
+		let mut stream = TcpStream::connect(&self.address)?;
+		write!(
+			stream,
+			"POST / HTTP/1.1\r\nHost: {}\r\nConnection: close\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+			self.address,
+			request.len(),
+			request,
+		)?;
+
+		let mut response = Vec::new();
+		stream.read_to_end(&mut response)?;
+
+		let (head, body) = match response.windows(4).position(|window| window == b"\r\n\r\n") {
+			Some(idx) => (String::from_utf8_lossy(&response[..idx]), &response[idx + 4..]),
+			None => return Err(io::Error::new(io::ErrorKind::InvalidData, "Incomplete HTTP response.")),
+		};
+		if !head.starts_with("HTTP/1.1 200") {
+			return Err(io::Error::new(io::ErrorKind::Other, format!("Unexpected HTTP response: {}", head)));
+		}
+
+		let chunked = head.lines().any(|line| {
+			let line = line.to_lowercase();
+			line.starts_with("transfer-encoding:") && line.contains("chunked")
+		});
+		let body = if chunked {
+			dechunk(body)?
+		} else {
+			body.to_vec()
+		};
+		String::from_utf8(body).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+	}
+}
+
+// Chunk boundaries may split multi-byte characters, so the body is decoded as bytes.
+fn dechunk(mut body: &[u8]) -> io::Result<Vec<u8>> {
+	let invalid = || io::Error::new(io::ErrorKind::InvalidData, "Invalid chunked encoding.");
+	let mut result = Vec::new();
+	loop {
+		let idx = body.windows(2).position(|window| window == b"\r\n").ok_or_else(invalid)?;
+		let size = ::std::str::from_utf8(&body[..idx]).map_err(|_| invalid())?;
+		let size = usize::from_str_radix(size.trim(), 16).map_err(|_| invalid())?;
+		if size == 0 {
+			return Ok(result);
+		}
+		let chunk = body.get(idx + 2..idx + 2 + size).ok_or_else(invalid)?;
+		result.extend_from_slice(chunk);
+		body = body.get(idx + 4 + size..).ok_or_else(invalid)?;
+	}
+}
+
+impl Transport for Http {
+	fn send(&mut self, request: &str) -> Option<String> {
+		let response = self.post(request).expect("HTTP request failed");
+		if response.trim().is_empty() {
+			None
+		} else {
+			Some(response)
+		}
+	}
+}
+
+/// Sends requests over a newline-delimited stream (TCP or IPC).
+///
+/// The server has to use `\n` as both incoming and outgoing separator
+/// and respond to requests in order (`Execution::Ordered`).
+pub struct Stream<S> {
+	stream: BufReader<S>,
+}
+
+impl<S: Read + Write> Stream<S> {
+	/// Creates new transport over given stream.
+	pub fn new(stream: S) -> Self {
+		Stream {
+			stream: BufReader::new(stream),
+		}
+	}
+
+	fn exchange(&mut self, request: &str) -> io::Result<Option<String>> {
+		write!(self.stream.get_mut(), "{}\n{}\n", request, SENTINEL)?;
+
+		let mut response = None;
+		loop {
+			let mut line = String::new();
+			if self.stream.read_line(&mut line)? == 0 {
+				return Err(io::ErrorKind::UnexpectedEof.into());
+			}
+			let line = line.trim();
+			if line.is_empty() {
+				continue;
+			}
+			if is_sentinel(line) {
+				return Ok(response);
+			}
+			response = Some(line.to_owned());
+		}
+	}
+}
+
+impl Stream<TcpStream> {
+	/// Connects to TCP server listening on given address.
+	pub fn tcp(address: &SocketAddr) -> io::Result<Self> {
+		TcpStream::connect(address).map(Self::new)
+	}
+}
+
+#[cfg(unix)]
+impl Stream<::std::os::unix::net::UnixStream> {
+	/// Connects to IPC server listening on given path.
+	pub fn ipc<P: AsRef<::std::path::Path>>(path: P) -> io::Result<Self> {
+		::std::os::unix::net::UnixStream::connect(path).map(Self::new)
+	}
+}
+
+impl<S: Read + Write> Transport for Stream<S> {
+	fn send(&mut self, request: &str) -> Option<String> {
+		self.exchange(request).expect("Stream request failed")
+	}
+}
+
+/// Sends requests over WebSocket connection (as text messages).
+///
+/// The server has to respond to requests in order (`Execution::Ordered`).
+pub struct Ws {
+	stream: TcpStream,
+}
+
+impl Ws {
+	/// Connects to WebSocket server listening on given address.
+	pub fn connect(address: &SocketAddr) -> io::Result<Self> {
+		let mut stream = TcpStream::connect(address)?;
+		write!(
+			stream,
+			"GET / HTTP/1.1\r\nHost: {}\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\
+			Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\nSec-WebSocket-Version: 13\r\n\r\n",
+			address,
+		)?;
+
+		// read the handshake response byte by byte, not to consume any frames
+		let mut head = Vec::new();
+		while !head.ends_with(b"\r\n\r\n") {
+			let mut byte = [0u8];
+			stream.read_exact(&mut byte)?;
+			head.push(byte[0]);
+		}
+		if !head.starts_with(b"HTTP/1.1 101") {
+			let head = String::from_utf8_lossy(&head).into_owned();
+			return Err(io::Error::new(io::ErrorKind::Other, format!("WebSocket handshake failed: {}", head)));
+		}
+
+		Ok(Ws { stream })
+	}
+
+	fn write_message(&mut self, message: &str) -> io::Result<()> {
+		const MASK: [u8; 4] = [0x12, 0x34, 0x56, 0x78];
+
+		let payload = message.as_bytes();
+		// FIN + text frame
+		let mut frame = vec![0x81];
+		match payload.len() {
+			len if len < 126 => frame.push(0x80 | len as u8),
+			len if len <= 0xffff => {
+				frame.push(0x80 | 126);
+				frame.extend_from_slice(&[(len >> 8) as u8, len as u8]);
+			},
+			len => {
+				frame.push(0x80 | 127);
+				frame.extend((0..8).rev().map(|i| ((len as u64) >> (i * 8)) as u8));
+			},
+		}
+		frame.extend_from_slice(&MASK);
+		frame.extend(payload.iter().enumerate().map(|(i, byte)| byte ^ MASK[i % 4]));
+		self.stream.write_all(&frame)
+	}
+
+	fn read_message(&mut self) -> io::Result<String> {
+		let mut message = Vec::new();
+		loop {
+			let mut header = [0u8; 2];
+			self.stream.read_exact(&mut header)?;
+			let fin = header[0] & 0x80 != 0;
+			let opcode = header[0] & 0x0f;
+			let len = match header[1] & 0x7f {
+				126 => {
+					let mut len = [0u8; 2];
+					self.stream.read_exact(&mut len)?;
+					(len[0] as usize) << 8 | len[1] as usize
+				},
+				127 => {
+					let mut len = [0u8; 8];
+					self.stream.read_exact(&mut len)?;
+					len.iter().fold(0, |acc, byte| acc << 8 | *byte as usize)
+				},
+				len => len as usize,
+			};
+
+			let mut payload = vec![0u8; len];
+			self.stream.read_exact(&mut payload)?;
+
+			match opcode {
+				// text or continuation frame
+				0x1 | 0x0 => message.extend_from_slice(&payload),
+				0x8 => return Err(io::Error::new(io::ErrorKind::ConnectionAborted, "WebSocket closed by the server.")),
+				// ignore control frames
+				_ => continue,
+			}
+			if fin {
+				return String::from_utf8(message).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e));
+			}
+		}
+	}
+
+	fn exchange(&mut self, request: &str) -> io::Result<Option<String>> {
+		self.write_message(request)?;
+		self.write_message(SENTINEL)?;
+
+		let mut response = None;
+		loop {
+			let message = self.read_message()?;
+			if is_sentinel(&message) {
+				return Ok(response);
+			}
+			response = Some(message);
+		}
+	}
+}
+
+impl Transport for Ws {
+	fn send(&mut self, request: &str) -> Option<String> {
+		self.exchange(request).expect("WebSocket request failed")
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::dechunk;
+
+	#[test]
+	fn should_decode_chunked_body() {
+		assert_eq!(dechunk(b"4\r\n{\"a\"\r\n3\r\n:1}\r\n0\r\n\r\n").unwrap(), b"{\"a\":1}");
+		assert!(dechunk(b"5\r\n{\"a\"\r\n").is_err());
+	}
+
+	#[test]
+	fn should_decode_chunks_splitting_characters() {
+		let body = "\"\u{17c}\"".as_bytes();
+		let chunked = [&b"2\r\n"[..], &body[..2], b"\r\n2\r\n", &body[2..], b"\r\n0\r\n\r\n"].concat();
+
+		assert_eq!(dechunk(&chunked).unwrap(), body);
+	}
+}
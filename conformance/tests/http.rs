@@ -0,0 +1,17 @@
+extern crate jsonrpc_conformance as conformance;
+extern crate jsonrpc_http_server as http;
+
+use http::jsonrpc_core::IoHandler;
+
+#[test]
+fn http_server_should_conform_to_the_spec() {
+	// given
+	let mut io = IoHandler::new();
+	conformance::register(&mut io);
+	let server = http::ServerBuilder::new(io)
+		.start_http(&"127.0.0.1:0".parse().unwrap())
+		.unwrap();
+
+	// then
+	conformance::assert_conforms(&mut conformance::Http::new(*server.address()));
+}
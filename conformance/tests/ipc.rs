@@ -0,0 +1,25 @@
+#![cfg(unix)]
+
+extern crate jsonrpc_conformance as conformance;
+extern crate jsonrpc_ipc_server as ipc;
+extern crate jsonrpc_server_utils as server_utils;
+
+use ipc::jsonrpc_core::IoHandler;
+use server_utils::codecs::Separator;
+
+#[test]
+fn ipc_server_should_conform_to_the_spec() {
+	// given
+	let mut io = IoHandler::new();
+	conformance::register(&mut io);
+	let path = std::env::temp_dir().join(format!("test-ipc-conformance-{}", std::process::id()));
+	let path = path.to_str().expect("Temporary directory path is valid UTF-8");
+	let _server = ipc::ServerBuilder::new(io)
+		.execution(ipc::Execution::Ordered)
+		.request_separators(Separator::Byte(b'\n'), Separator::Byte(b'\n'))
+		.start(path)
+		.unwrap();
+
+	// then
+	conformance::assert_conforms(&mut conformance::Stream::ipc(path).unwrap());
+}
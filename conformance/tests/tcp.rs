@@ -0,0 +1,18 @@
+extern crate jsonrpc_conformance as conformance;
+extern crate jsonrpc_tcp_server as tcp;
+
+use tcp::jsonrpc_core::IoHandler;
+
+#[test]
+fn tcp_server_should_conform_to_the_spec() {
+	// given
+	let mut io = IoHandler::new();
+	conformance::register(&mut io);
+	let server = tcp::ServerBuilder::new(io)
+		.execution(tcp::Execution::Ordered)
+		.start(&"127.0.0.1:0".parse().unwrap())
+		.unwrap();
+
+	// then
+	conformance::assert_conforms(&mut conformance::Stream::tcp(server.address()).unwrap());
+}
@@ -0,0 +1,18 @@
+extern crate jsonrpc_conformance as conformance;
+extern crate jsonrpc_ws_server as ws;
+
+use ws::jsonrpc_core::IoHandler;
+
+#[test]
+fn ws_server_should_conform_to_the_spec() {
+	// given
+	let mut io = IoHandler::new();
+	conformance::register(&mut io);
+	let server = ws::ServerBuilder::new(io)
+		.execution(ws::Execution::Ordered)
+		.start(&"127.0.0.1:0".parse().unwrap())
+		.unwrap();
+
+	// then
+	conformance::assert_conforms(&mut conformance::Ws::connect(server.addr()).unwrap());
+}
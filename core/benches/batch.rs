@@ -0,0 +1,81 @@
+//! Compares allocations and time spent on handling a large batch request
+//! using the raw params fast path and regular `Request` deserialization.
+//!
+//! Run with `cargo bench -p jsonrpc-core`.
+
+extern crate jsonrpc_core;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+use jsonrpc_core::*;
+use jsonrpc_core::futures::Future;
+
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+	unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+		ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+		System.alloc(layout)
+	}
+
+	unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+		System.dealloc(ptr, layout)
+	}
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+/// Sums numbers deserialized directly from raw params.
+struct Sum;
+
+impl RpcMethod<()> for Sum {
+	fn call(&self, params: Params, _meta: ()) -> BoxFuture<Value> {
+		let result = params.parse::<(Vec<u64>,)>().map(|(numbers,)| numbers.iter().sum::<u64>().into());
+		Box::new(futures::done(result))
+	}
+
+	fn call_raw(&self, params: RawParams, _meta: ()) -> BoxFuture<Value> {
+		let result = params.parse::<(Vec<u64>,)>().map(|(numbers,)| numbers.iter().sum::<u64>().into());
+		Box::new(futures::done(result))
+	}
+}
+
+fn measure<F: FnMut() -> Option<String>>(name: &str, iterations: u32, mut f: F) {
+	let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+	let start = Instant::now();
+	for _ in 0..iterations {
+		assert!(f().is_some());
+	}
+	let elapsed = start.elapsed();
+	let allocations = ALLOCATIONS.load(Ordering::Relaxed) - allocations;
+
+	println!(
+		"{:>8}: {:>8} allocations/request, {:>8} us/request",
+		name,
+		allocations / iterations as usize,
+		(elapsed.as_secs() * 1_000_000 + elapsed.subsec_nanos() as u64 / 1_000) / iterations as u64,
+	);
+}
+
+fn main() {
+	let mut io = IoHandler::new();
+	io.add_method_with_meta("sum", Sum);
+
+	let numbers = (0..256).map(|x| x.to_string()).collect::<Vec<_>>().join(",");
+	let request = format!("[{}]", (0..256)
+		.map(|id| format!(r#"{{"jsonrpc":"2.0","method":"sum","params":[[{}]],"id":{}}}"#, numbers, id))
+		.collect::<Vec<_>>()
+		.join(","));
+	let iterations = 20;
+
+	measure("regular", iterations, || {
+		let request: Request = serde_json::from_str(&request).unwrap();
+		io.handle_rpc_request(request).wait().unwrap().map(|response| serde_json::to_string(&response).unwrap())
+	});
+	measure("raw", iterations, || io.handle_request_sync(&request));
+}
@@ -0,0 +1,575 @@
+//! Idempotency keys and response caching middleware.
+//!
+//! Calls carrying an idempotency key (supplied by the transport via `CacheMetadata`
+//! or passed in `idempotencyKey` field of the params) are executed once per client, key and method,
+//! duplicates receive the `Output` of the first call (re-addressed to their own id).
+//! Reusing a key with different params is rejected.
+//! Methods explicitly marked as cacheable get their successful results cached by params
+//! (shared by all clients).
+//!
+//! ```rust
+//! extern crate jsonrpc_core;
+//!
+//! use std::time::Duration;
+//! use jsonrpc_core::*;
+//! use jsonrpc_core::cache::Cache;
+//!
+//! fn main() {
+//! 	let cache = Cache::default().cacheable("get_balance", Duration::from_secs(5));
+//! 	let mut io = MetaIoHandler::with_middleware(cache.clone());
+//! 	io.add_method("get_balance", |_| Ok(Value::from(10)));
+//!
+//! 	let request = r#"{"jsonrpc": "2.0", "method": "get_balance", "params": ["0x1"], "id": 1}"#;
+//! 	io.handle_request_sync(request, ());
+//! 	io.handle_request_sync(request, ());
+//! 	assert_eq!(cache.stats().hits, 1);
+//! }
+//! ```
+
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use futures::{future, Future};
+use futures::future::Shared;
+use futures::sync::oneshot;
+use serde_json;
+
+use calls::Metadata;
+use middleware::Middleware;
+use types::{Call, Error, ErrorCode, Id, MethodCall, Output, Params, Request, Response, Version};
+
+/// Name of the params field carrying the idempotency key.
+pub const IDEMPOTENCY_KEY_FIELD: &str = "idempotencyKey";
+
+/// Metadata that can carry an idempotency key.
+pub trait CacheMetadata: Metadata {
+	/// Idempotency key supplied by the transport (e.g. `Idempotency-Key` HTTP header).
+	/// Takes precedence over the key passed in params.
+	fn idempotency_key(&self) -> Option<String> {
+		None
+	}
+
+	/// Identifies the client (e.g. authenticated user), idempotency keys of different clients never collide.
+	/// Clients without an id share the keys, so it should be provided if they don't trust each other.
+	fn client_id(&self) -> Option<String> {
+		None
+	}
+}
+
+impl CacheMetadata for () {}
+
+impl<T: CacheMetadata> CacheMetadata for Option<T> {
+	fn idempotency_key(&self) -> Option<String> {
+		self.as_ref().and_then(|meta| meta.idempotency_key())
+	}
+
+	fn client_id(&self) -> Option<String> {
+		self.as_ref().and_then(|meta| meta.client_id())
+	}
+}
+
+/// Cache statistics.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CacheStats {
+	/// Calls answered from the cache (or joined with a call in progress).
+	pub hits: usize,
+	/// Calls that had to be executed.
+	pub misses: usize,
+	/// Entries removed to respect the size limit.
+	pub evictions: usize,
+	/// Current number of entries (including calls in progress).
+	pub entries: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Key {
+	/// Client, method and idempotency key.
+	Idempotent(Option<String>, String, String),
+	/// Method and serialized params.
+	Call(String, String),
+}
+
+enum Entry {
+	Pending(Shared<oneshot::Receiver<Output>>),
+	Ready(Output, Instant),
+}
+
+struct Cached {
+	/// Hash of the params of the call that created the entry.
+	params: u64,
+	entry: Entry,
+}
+
+impl Cached {
+	fn is_live(&self, now: Instant) -> bool {
+		match self.entry {
+			Entry::Ready(_, expires) => expires > now,
+			Entry::Pending(_) => true,
+		}
+	}
+}
+
+#[derive(Default)]
+struct State {
+	entries: HashMap<Key, Cached>,
+	hits: usize,
+	misses: usize,
+	evictions: usize,
+}
+
+type SharedState = Arc<Mutex<State>>;
+
+fn lock(state: &SharedState) -> MutexGuard<State> {
+	state.lock().expect("Lock is never poisoned; qed")
+}
+
+/// Idempotency keys and response caching middleware.
+///
+/// Clones share the cache, so a clone can be kept to read the statistics.
+#[derive(Clone)]
+pub struct Cache {
+	idempotency_ttl: Duration,
+	cacheable: HashMap<String, Duration>,
+	max_entries: usize,
+	state: SharedState,
+}
+
+impl Default for Cache {
+	fn default() -> Self {
+		Cache {
+			idempotency_ttl: Duration::from_secs(10 * 60),
+			cacheable: Default::default(),
+			max_entries: 1024,
+			state: Default::default(),
+		}
+	}
+}
+
+impl fmt::Debug for Cache {
+	fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+		fmt.debug_struct("Cache")
+			.field("idempotency_ttl", &self.idempotency_ttl)
+			.field("cacheable", &self.cacheable)
+			.field("max_entries", &self.max_entries)
+			.field("stats", &self.stats())
+			.finish()
+	}
+}
+
+impl Cache {
+	/// How long outputs of calls with idempotency keys are kept (10 minutes by default).
+	pub fn idempotency_ttl(mut self, ttl: Duration) -> Self {
+		self.idempotency_ttl = ttl;
+		self
+	}
+
+	/// Marks a method as cacheable: its successful results are reused
+	/// for calls with the same params for given time.
+	/// Note the name is matched as called, aliases need to be marked separately.
+	pub fn cacheable<T: Into<String>>(mut self, method: T, ttl: Duration) -> Self {
+		self.cacheable.insert(method.into(), ttl);
+		self
+	}
+
+	/// Maximal number of cached entries (1024 by default).
+	/// When the limit is reached, the entry closest to expiration is evicted.
+	pub fn max_entries(mut self, max_entries: usize) -> Self {
+		assert!(max_entries > 0, "Cache needs room for at least one entry.");
+		self.max_entries = max_entries;
+		self
+	}
+
+	/// Returns current statistics.
+	pub fn stats(&self) -> CacheStats {
+		let state = lock(&self.state);
+		CacheStats {
+			hits: state.hits,
+			misses: state.misses,
+			evictions: state.evictions,
+			entries: state.entries.len(),
+		}
+	}
+
+	/// Returns the cache key of given call, hash of its params, time to live and whether failures should be cached.
+	fn key<M: CacheMetadata>(&self, call: &MethodCall, meta: &M) -> Option<(Key, u64, Duration, bool)> {
+		let params = || serde_json::to_string(&call.params).expect("Params serialization is infallible; qed");
+		let idempotency_key = meta.idempotency_key().or_else(|| match call.params {
+			Some(Params::Map(ref map)) => map.get(IDEMPOTENCY_KEY_FIELD).and_then(|key| key.as_str()).map(Into::into),
+			_ => None,
+		});
+		if let Some(key) = idempotency_key {
+			let key = Key::Idempotent(meta.client_id(), call.method.clone(), key);
+			return Some((key, hash(&params()), self.idempotency_ttl, true));
+		}
+
+		self.cacheable.get(&call.method).map(|ttl| {
+			let params = params();
+			let hash = hash(&params);
+			(Key::Call(call.method.clone(), params), hash, *ttl, false)
+		})
+	}
+
+	/// Makes room for a new entry, returns `false` if all entries are calls in progress.
+	fn make_room(&self, state: &mut State, now: Instant) -> bool {
+		if state.entries.len() < self.max_entries {
+			return true;
+		}
+
+		state.entries.retain(|_, cached| cached.is_live(now));
+		if state.entries.len() < self.max_entries {
+			return true;
+		}
+
+		let oldest = state.entries.iter()
+			.filter_map(|(key, cached)| match cached.entry {
+				Entry::Ready(_, expires) => Some((expires, key)),
+				Entry::Pending(_) => None,
+			})
+			.min_by_key(|&(expires, _)| expires)
+			.map(|(_, key)| key.clone());
+
+		match oldest {
+			Some(key) => {
+				state.entries.remove(&key);
+				state.evictions += 1;
+				true
+			},
+			None => false,
+		}
+	}
+}
+
+/// Stores the output of a call in progress, removes the entry if the call never completes.
+struct PendingCall {
+	key: Option<Key>,
+	params: u64,
+	ttl: Duration,
+	cache_failures: bool,
+	state: SharedState,
+	sender: Option<oneshot::Sender<Output>>,
+}
+
+impl PendingCall {
+	fn complete(mut self, response: &Option<Response>) {
+		let output = match *response {
+			Some(Response::Single(ref output)) => output.clone(),
+			_ => return,
+		};
+		let key = self.key.take().expect("Key is taken only once; qed");
+
+		let failed = match output {
+			Output::Failure(_) => true,
+			_ => false,
+		};
+		let mut state = lock(&self.state);
+		if self.cache_failures || !failed {
+			state.entries.insert(key, Cached {
+				params: self.params,
+				entry: Entry::Ready(output.clone(), Instant::now() + self.ttl),
+			});
+		} else {
+			state.entries.remove(&key);
+		}
+
+		if let Some(sender) = self.sender.take() {
+			// duplicates might have been dropped already.
+			let _ = sender.send(output);
+		}
+	}
+}
+
+impl Drop for PendingCall {
+	fn drop(&mut self) {
+		if let Some(key) = self.key.take() {
+			let mut state = lock(&self.state);
+			let pending = match state.entries.get(&key) {
+				Some(&Cached { entry: Entry::Pending(_), .. }) => true,
+				_ => false,
+			};
+			if pending {
+				state.entries.remove(&key);
+			}
+		}
+	}
+}
+
+fn hash(params: &str) -> u64 {
+	let mut hasher = DefaultHasher::new();
+	params.hash(&mut hasher);
+	hasher.finish()
+}
+
+fn with_id(output: Output, id: Id, jsonrpc: Option<Version>) -> Output {
+	match output {
+		Output::Success(s) => Output::Success(::types::Success { id: id, jsonrpc: jsonrpc, ..s }),
+		Output::Failure(f) => Output::Failure(::types::Failure { id: id, jsonrpc: jsonrpc, ..f }),
+	}
+}
+
+impl<M: CacheMetadata> Middleware<M> for Cache {
+	type Future = Box<Future<Item = Option<Response>, Error = ()> + Send>;
+
+	fn on_request<F, X>(&self, request: Request, meta: M, next: F) -> Self::Future where
+		F: FnOnce(Request, M) -> X + Send,
+		X: Future<Item = Option<Response>, Error = ()> + Send + 'static,
+	{
+		// Only single calls are cached, batches are always executed.
+		let lookup = match request {
+			Request::Single(Call::MethodCall(ref call)) => self.key(call, &meta)
+				.map(|(key, params, ttl, cache_failures)| (key, params, ttl, cache_failures, call.id.clone(), call.jsonrpc)),
+			_ => None,
+		};
+		let (key, params, ttl, cache_failures, id, jsonrpc) = match lookup {
+			Some(lookup) => lookup,
+			None => return Box::new(next(request, meta)),
+		};
+
+		let now = Instant::now();
+		let mut state = lock(&self.state);
+		let cached = match state.entries.get(&key) {
+			Some(cached) if cached.is_live(now) && cached.params != params => {
+				let error = Error {
+					code: ErrorCode::InvalidParams,
+					message: "Idempotency key was already used with different params.".into(),
+					data: None,
+				};
+				return Box::new(future::ok(Some(Response::Single(Output::from(Err(error), id, jsonrpc)))));
+			},
+			Some(&Cached { entry: Entry::Ready(ref output, expires), .. }) if expires > now => {
+				Some(future::Either::A(future::ok(output.clone())))
+			},
+			Some(&Cached { entry: Entry::Pending(ref pending), .. }) => Some(future::Either::B(pending.clone().then(|result| Ok(match result {
+				Ok(output) => (*output).clone(),
+				// The original call never completed.
+				Err(_) => Output::from(Err(Error::internal_error()), Id::Null, None),
+			})))),
+			_ => None,
+		};
+		if let Some(cached) = cached {
+			state.hits += 1;
+			return Box::new(cached.map(move |output| Some(Response::Single(with_id(output, id, jsonrpc)))));
+		}
+
+		state.misses += 1;
+		if !self.make_room(&mut state, now) {
+			debug!(target: "rpc", "Cache is full of calls in progress, executing {:?} without caching.", key);
+			return Box::new(next(request, meta));
+		}
+
+		let (sender, receiver) = oneshot::channel();
+		state.entries.insert(key.clone(), Cached {
+			params: params,
+			entry: Entry::Pending(receiver.shared()),
+		});
+		drop(state);
+
+		let pending = PendingCall {
+			key: Some(key),
+			params: params,
+			ttl: ttl,
+			cache_failures: cache_failures,
+			state: self.state.clone(),
+			sender: Some(sender),
+		};
+		Box::new(next(request, meta).map(move |response| {
+			pending.complete(&response);
+			response
+		}))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::{Arc, Mutex};
+	use std::sync::atomic::{AtomicUsize, Ordering};
+	use std::time::Duration;
+	use futures::Future;
+	use futures::sync::oneshot;
+	use types::{Error, Value};
+	use calls::Metadata;
+	use io::MetaIoHandler;
+	use super::{Cache, CacheMetadata, CacheStats};
+
+	fn counter<M: CacheMetadata>(io: &mut MetaIoHandler<M, Cache>, name: &str) -> Arc<AtomicUsize> {
+		let calls = Arc::new(AtomicUsize::new(0));
+		let c = calls.clone();
+		io.add_method(name, move |_| Ok(Value::from(c.fetch_add(1, Ordering::SeqCst) + 1)));
+		calls
+	}
+
+	fn call(io: &MetaIoHandler<(), Cache>, method: &str, params: &str, id: u64) -> String {
+		let request = format!(r#"{{"jsonrpc":"2.0","method":"{}","params":{},"id":{}}}"#, method, params, id);
+		io.handle_request_sync(&request, ()).unwrap()
+	}
+
+	#[test]
+	fn should_return_output_of_first_call_for_duplicate_idempotency_keys() {
+		// given
+		let cache = Cache::default();
+		let mut io = MetaIoHandler::with_middleware(cache.clone());
+		let calls = counter(&mut io, "transfer");
+
+		// when
+		let first = call(&io, "transfer", r#"{"idempotencyKey":"a"}"#, 1);
+		let retry = call(&io, "transfer", r#"{"idempotencyKey":"a"}"#, 2);
+		let other = call(&io, "transfer", r#"{"idempotencyKey":"b"}"#, 3);
+		let no_key = call(&io, "transfer", r#"{}"#, 4);
+
+		// then
+		assert_eq!(first, r#"{"jsonrpc":"2.0","result":1,"id":1}"#);
+		assert_eq!(retry, r#"{"jsonrpc":"2.0","result":1,"id":2}"#);
+		assert_eq!(other, r#"{"jsonrpc":"2.0","result":2,"id":3}"#);
+		assert_eq!(no_key, r#"{"jsonrpc":"2.0","result":3,"id":4}"#);
+		assert_eq!(calls.load(Ordering::SeqCst), 3);
+		assert_eq!(cache.stats(), CacheStats { hits: 1, misses: 2, evictions: 0, entries: 2 });
+	}
+
+	#[test]
+	fn should_take_idempotency_key_from_metadata() {
+		#[derive(Clone, Default)]
+		struct Meta(Option<String>);
+		impl Metadata for Meta {}
+		impl CacheMetadata for Meta {
+			fn idempotency_key(&self) -> Option<String> {
+				self.0.clone()
+			}
+		}
+
+		// given
+		let mut io = MetaIoHandler::with_middleware(Cache::default());
+		let calls = counter(&mut io, "transfer");
+		let request = r#"{"jsonrpc":"2.0","method":"transfer","params":[],"id":1}"#;
+
+		// when
+		io.handle_request_sync(request, Meta(Some("a".into())));
+		io.handle_request_sync(request, Meta(Some("a".into())));
+		io.handle_request_sync(request, Meta(None));
+
+		// then
+		assert_eq!(calls.load(Ordering::SeqCst), 2);
+	}
+
+	#[test]
+	fn should_scope_idempotency_keys_by_client() {
+		#[derive(Clone, Default)]
+		struct Meta(&'static str);
+		impl Metadata for Meta {}
+		impl CacheMetadata for Meta {
+			fn client_id(&self) -> Option<String> {
+				Some(self.0.into())
+			}
+		}
+
+		// given
+		let mut io = MetaIoHandler::with_middleware(Cache::default());
+		let calls = counter(&mut io, "transfer");
+		let request = r#"{"jsonrpc":"2.0","method":"transfer","params":{"idempotencyKey":"a"},"id":1}"#;
+
+		// when
+		let alice = io.handle_request_sync(request, Meta("alice"));
+		let bob = io.handle_request_sync(request, Meta("bob"));
+		let retry = io.handle_request_sync(request, Meta("alice"));
+
+		// then
+		assert_eq!(alice, Some(r#"{"jsonrpc":"2.0","result":1,"id":1}"#.into()));
+		assert_eq!(bob, Some(r#"{"jsonrpc":"2.0","result":2,"id":1}"#.into()));
+		assert_eq!(retry, alice);
+		assert_eq!(calls.load(Ordering::SeqCst), 2);
+	}
+
+	#[test]
+	fn should_reject_idempotency_key_reused_with_different_params() {
+		// given
+		let mut io = MetaIoHandler::with_middleware(Cache::default());
+		let calls = counter(&mut io, "transfer");
+
+		// when
+		let first = call(&io, "transfer", r#"{"idempotencyKey":"a","amount":5}"#, 1);
+		let other = call(&io, "transfer", r#"{"idempotencyKey":"a","amount":6}"#, 2);
+
+		// then
+		assert_eq!(first, r#"{"jsonrpc":"2.0","result":1,"id":1}"#);
+		assert_eq!(other, r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Idempotency key was already used with different params."},"id":2}"#);
+		assert_eq!(calls.load(Ordering::SeqCst), 1);
+	}
+
+	#[test]
+	fn should_join_duplicates_of_call_in_progress() {
+		// given
+		let cache = Cache::default();
+		let mut io = MetaIoHandler::with_middleware(cache.clone());
+		let (sender, receiver) = oneshot::channel::<Value>();
+		let receiver = Mutex::new(Some(receiver));
+		io.add_method("transfer", move |_| {
+			receiver.lock().unwrap().take().expect("Called once").map_err(|_| Error::internal_error())
+		});
+		let request = |id| format!(r#"{{"jsonrpc":"2.0","method":"transfer","params":{{"idempotencyKey":"a"}},"id":{}}}"#, id);
+
+		// when
+		let first = io.handle_request(&request(1), ());
+		let retry = io.handle_request(&request(2), ());
+		sender.send(Value::from(5)).unwrap();
+
+		// then
+		assert_eq!(first.wait(), Ok(Some(r#"{"jsonrpc":"2.0","result":5,"id":1}"#.into())));
+		assert_eq!(retry.wait(), Ok(Some(r#"{"jsonrpc":"2.0","result":5,"id":2}"#.into())));
+		assert_eq!(cache.stats().hits, 1);
+	}
+
+	#[test]
+	fn should_cache_successful_results_of_cacheable_methods() {
+		// given
+		let cache = Cache::default()
+			.cacheable("balance", Duration::from_secs(60))
+			.cacheable("failing", Duration::from_secs(60))
+			.cacheable("volatile", Duration::from_secs(0));
+		let mut io = MetaIoHandler::with_middleware(cache.clone());
+		let balance = counter(&mut io, "balance");
+		let volatile = counter(&mut io, "volatile");
+		let other = counter(&mut io, "other");
+		let failing = Arc::new(AtomicUsize::new(0));
+		let f = failing.clone();
+		io.add_method("failing", move |_| {
+			f.fetch_add(1, Ordering::SeqCst);
+			Err(Error::internal_error())
+		});
+
+		// when
+		assert_eq!(call(&io, "balance", r#"{"b":1,"a":2}"#, 1), r#"{"jsonrpc":"2.0","result":1,"id":1}"#);
+		assert_eq!(call(&io, "balance", r#"{"a":2,"b":1}"#, 2), r#"{"jsonrpc":"2.0","result":1,"id":2}"#);
+		assert_eq!(call(&io, "balance", r#"[1]"#, 3), r#"{"jsonrpc":"2.0","result":2,"id":3}"#);
+		for id in 0..2 {
+			call(&io, "failing", "[]", id);
+			call(&io, "volatile", "[]", id);
+			call(&io, "other", "[]", id);
+		}
+
+		// then
+		assert_eq!(balance.load(Ordering::SeqCst), 2);
+		assert_eq!(failing.load(Ordering::SeqCst), 2);
+		assert_eq!(volatile.load(Ordering::SeqCst), 2);
+		assert_eq!(other.load(Ordering::SeqCst), 2);
+		assert_eq!(cache.stats().hits, 1);
+	}
+
+	#[test]
+	fn should_evict_entries_over_the_limit() {
+		// given
+		let cache = Cache::default().max_entries(1).cacheable("balance", Duration::from_secs(60));
+		let mut io = MetaIoHandler::with_middleware(cache.clone());
+		let balance = counter(&mut io, "balance");
+
+		// when
+		call(&io, "balance", "[1]", 1);
+		call(&io, "balance", "[2]", 2);
+		call(&io, "balance", "[1]", 3);
+
+		// then
+		assert_eq!(balance.load(Ordering::SeqCst), 3);
+		assert_eq!(cache.stats(), CacheStats { hits: 0, misses: 3, evictions: 2, entries: 1 });
+	}
+}
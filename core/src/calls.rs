@@ -1,8 +1,9 @@
 use std::fmt;
 use std::sync::Arc;
-use types::{Params, Value, Error};
-use futures::{Future, IntoFuture};
-use BoxFuture;
+use serde_json;
+use types::{Params, RawParams, RawValue, Value, Error};
+use futures::{self, Future, IntoFuture, Stream};
+use {BoxFuture, BoxStream};
 
 /// Metadata trait
 pub trait Metadata: Clone + Send + 'static {}
@@ -23,6 +24,49 @@ pub trait RpcMethodSimple: Send + Sync + 'static {
 pub trait RpcMethod<T: Metadata>: Send + Sync + 'static {
 	/// Call method
 	fn call(&self, params: Params, meta: T) -> BoxFuture<Value>;
+
+	/// Call method with params borrowed from the request as raw JSON.
+	/// Has to deserialize the params before returning.
+	/// Default implementation converts them to `Params` and invokes `call`.
+	fn call_raw(&self, params: RawParams, meta: T) -> BoxFuture<Value> {
+		match params.to_params() {
+			Ok(params) => self.call(params, meta),
+			Err(err) => Box::new(futures::failed(err)),
+		}
+	}
+
+	/// Does the method return results already serialized to JSON?
+	/// If so, requests are handled with `call_with_raw_result` instead of `call`.
+	fn has_raw_result(&self) -> bool {
+		false
+	}
+
+	/// Call method returning pre-serialized JSON result,
+	/// which is spliced into the response without re-parsing.
+	/// Default implementation serializes the result of `call`.
+	fn call_with_raw_result(&self, params: Params, meta: T) -> BoxFuture<Box<RawValue>> {
+		Box::new(self.call(params, meta).and_then(|value| {
+			serde_json::to_string(&value)
+				.and_then(RawValue::from_string)
+				.map_err(|_| Error::internal_error())
+		}))
+	}
+
+	/// JSON Schema of params accepted by the method (if any).
+	fn params_schema(&self) -> Option<&Value> {
+		None
+	}
+
+	/// JSON Schema of the result returned by the method (if any).
+	fn result_schema(&self) -> Option<&Value> {
+		None
+	}
+}
+
+/// Method returning results as a stream of values
+pub trait RpcStreamingMethod<T: Metadata>: Send + Sync + 'static {
+	/// Call method
+	fn call(&self, params: Params, meta: T) -> BoxStream<Value>;
 }
 
 /// Notification
@@ -42,17 +86,101 @@ pub trait RpcNotification<T: Metadata>: Send + Sync + 'static {
 pub enum RemoteProcedure<T: Metadata> {
 	/// A method call
 	Method(Arc<RpcMethod<T>>),
+	/// A method call producing a stream of results
+	StreamingMethod(Arc<RpcStreamingMethod<T>>),
 	/// A notification
 	Notification(Arc<RpcNotification<T>>),
 	/// An alias to other method,
 	Alias(String),
 }
 
+impl<T: Metadata> RemoteProcedure<T> {
+	/// Attaches JSON Schemas of params and result to a method.
+	/// Schemas that are not provided are inherited from the method itself.
+	/// Notifications and aliases are returned unchanged.
+	pub fn with_schema(self, params: Option<Value>, result: Option<Value>) -> Self {
+		match self {
+			RemoteProcedure::Method(method) => RemoteProcedure::Method(Arc::new(SchemaMethod {
+				method,
+				params,
+				result,
+			})),
+			procedure => procedure,
+		}
+	}
+}
+
+struct SchemaMethod<T: Metadata> {
+	method: Arc<RpcMethod<T>>,
+	params: Option<Value>,
+	result: Option<Value>,
+}
+
+impl<T: Metadata> RpcMethod<T> for SchemaMethod<T> {
+	fn call(&self, params: Params, meta: T) -> BoxFuture<Value> {
+		self.method.call(params, meta)
+	}
+
+	fn call_raw(&self, params: RawParams, meta: T) -> BoxFuture<Value> {
+		self.method.call_raw(params, meta)
+	}
+
+	fn has_raw_result(&self) -> bool {
+		self.method.has_raw_result()
+	}
+
+	fn call_with_raw_result(&self, params: Params, meta: T) -> BoxFuture<Box<RawValue>> {
+		self.method.call_with_raw_result(params, meta)
+	}
+
+	fn params_schema(&self) -> Option<&Value> {
+		self.params.as_ref().or_else(|| self.method.params_schema())
+	}
+
+	fn result_schema(&self) -> Option<&Value> {
+		self.result.as_ref().or_else(|| self.method.result_schema())
+	}
+}
+
+/// Method returning pre-serialized JSON.
+pub struct RawResultMethod<F> {
+	closure: F,
+}
+
+impl<F> RawResultMethod<F> {
+	/// Wraps given closure.
+	pub fn new(closure: F) -> Self {
+		RawResultMethod { closure }
+	}
+}
+
+impl<F: Send + Sync + 'static, X: Send + 'static, T, I> RpcMethod<T> for RawResultMethod<F> where
+	T: Metadata,
+	F: Fn(Params, T) -> I,
+	I: IntoFuture<Item = Box<RawValue>, Error = Error, Future = X>,
+	X: Future<Item = Box<RawValue>, Error = Error>,
+{
+	fn call(&self, params: Params, meta: T) -> BoxFuture<Value> {
+		Box::new(self.call_with_raw_result(params, meta).and_then(|raw| {
+			serde_json::from_str(raw.get()).map_err(|_| Error::internal_error())
+		}))
+	}
+
+	fn has_raw_result(&self) -> bool {
+		true
+	}
+
+	fn call_with_raw_result(&self, params: Params, meta: T) -> BoxFuture<Box<RawValue>> {
+		Box::new((self.closure)(params, meta).into_future())
+	}
+}
+
 impl<T: Metadata> fmt::Debug for RemoteProcedure<T> {
 	fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
 		use self::RemoteProcedure::*;
 		match *self {
 			Method(..) => write!(fmt, "<method>"),
+			StreamingMethod(..) => write!(fmt, "<streaming method>"),
 			Notification(..) => write!(fmt, "<notification>"),
 			Alias(ref alias) => write!(fmt, "alias => {:?}", alias)
 		}
@@ -89,6 +217,16 @@ impl<F: Send + Sync + 'static, X: Send + 'static, T, I> RpcMethod<T> for F where
 	}
 }
 
+impl<F: Send + Sync + 'static, T, I> RpcStreamingMethod<T> for F where
+	T: Metadata,
+	F: Fn(Params, T) -> I,
+	I: Stream<Item = Value, Error = Error> + Send + 'static,
+{
+	fn call(&self, params: Params, meta: T) -> BoxStream<Value> {
+		Box::new(self(params, meta))
+	}
+}
+
 impl<F: Send + Sync + 'static, T> RpcNotification<T> for F where
 	T: Metadata,
 	F: Fn(Params, T),
@@ -0,0 +1,140 @@
+//! Deprecation notices for methods and aliases.
+
+use std::sync::atomic::{self, AtomicUsize};
+
+use types::{Error, Value};
+
+/// Describes why a method (or alias) is deprecated and when it is going away.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Deprecation {
+	/// Human-readable explanation, usually pointing to the replacement.
+	pub message: String,
+	/// Version in which the method is going to be removed.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub sunset: Option<String>,
+	/// Should the notice be attached to `data` of errors returned by the method?
+	#[serde(skip_serializing)]
+	pub error_data: bool,
+}
+
+impl Deprecation {
+	/// Creates new deprecation notice with given message.
+	pub fn new<T: Into<String>>(message: T) -> Self {
+		Deprecation {
+			message: message.into(),
+			sunset: None,
+			error_data: false,
+		}
+	}
+
+	/// Sets the version in which the method is going to be removed.
+	pub fn sunset<T: Into<String>>(mut self, version: T) -> Self {
+		self.sunset = Some(version.into());
+		self
+	}
+
+	/// Attach the notice to `data` of errors returned by the method.
+	/// Errors that already carry `data` are left untouched.
+	pub fn with_error_data(mut self) -> Self {
+		self.error_data = true;
+		self
+	}
+
+	/// Returns a warning string describing this notice.
+	pub fn warning(&self, name: &str) -> String {
+		match self.sunset {
+			Some(ref sunset) => format!("Method {} is deprecated and will be removed in {}: {}", name, sunset, self.message),
+			None => format!("Method {} is deprecated: {}", name, self.message),
+		}
+	}
+
+	/// Attaches this notice to given error (if requested and possible).
+	pub fn annotate(&self, mut error: Error) -> Error {
+		if self.error_data && error.data.is_none() {
+			let mut data = ::serde_json::Map::new();
+			data.insert("deprecated".into(), ::serde_json::to_value(self).expect("Deprecation serialization is infallible; qed"));
+			error.data = Some(Value::Object(data));
+		}
+		error
+	}
+}
+
+/// Deprecation notice together with usage counter.
+#[derive(Debug)]
+pub struct Deprecated {
+	notice: Deprecation,
+	calls: AtomicUsize,
+}
+
+impl Deprecated {
+	/// Creates new entry with zero usage count.
+	pub fn new(notice: Deprecation) -> Self {
+		Deprecated {
+			notice,
+			calls: AtomicUsize::new(0),
+		}
+	}
+
+	/// Returns the deprecation notice.
+	pub fn notice(&self) -> &Deprecation {
+		&self.notice
+	}
+
+	/// Returns how many times deprecated method was invoked.
+	pub fn calls(&self) -> usize {
+		self.calls.load(atomic::Ordering::Relaxed)
+	}
+
+	/// Logs and counts a single usage of deprecated method.
+	pub fn record(&self, name: &str) {
+		let calls = self.calls.fetch_add(1, atomic::Ordering::Relaxed) + 1;
+		warn!(target: "rpc", "{} (called {} times)", self.notice.warning(name), calls);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use types::{Error, ErrorCode};
+	use super::Deprecation;
+
+	#[test]
+	fn should_attach_notice_to_error_data() {
+		// given
+		let notice = Deprecation::new("Use `say_hello` instead.").sunset("2.0").with_error_data();
+
+		// when
+		let error = notice.annotate(Error::new(ErrorCode::InternalError));
+
+		// then
+		assert_eq!(
+			::serde_json::to_string(&error).unwrap(),
+			r#"{"code":-32603,"message":"Internal error","data":{"deprecated":{"message":"Use `say_hello` instead.","sunset":"2.0"}}}"#
+		);
+	}
+
+	#[test]
+	fn should_not_override_existing_data() {
+		// given
+		let notice = Deprecation::new("Use `say_hello` instead.").with_error_data();
+		let mut error = Error::internal_error();
+		error.data = Some("details".into());
+
+		// when
+		let error = notice.annotate(error);
+
+		// then
+		assert_eq!(error.data, Some("details".into()));
+	}
+
+	#[test]
+	fn should_format_warning() {
+		assert_eq!(
+			Deprecation::new("Use `b` instead.").warning("a"),
+			"Method a is deprecated: Use `b` instead."
+		);
+		assert_eq!(
+			Deprecation::new("Use `b` instead.").sunset("2.0").warning("a"),
+			"Method a is deprecated and will be removed in 2.0: Use `b` instead."
+		);
+	}
+}
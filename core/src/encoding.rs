@@ -0,0 +1,207 @@
+//! Encodings of requests and responses.
+//!
+//! JSON is always available, binary encodings are enabled with `cbor` and `msgpack` features.
+
+use std::fmt;
+use std::sync::Arc;
+
+use serde_json;
+#[cfg(feature = "cbor")]
+use serde_cbor;
+#[cfg(feature = "msgpack")]
+use rmp_serde;
+
+use types::{Error, Request, Response, Value};
+
+/// Format used to serialize requests and responses.
+pub trait Encoding: fmt::Debug + Send + Sync + 'static {
+	/// MIME type of encoded messages (used for HTTP content negotiation).
+	fn content_type(&self) -> &'static str;
+
+	/// Deserializes a request.
+	fn decode(&self, request: &[u8]) -> Result<Request, Error>;
+
+	/// Serializes a response.
+	fn encode(&self, response: &Response) -> Vec<u8>;
+
+	/// Deserializes any other message (e.g. a response sent by the client).
+	fn decode_message(&self, message: &[u8]) -> Result<Value, Error>;
+
+	/// Serializes any other message (e.g. a notification sent to the client).
+	fn encode_message(&self, message: &Value) -> Vec<u8>;
+}
+
+impl<E: Encoding + ?Sized> Encoding for Arc<E> {
+	fn content_type(&self) -> &'static str {
+		(**self).content_type()
+	}
+
+	fn decode(&self, request: &[u8]) -> Result<Request, Error> {
+		(**self).decode(request)
+	}
+
+	fn encode(&self, response: &Response) -> Vec<u8> {
+		(**self).encode(response)
+	}
+
+	fn decode_message(&self, message: &[u8]) -> Result<Value, Error> {
+		(**self).decode_message(message)
+	}
+
+	fn encode_message(&self, message: &Value) -> Vec<u8> {
+		(**self).encode_message(message)
+	}
+}
+
+fn parse_error<E>(_: E) -> Error {
+	Error::parse_error()
+}
+
+// Pre-serialized results (`Success::raw_result`) can only be written by `serde_json`,
+// so binary encodings serialize an intermediate `Value` instead.
+#[cfg(any(feature = "cbor", feature = "msgpack"))]
+fn to_value(response: &Response) -> serde_json::Value {
+	serde_json::to_value(response).expect("Response serialization is infallible; qed")
+}
+
+/// JSON encoding.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Json;
+
+impl Encoding for Json {
+	fn content_type(&self) -> &'static str {
+		"application/json"
+	}
+
+	fn decode(&self, request: &[u8]) -> Result<Request, Error> {
+		serde_json::from_slice(request).map_err(parse_error)
+	}
+
+	fn encode(&self, response: &Response) -> Vec<u8> {
+		serde_json::to_vec(response).expect("Response serialization is infallible; qed")
+	}
+
+	fn decode_message(&self, message: &[u8]) -> Result<Value, Error> {
+		serde_json::from_slice(message).map_err(parse_error)
+	}
+
+	fn encode_message(&self, message: &Value) -> Vec<u8> {
+		serde_json::to_vec(message).expect("Value serialization is infallible; qed")
+	}
+}
+
+/// CBOR encoding (RFC 7049).
+#[cfg(feature = "cbor")]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Cbor;
+
+#[cfg(feature = "cbor")]
+impl Encoding for Cbor {
+	fn content_type(&self) -> &'static str {
+		"application/cbor"
+	}
+
+	fn decode(&self, request: &[u8]) -> Result<Request, Error> {
+		serde_cbor::from_slice(request).map_err(parse_error)
+	}
+
+	fn encode(&self, response: &Response) -> Vec<u8> {
+		self.encode_message(&to_value(response))
+	}
+
+	fn decode_message(&self, message: &[u8]) -> Result<Value, Error> {
+		serde_cbor::from_slice(message).map_err(parse_error)
+	}
+
+	fn encode_message(&self, message: &Value) -> Vec<u8> {
+		serde_cbor::to_vec(message).expect("Value serialization is infallible; qed")
+	}
+}
+
+/// MessagePack encoding.
+///
+/// Structures are encoded as maps (with field names), same as in JSON.
+#[cfg(feature = "msgpack")]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct MessagePack;
+
+#[cfg(feature = "msgpack")]
+impl Encoding for MessagePack {
+	fn content_type(&self) -> &'static str {
+		"application/msgpack"
+	}
+
+	fn decode(&self, request: &[u8]) -> Result<Request, Error> {
+		rmp_serde::from_slice(request).map_err(parse_error)
+	}
+
+	fn encode(&self, response: &Response) -> Vec<u8> {
+		self.encode_message(&to_value(response))
+	}
+
+	fn decode_message(&self, message: &[u8]) -> Result<Value, Error> {
+		rmp_serde::from_slice(message).map_err(parse_error)
+	}
+
+	fn encode_message(&self, message: &Value) -> Vec<u8> {
+		rmp_serde::to_vec(message).expect("Value serialization is infallible; qed")
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use types::{Call, Id, MethodCall, Params, Request, Response, Output, Success, Failure, Error, Version, Value};
+	use super::{Encoding, Json};
+
+	fn request() -> Request {
+		Request::Single(Call::MethodCall(MethodCall {
+			jsonrpc: Some(Version::V2),
+			method: "add".into(),
+			params: Some(Params::Array(vec![Value::from(1), Value::from(2)])),
+			id: Id::Str("a1".into()),
+		}))
+	}
+
+	fn response() -> Response {
+		Response::Batch(vec![
+			Output::Success(Success { jsonrpc: Some(Version::V2), result: Value::from(3), id: Id::Num(1), raw_result: None }),
+			Output::Failure(Failure { jsonrpc: Some(Version::V2), error: Error::method_not_found(), id: Id::Num(2) }),
+		])
+	}
+
+	fn check_decode<E: Encoding>(encoding: E, encoded_request: &[u8]) {
+		assert_eq!(encoding.decode(encoded_request), Ok(request()));
+		assert_eq!(encoding.decode(&[0xc1]), Err(Error::parse_error()));
+	}
+
+	#[test]
+	fn should_encode_json() {
+		check_decode(Json, br#"{"jsonrpc":"2.0","method":"add","params":[1,2],"id":"a1"}"#);
+		assert_eq!(
+			Json.encode(&response()),
+			br#"[{"jsonrpc":"2.0","result":3,"id":1},{"jsonrpc":"2.0","error":{"code":-32601,"message":"Method not found"},"id":2}]"#.to_vec()
+		);
+	}
+
+	#[cfg(feature = "cbor")]
+	#[test]
+	fn should_encode_cbor() {
+		use serde_cbor;
+		use super::Cbor;
+
+		check_decode(Cbor, &serde_cbor::to_vec(&request()).unwrap());
+		let response: Value = serde_cbor::from_slice(&Cbor.encode(&response())).unwrap();
+		assert_eq!(response, ::serde_json::to_value(&self::response()).unwrap());
+	}
+
+	#[cfg(feature = "msgpack")]
+	#[test]
+	fn should_encode_msgpack() {
+		use rmp_serde;
+		use super::MessagePack;
+
+		check_decode(MessagePack, &rmp_serde::to_vec_named(&request()).unwrap());
+		let response: Value = rmp_serde::from_slice(&MessagePack.encode(&response())).unwrap();
+		assert_eq!(response, ::serde_json::to_value(&self::response()).unwrap());
+	}
+}
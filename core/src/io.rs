@@ -1,23 +1,34 @@
 use std::sync::Arc;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::mem;
 use std::ops::{Deref, DerefMut};
 
 use serde_json;
-use futures::{self, future, Future};
+use futures::{self, future, Async, Future, IntoFuture, Poll, Sink, Stream};
+use futures::sync::mpsc;
 
-use calls::{RemoteProcedure, Metadata, RpcMethodSimple, RpcMethod, RpcNotificationSimple, RpcNotification};
+use encoding::Encoding;
+use calls::{RemoteProcedure, Metadata, RpcMethodSimple, RpcMethod, RpcStreamingMethod, RpcNotificationSimple, RpcNotification, RawResultMethod};
+use deprecation::{Deprecation, Deprecated};
+use limits::{Limits, Priority};
 use middleware::{self, Middleware};
-use types::{Params, Error, ErrorCode, Version};
-use types::{Request, Response, Call, Output};
+use rpc_error::{IntoRpcError, ErrorRegistry, DuplicateErrorCode};
+use schema;
+use types::{Params, Error, ErrorCode, Version, Value, RawValue};
+use types::{Request, Response, Call, Notification, Output, Id, RawRequest, RawCall, Success};
+use BoxFuture;
 
 /// A type representing middleware or RPC response before serialization.
 pub type FutureResponse = Box<Future<Item=Option<Response>, Error=()> + Send>;
 
+/// A type representing middleware or RPC response to a parsed request before serialization.
+pub type FutureRpcResponse<F> = future::Either<future::FutureResult<Option<Response>, ()>, future::Either<F, FutureResponse>>;
+
 /// A type representing future string response.
-pub type FutureResult<F> = future::Map<
-	future::Either<future::FutureResult<Option<Response>, ()>, F>,
-	fn(Option<Response>) -> Option<String>,
->;
+pub type FutureResult<F> = future::Map<FutureRpcResponse<F>, fn(Option<Response>) -> Option<String>>;
+
+/// A type representing future response serialized with custom `Encoding`.
+pub type FutureEncoded = Box<Future<Item=Option<Vec<u8>>, Error=()> + Send>;
 
 /// A type representing a result of a single method call.
 pub type FutureOutput = future::Either<
@@ -25,20 +36,144 @@ pub type FutureOutput = future::Either<
 	future::FutureResult<Option<Output>, ()>,
 >;
 
+/// A type representing messages sent in response to a call to a streaming method:
+/// notifications carrying the results followed by the response.
+pub type ResponseStream = Box<Stream<Item=String, Error=()> + Send>;
+
+/// Name of notifications carrying results of streaming methods.
+/// Params are `{"id": <id of the call>, "result": <value>}`.
+pub const STREAM_CHUNK_METHOD: &str = "rpc.chunk";
+
+/// Response to a request handled by `MetaIoHandler::handle_streaming_request`.
+pub enum StreamingResponse<R> {
+	/// Messages sent in response to a call to a streaming method.
+	Stream(ResponseStream),
+	/// Response to any other request.
+	Response(R),
+}
+
+// Number of results a streaming method can produce before it has to wait for them to be sent.
+const STREAM_BUFFER: usize = 16;
+
+type ChunkSender = mpsc::Sender<String>;
+
 /// A type representing a result of a single method call.
 pub type FutureOutput = future::Either<
 	BoxFuture<Option<Output>, ()>,
 	future::FutureResult<Option<Output>, ()>,
 >;
 
+/// Result of a method call before it's turned into `Output`.
+enum MethodResult {
+	Value(Value),
+	Raw(Box<RawValue>),
+}
+
+type MethodFuture = future::Either<
+	future::Map<BoxFuture<Value>, fn(Value) -> MethodResult>,
+	future::Map<BoxFuture<Box<RawValue>>, fn(Box<RawValue>) -> MethodResult>,
+>;
+
+fn call_method<T: Metadata>(method: &RpcMethod<T>, params: Params, meta: T) -> MethodFuture {
+	use self::future::Either::{A, B};
+
+	if method.has_raw_result() {
+		B(method.call_with_raw_result(params, meta).map(MethodResult::Raw as fn(_) -> _))
+	} else {
+		A(method.call(params, meta).map(MethodResult::Value as fn(_) -> _))
+	}
+}
+
+// Adapts streaming method to be called as a regular one.
+// Values are either sent as notifications (and the call returns `null`)
+// or collected into an array if the transport can't deliver them.
+struct StreamedMethod<T: Metadata> {
+	method: Arc<RpcStreamingMethod<T>>,
+	id: Id,
+	jsonrpc: Option<Version>,
+	chunks: Option<ChunkSender>,
+}
+
+impl<T: Metadata> RpcMethod<T> for StreamedMethod<T> {
+	fn call(&self, params: Params, meta: T) -> BoxFuture<Value> {
+		let values = self.method.call(params, meta);
+		let chunks = match self.chunks {
+			Some(ref chunks) => chunks.clone(),
+			None => return Box::new(values.collect().map(Value::Array)),
+		};
+
+		let id = serde_json::to_value(&self.id).expect("Id serialization is infallible; qed");
+		let jsonrpc = self.jsonrpc;
+		Box::new(values.fold(chunks, move |chunks, result| {
+			let mut params = serde_json::Map::new();
+			params.insert("id".into(), id.clone());
+			params.insert("result".into(), result);
+			let notification = Notification {
+				jsonrpc,
+				method: STREAM_CHUNK_METHOD.into(),
+				params: Some(Params::Map(params)),
+			};
+			let notification = serde_json::to_string(&notification).expect("Notification serialization is infallible; qed");
+			// The receiver is only dropped when the response is not needed any more.
+			chunks.send(notification).map_err(|_| Error::internal_error())
+		}).map(|_| Value::Null))
+	}
+}
+
+enum StreamedResponse<F> {
+	Pending(F),
+	Ready(Option<String>),
+	Done,
+}
+
+// Yields all chunks sent by the call before the response.
+struct ChunkedResponse<F> {
+	chunks: mpsc::Receiver<String>,
+	response: StreamedResponse<F>,
+}
+
+impl<F: Future<Item=Option<String>, Error=()>> Stream for ChunkedResponse<F> {
+	type Item = String;
+	type Error = ();
+
+	fn poll(&mut self) -> Poll<Option<String>, ()> {
+		// Chunks are produced only while the call is polled.
+		if let StreamedResponse::Pending(ref mut response) = self.response {
+			if let Async::Ready(response) = response.poll()? {
+				self.response = StreamedResponse::Ready(response);
+			}
+		}
+		if let StreamedResponse::Done = self.response {
+			return Ok(Async::Ready(None));
+		}
+
+		match self.chunks.poll()? {
+			Async::Ready(Some(chunk)) => return Ok(Async::Ready(Some(chunk))),
+			Async::Ready(None) => {},
+			Async::NotReady => return Ok(Async::NotReady),
+		}
+
+		match mem::replace(&mut self.response, StreamedResponse::Done) {
+			StreamedResponse::Ready(response) => Ok(Async::Ready(response)),
+			StreamedResponse::Pending(response) => {
+				self.response = StreamedResponse::Pending(response);
+				Ok(Async::NotReady)
+			},
+			StreamedResponse::Done => Ok(Async::Ready(None)),
+		}
+	}
+}
+
 /// `IoHandler` json-rpc protocol compatibility
 #[derive(Debug, Clone, Copy)]
 pub enum Compatibility {
 	/// Compatible only with JSON-RPC 1.x
+	/// (responses carry both `result` and `error`, calls with `null` id are notifications)
 	V1,
 	/// Compatible only with JSON-RPC 2.0
 	V2,
 	/// Compatible with both
+	/// (calls without `jsonrpc` follow JSON-RPC 1.x rules)
 	Both,
 }
 
@@ -58,6 +193,15 @@ impl Compatibility {
 		}
 	}
 
+	// JSON-RPC 1.0 notifications are calls with `null` id.
+	fn is_v1_notification(&self, jsonrpc: Option<Version>, id: &Id) -> bool {
+		match (*self, jsonrpc, id) {
+			(Compatibility::V1, None, &Id::Null) |
+			(Compatibility::Both, None, &Id::Null) => true,
+			_ => false,
+		}
+	}
+
 	fn default_version(&self) -> Option<Version> {
 		match *self {
 			Compatibility::V1 => None,
@@ -74,6 +218,11 @@ pub struct MetaIoHandler<T: Metadata, S: Middleware<T> = middleware::Noop> {
 	middleware: S,
 	compatibility: Compatibility,
 	methods: HashMap<String, RemoteProcedure<T>>,
+	deprecated: HashMap<String, Deprecated>,
+	read_only: HashSet<String>,
+	discovery: Option<String>,
+	errors: ErrorRegistry,
+	limits: Limits,
 }
 
 impl<T: Metadata> Default for MetaIoHandler<T> {
@@ -89,6 +238,11 @@ impl<T: Metadata> MetaIoHandler<T> {
 			compatibility: compatibility,
 			middleware: Default::default(),
 			methods: Default::default(),
+			deprecated: Default::default(),
+			read_only: Default::default(),
+			discovery: None,
+			errors: Default::default(),
+			limits: Default::default(),
 		}
 	}
 }
@@ -101,6 +255,11 @@ impl<T: Metadata, S: Middleware<T>> MetaIoHandler<T, S> {
 			compatibility: compatibility,
 			middleware: middleware,
 			methods: Default::default(),
+			deprecated: Default::default(),
+			read_only: Default::default(),
+			discovery: None,
+			errors: Default::default(),
+			limits: Default::default(),
 		}
 	}
 
@@ -110,6 +269,11 @@ impl<T: Metadata, S: Middleware<T>> MetaIoHandler<T, S> {
 			compatibility: Default::default(),
 			middleware: middleware,
 			methods: Default::default(),
+			deprecated: Default::default(),
+			read_only: Default::default(),
+			discovery: None,
+			errors: Default::default(),
+			limits: Default::default(),
 		}
 	}
 
@@ -121,6 +285,178 @@ impl<T: Metadata, S: Middleware<T>> MetaIoHandler<T, S> {
 		);
 	}
 
+	/// Adds an alias to a method and marks the alias as deprecated.
+	pub fn add_deprecated_alias(&mut self, alias: &str, other: &str, deprecation: Deprecation) {
+		self.add_alias(alias, other);
+		self.deprecate(alias, deprecation);
+	}
+
+	/// Marks a method, notification or alias as deprecated.
+	/// Calls are still processed, but every usage is logged and counted.
+	pub fn deprecate(&mut self, name: &str, deprecation: Deprecation) {
+		self.deprecated.insert(name.into(), Deprecated::new(deprecation));
+	}
+
+	/// Reports deprecation notices that apply to calls of given method:
+	/// the notice of the method itself and, for an alias, the notice of the aliased method.
+	pub fn deprecations<D>(&self, name: &str, mut report: D) where
+		D: FnMut(&str, &Deprecation),
+	{
+		self.deprecated_entries(name, |name, deprecated| report(name, deprecated.notice()));
+	}
+
+	fn deprecated_entries<D>(&self, name: &str, mut report: D) where
+		D: FnMut(&str, &Deprecated),
+	{
+		if self.deprecated.is_empty() {
+			return;
+		}
+
+		let target = match self.methods.get(name) {
+			Some(&RemoteProcedure::Alias(ref alias)) => Some(alias.as_str()),
+			_ => None,
+		};
+		for name in Some(name).into_iter().chain(target) {
+			if let Some(deprecated) = self.deprecated.get(name) {
+				report(name, deprecated);
+			}
+		}
+	}
+
+	/// Returns deprecation notice of given method or alias (if any).
+	pub fn deprecation(&self, name: &str) -> Option<&Deprecation> {
+		self.deprecated.get(name).map(Deprecated::notice)
+	}
+
+	/// Returns how many times given deprecated method or alias was invoked.
+	pub fn deprecated_calls(&self, name: &str) -> usize {
+		self.deprecated.get(name).map(Deprecated::calls).unwrap_or(0)
+	}
+
+	/// Marks a method or alias as read-only (free of side effects),
+	/// so transports may expose it to safe requests, e.g. HTTP GET.
+	pub fn mark_read_only(&mut self, name: &str) {
+		self.read_only.insert(name.into());
+	}
+
+	/// Returns `true` if given method, or the target of given alias, is read-only.
+	pub fn is_read_only(&self, name: &str) -> bool {
+		if self.read_only.contains(name) {
+			return true;
+		}
+
+		match self.methods.get(name) {
+			Some(&RemoteProcedure::Alias(ref target)) => self.read_only.contains(target),
+			_ => false,
+		}
+	}
+
+	/// Attaches JSON Schema of params to a method (or the method aliased by given name).
+	/// Params are validated before the method is invoked.
+	pub fn set_params_schema(&mut self, name: &str, schema: Value) -> Result<(), schema::UnknownMethod> {
+		schema::attach(&mut self.methods, name, Some(schema), None)
+	}
+
+	/// Attaches JSON Schema of the result to a method (or the method aliased by given name).
+	/// Results are only validated in debug builds.
+	pub fn set_result_schema(&mut self, name: &str, schema: Value) -> Result<(), schema::UnknownMethod> {
+		schema::attach(&mut self.methods, name, None, Some(schema))
+	}
+
+	/// Registers error codes used by given module (see `IntoRpcError::error_codes`).
+	/// Fails if any of the codes is already used by other module.
+	pub fn register_errors<E: IntoRpcError>(&mut self, module: &str) -> Result<(), DuplicateErrorCode> {
+		self.errors.register::<E>(module)
+	}
+
+	/// Returns registry of error codes used by the methods.
+	pub fn error_registry(&self) -> &ErrorRegistry {
+		&self.errors
+	}
+
+	/// Limits the number of method calls in flight (not counting notifications).
+	/// Calls over the limit are rejected with `Error::server_busy`, unless the method has `Priority::Critical`.
+	pub fn set_max_in_flight(&mut self, limit: usize) {
+		self.limits.set_max_in_flight(limit);
+	}
+
+	/// Limits the number of concurrent calls to given method (including its aliases).
+	pub fn set_concurrency_limit(&mut self, name: &str, limit: usize) {
+		self.limits.set_method_limit(name, limit);
+	}
+
+	/// Sets priority class of given method.
+	/// Priority of an alias takes precedence over the priority of the method it points to.
+	pub fn set_priority(&mut self, name: &str, priority: Priority) {
+		self.limits.set_priority(name, priority);
+	}
+
+	/// Delay clients are advised to wait before retrying rejected calls (1 second by default).
+	/// Passed in `retryAfter` field of the error data (in seconds, rounded up).
+	pub fn set_retry_after(&mut self, delay: ::std::time::Duration) {
+		self.limits.set_retry_after(delay);
+	}
+
+	/// Returns the number of method calls in flight.
+	pub fn calls_in_flight(&self) -> usize {
+		self.limits.in_flight()
+	}
+
+	/// Returns the number of calls rejected because of the limits.
+	pub fn rejected_calls(&self) -> usize {
+		self.limits.rejected()
+	}
+
+	/// Exposes a method with given name returning a description
+	/// of all supported methods, notifications and aliases (see `discover`).
+	pub fn add_discovery_method(&mut self, name: &str) {
+		self.discovery = Some(name.into());
+	}
+
+	/// Describes all supported methods, notifications and aliases
+	/// together with registered error codes.
+	pub fn discover(&self) -> Value {
+		let mut methods = serde_json::Map::new();
+		for (name, procedure) in &self.methods {
+			let mut description = serde_json::Map::new();
+			match *procedure {
+				RemoteProcedure::Method(ref method) => {
+					description.insert("kind".into(), "method".into());
+					if let Some(schema) = method.params_schema() {
+						description.insert("params".into(), schema.clone());
+					}
+					if let Some(schema) = method.result_schema() {
+						description.insert("result".into(), schema.clone());
+					}
+				},
+				RemoteProcedure::StreamingMethod(_) => {
+					description.insert("kind".into(), "streaming method".into());
+				},
+				RemoteProcedure::Notification(_) => {
+					description.insert("kind".into(), "notification".into());
+				},
+				RemoteProcedure::Alias(ref target) => {
+					description.insert("kind".into(), "alias".into());
+					description.insert("target".into(), target.clone().into());
+				},
+			}
+			if self.is_read_only(name) {
+				description.insert("readOnly".into(), true.into());
+			}
+			if let Some(deprecation) = self.deprecation(name) {
+				description.insert("deprecated".into(), serde_json::to_value(deprecation).expect("Deprecation serialization is infallible; qed"));
+			}
+			methods.insert(name.clone(), Value::Object(description));
+		}
+
+		let mut discovery = serde_json::Map::new();
+		discovery.insert("methods".into(), Value::Object(methods));
+		if !self.errors.is_empty() {
+			discovery.insert("errors".into(), self.errors.to_value());
+		}
+		Value::Object(discovery)
+	}
+
 	/// Adds new supported asynchronous method
 	pub fn add_method<F>(&mut self, name: &str, method: F) where
 		F: RpcMethodSimple,
@@ -147,6 +483,27 @@ impl<T: Metadata, S: Middleware<T>> MetaIoHandler<T, S> {
 		);
 	}
 
+	/// Adds new supported method producing a stream of results.
+	/// See `handle_streaming_request` for details on how the results are delivered.
+	pub fn add_streaming_method<F>(&mut self, name: &str, method: F) where
+		F: RpcStreamingMethod<T>,
+	{
+		self.methods.insert(
+			name.into(),
+			RemoteProcedure::StreamingMethod(Arc::new(method)),
+		);
+	}
+
+	/// Adds new supported asynchronous method returning results already serialized to JSON.
+	/// The result is written to the response as-is, without going through `Value`.
+	pub fn add_method_with_raw_result<F, I>(&mut self, name: &str, method: F) where
+		F: Fn(Params, T) -> I + Send + Sync + 'static,
+		I: IntoFuture<Item = Box<RawValue>, Error = Error>,
+		I::Future: Send + 'static,
+	{
+		self.add_method_with_meta(name, RawResultMethod::new(method));
+	}
+
 	/// Adds new supported notification with metadata support.
 	pub fn add_notification_with_meta<F>(&mut self, name: &str, notification: F) where
 		F: RpcNotification<T>,
@@ -173,107 +530,381 @@ impl<T: Metadata, S: Middleware<T>> MetaIoHandler<T, S> {
 
 	/// Handle given request asynchronously.
 	pub fn handle_request(&self, request: &str, meta: T) -> FutureResult<S::Future> {
-		use self::future::Either::{A, B};
-		fn as_string(response: Option<Response>) -> Option<String> {
-			let res = response.map(write_response);
-			debug!(target: "rpc", "Response: {}.", match res {
-				Some(ref res) => res,
-				None => "None",
-			});
-			res
+		match self.handle_text_request(request, meta, false, |_, _| {}) {
+			StreamingResponse::Response(response) => response.map(as_string as fn(_) -> _),
+			StreamingResponse::Stream(_) => unreachable!("Streaming is disabled; qed"),
 		}
+	}
 
-		trace!(target: "rpc", "Request: {}.", request);
-		let request = read_request(request);
-		let result = match request {
+	/// Handle request serialized with given encoding asynchronously.
+	/// The response is serialized with the same encoding.
+	pub fn handle_encoded_request<E: Encoding>(&self, request: &[u8], encoding: E, meta: T) -> FutureEncoded {
+		use self::future::Either::{A, B};
+
+		let result = match encoding.decode(request) {
 			Err(error) => A(futures::finished(Some(Response::from(error, self.compatibility.default_version())))),
 			Ok(request) => B(self.handle_rpc_request(request, meta)),
 		};
 
-		result.map(as_string)
+		Box::new(result.map(move |response| response.map(|response| encoding.encode(&response))))
+	}
+
+	/// Handle given request asynchronously, streaming results of a call to a streaming method
+	/// (see `add_streaming_method`).
+	///
+	/// Every value produced by the method is sent as `STREAM_CHUNK_METHOD` notification
+	/// referencing the id of the call. The stream ends with the response, which has `null` result
+	/// or carries the error that interrupted the stream.
+	/// The method waits while results are not consumed.
+	/// Any other request is handled like with `handle_request`.
+	/// Calls to streaming methods within batches (or over `handle_request`) return all values collected into an array.
+	pub fn handle_streaming_request(&self, request: &str, meta: T) -> StreamingResponse<FutureResult<S::Future>> {
+		match self.handle_text_request(request, meta, true, |_, _| {}) {
+			StreamingResponse::Response(response) => StreamingResponse::Response(response.map(as_string as fn(_) -> _)),
+			StreamingResponse::Stream(messages) => StreamingResponse::Stream(messages),
+		}
+	}
+
+	/// Like `handle_streaming_request`, but the response is returned before serialization
+	/// (e.g. to let the transport inspect the outputs) and before the request is dispatched
+	/// `on_deprecated` is given the name and notice of every deprecation that applies to its calls
+	/// (see `deprecations`), e.g. to warn the client.
+	pub fn handle_streaming_request_with<D>(&self, request: &str, meta: T, on_deprecated: D) -> StreamingResponse<FutureRpcResponse<S::Future>> where
+		D: FnMut(&str, &Deprecation),
+	{
+		self.handle_text_request(request, meta, true, on_deprecated)
+	}
+
+	fn handle_text_request<D>(&self, request: &str, meta: T, streaming: bool, mut on_deprecated: D) -> StreamingResponse<FutureRpcResponse<S::Future>> where
+		D: FnMut(&str, &Deprecation),
+	{
+		use self::future::Either::{A, B};
+
+		trace!(target: "rpc", "Request: {}.", request);
+		// Fast path: dispatch params as raw JSON unless middleware wants to see the `Request`.
+		let result = if self.middleware.inspects_requests() {
+			match read_request(request) {
+				Err(error) => A(futures::finished(Some(Response::from(error, self.compatibility.default_version())))),
+				Ok(request) => {
+					for_each_method(&request, |method| self.deprecations(method, &mut on_deprecated));
+					if streaming && self.is_streaming_request(&request) {
+						let (sender, receiver) = mpsc::channel(STREAM_BUFFER);
+						let response = self.middleware.on_request(request, meta, move |request, meta| {
+							self.dispatch(request, meta, Some(sender))
+						});
+						return StreamingResponse::Stream(chunked_response(receiver, response));
+					}
+					B(A(self.handle_rpc_request(request, meta)))
+				},
+			}
+		} else {
+			match read_raw_request(request) {
+				Err(error) => A(futures::finished(Some(Response::from(error, self.compatibility.default_version())))),
+				Ok(request) => {
+					for_each_raw_method(&request, |method| self.deprecations(method, &mut on_deprecated));
+					if streaming && self.is_streaming_raw_request(&request) {
+						let (sender, receiver) = mpsc::channel(STREAM_BUFFER);
+						let response = self.dispatch_raw(request, meta, Some(sender));
+						return StreamingResponse::Stream(chunked_response(receiver, response));
+					}
+					B(B(self.handle_raw_request(request, meta)))
+				},
+			}
+		};
+
+		StreamingResponse::Response(result)
+	}
+
+	fn is_streaming_request(&self, request: &Request) -> bool {
+		match *request {
+			Request::Single(Call::MethodCall(ref call)) => {
+				!self.compatibility.is_v1_notification(call.jsonrpc, &call.id) && self.is_streaming(&call.method)
+			},
+			_ => false,
+		}
+	}
+
+	fn is_streaming_raw_request(&self, request: &RawRequest) -> bool {
+		match *request {
+			RawRequest::Single(RawCall::Call(ref call)) => match call.id {
+				Some(ref id) => !self.compatibility.is_v1_notification(call.jsonrpc, id) && self.is_streaming(&call.method),
+				None => false,
+			},
+			_ => false,
+		}
+	}
+
+	fn is_streaming(&self, name: &str) -> bool {
+		match self.methods.get(name) {
+			Some(&RemoteProcedure::StreamingMethod(_)) => true,
+			Some(&RemoteProcedure::Alias(ref alias)) => match self.methods.get(alias) {
+				Some(&RemoteProcedure::StreamingMethod(_)) => true,
+				_ => false,
+			},
+			_ => false,
+		}
 	}
 
 	/// Handle deserialized RPC request.
 	pub fn handle_rpc_request(&self, request: Request, meta: T) -> S::Future {
-		use self::future::Either::{A, B};
+		self.middleware.on_request(request, meta, |request, meta| self.dispatch(request, meta, None))
+	}
 
-		self.middleware.on_request(request, meta, |request, meta| match request {
+	fn dispatch(&self, request: Request, meta: T, chunks: Option<ChunkSender>) -> FutureResponse {
+		match request {
 			Request::Single(call) => {
-				A(self.handle_call(call, meta).map(|output| output.map(Response::Single)))
+				Box::new(self.handle_call_with_chunks(call, meta, chunks).map(|output| output.map(Response::Single)))
 			},
 			Request::Batch(calls) => {
+				let empty = empty_batch_response(calls.is_empty(), self.compatibility.default_version());
 				let futures: Vec<_> = calls.into_iter().map(move |call| self.handle_call(call, meta.clone())).collect();
-				B(futures::future::join_all(futures).map(|outs| {
+				Box::new(futures::future::join_all(futures).map(move |outs| {
 					let outs: Vec<_> = outs.into_iter().filter_map(|v| v).collect();
 					if outs.is_empty() {
-						None
+						empty
 					} else {
 						Some(Response::Batch(outs))
 					}
 				}))
 			},
-		})
+		}
+	}
+
+	/// Handle RPC request with params kept as raw JSON.
+	/// NOTE: Middleware is not invoked.
+	pub fn handle_raw_request(&self, request: RawRequest, meta: T) -> FutureResponse {
+		self.dispatch_raw(request, meta, None)
+	}
+
+	fn dispatch_raw(&self, request: RawRequest, meta: T, chunks: Option<ChunkSender>) -> FutureResponse {
+		match request {
+			RawRequest::Single(call) => {
+				Box::new(self.handle_raw_call_with_chunks(call, meta, chunks).map(|output| output.map(Response::Single)))
+			},
+			RawRequest::Batch(calls) => {
+				let empty = empty_batch_response(calls.is_empty(), self.compatibility.default_version());
+				let futures: Vec<_> = calls.into_iter().map(move |call| self.handle_raw_call(call, meta.clone())).collect();
+				Box::new(futures::future::join_all(futures).map(move |outs| {
+					let outs: Vec<_> = outs.into_iter().filter_map(|v| v).collect();
+					if outs.is_empty() {
+						empty
+					} else {
+						Some(Response::Batch(outs))
+					}
+				}))
+			},
+		}
 	}
 
 	/// Handle single call asynchronously.
 	pub fn handle_call(&self, call: Call, meta: T) -> FutureOutput {
-		use self::future::Either::{A, B};
+		self.handle_call_with_chunks(call, meta, None)
+	}
+
+	fn handle_call_with_chunks(&self, call: Call, meta: T, chunks: Option<ChunkSender>) -> FutureOutput {
+		use self::future::Either::B;
+
+		let call = match call {
+			Call::MethodCall(method) => if self.compatibility.is_v1_notification(method.jsonrpc, &method.id) {
+				Call::Notification(Notification {
+					jsonrpc: method.jsonrpc,
+					method: method.method,
+					params: method.params,
+				})
+			} else {
+				Call::MethodCall(method)
+			},
+			call => call,
+		};
 
 		match call {
 			Call::MethodCall(method) => {
 				let params = method.params.unwrap_or(Params::None);
-				let id = method.id;
-				let jsonrpc = method.jsonrpc;
-				let valid_version = self.compatibility.is_version_valid(jsonrpc);
+				self.handle_method_call(&method.method, method.id, method.jsonrpc, chunks, move |method| {
+					let params = match method.params_schema() {
+						Some(schema) => schema::validate_params(schema, params)?,
+						None => params,
+					};
 
-				let call_method = |method: &Arc<RpcMethod<T>>| {
 					let method = method.clone();
-					futures::lazy(move || method.call(params, meta))
-				};
-
-				let result = match (valid_version, self.methods.get(&method.method)) {
-					(false, _) => Err(Error::invalid_version()),
-					(true, Some(&RemoteProcedure::Method(ref method))) => Ok(call_method(method)),
-					(true, Some(&RemoteProcedure::Alias(ref alias))) => match self.methods.get(alias) {
-						Some(&RemoteProcedure::Method(ref method)) => Ok(call_method(method)),
-						_ => Err(Error::method_not_found()),
-					},
-					(true, _) => Err(Error::method_not_found()),
-				};
-
-				match result {
-					Ok(result) => A(Box::new(
-						result.then(move |result| futures::finished(Some(Output::from(result, id, jsonrpc))))
-					)),
-					Err(err) => B(futures::finished(Some(Output::from(Err(err), id, jsonrpc)))),
-				}
+					Ok(futures::lazy(move || call_method(&*method, params, meta)))
+				})
 			},
 			Call::Notification(notification) => {
 				let params = notification.params.unwrap_or(Params::None);
-				let jsonrpc = notification.jsonrpc;
-				if !self.compatibility.is_version_valid(jsonrpc) {
-					return B(futures::finished(None));
+				self.handle_notification(&notification.method, notification.jsonrpc, move || Ok(params), meta);
+				B(futures::finished(None))
+			},
+			Call::Invalid(id) => {
+				B(futures::finished(Some(Output::invalid_request(id, self.compatibility.default_version()))))
+			},
+		}
+	}
+
+	/// Handle single call with params kept as raw JSON.
+	/// Params are deserialized before this function returns.
+	pub fn handle_raw_call(&self, call: RawCall, meta: T) -> FutureOutput {
+		self.handle_raw_call_with_chunks(call, meta, None)
+	}
+
+	fn handle_raw_call_with_chunks(&self, call: RawCall, meta: T, chunks: Option<ChunkSender>) -> FutureOutput {
+		use self::future::Either::B;
+
+		let call = match call {
+			RawCall::Call(call) => call,
+			RawCall::Invalid(id) => {
+				return B(futures::finished(Some(Output::invalid_request(id, self.compatibility.default_version()))));
+			},
+		};
+
+		let params = call.params;
+		let id = match call.id {
+			Some(ref id) if self.compatibility.is_v1_notification(call.jsonrpc, id) => None,
+			id => id,
+		};
+
+		match id {
+			Some(id) => self.handle_method_call(&call.method, id, call.jsonrpc, chunks, move |method| {
+				if let Some(schema) = method.params_schema() {
+					// Params parsed for validation are passed to the method instead of parsing them again.
+					let params = schema::validate_params(schema, params.to_params()?)?;
+					return Ok(call_method(&**method, params, meta));
+				}
+
+				if method.has_raw_result() {
+					Ok(call_method(&**method, params.to_params()?, meta))
+				} else {
+					Ok(future::Either::A(method.call_raw(params, meta).map(MethodResult::Value as fn(_) -> _)))
 				}
+			}),
+			None => {
+				self.handle_notification(&call.method, call.jsonrpc, move || params.to_params(), meta);
+				B(futures::finished(None))
+			},
+		}
+	}
+
+	fn handle_method_call<F, I>(&self, name: &str, id: Id, jsonrpc: Option<Version>, chunks: Option<ChunkSender>, invoke: I) -> FutureOutput where
+		I: FnOnce(&Arc<RpcMethod<T>>) -> Result<F, Error>,
+		F: Future<Item = MethodResult, Error = Error> + Send + 'static,
+	{
+		use self::future::Either::{A, B};
+
+		let valid_version = self.compatibility.is_version_valid(jsonrpc);
+		if valid_version && self.discovery.as_ref().map_or(false, |discovery| discovery == name) {
+			return B(futures::finished(Some(Output::from(Ok(self.discover()), id, jsonrpc))));
+		}
 
-				match self.methods.get(&notification.method) {
-					Some(&RemoteProcedure::Notification(ref notification)) => {
-						notification.execute(params, meta);
+		let deprecated = self.record_deprecated(name);
+		let call_method = |target: &str, method: &Arc<RpcMethod<T>>| {
+			// Validating results is only meant to catch bugs during development.
+			let result_schema = if cfg!(debug_assertions) {
+				method.result_schema().cloned()
+			} else {
+				None
+			};
+
+			let permit = self.limits.acquire(target, name)?;
+			Ok(invoke(method)?.then(move |result| {
+				drop(permit);
+				result
+			}).and_then(move |result| {
+				let validation = result_schema.map(|schema| match result {
+					MethodResult::Value(ref value) => schema::validate(&schema, value),
+					MethodResult::Raw(ref raw) => match serde_json::from_str(raw.get()) {
+						Ok(value) => schema::validate(&schema, &value),
+						Err(_) => Ok(()),
 					},
-					Some(&RemoteProcedure::Alias(ref alias)) => {
-						if let Some(&RemoteProcedure::Notification(ref notification)) = self.methods.get(alias) {
-							notification.execute(params, meta);
-						}
+				});
+				match validation {
+					Some(Err(violation)) => {
+						warn!(target: "rpc", "Method returned result not conforming to the schema: {}", violation);
+						Err(violation.into_result_error())
 					},
-					_ => {},
+					_ => Ok(result),
 				}
+			}))
+		};
 
-				B(futures::finished(None))
+		let streamed = |method: &Arc<RpcStreamingMethod<T>>| -> Arc<RpcMethod<T>> {
+			Arc::new(StreamedMethod {
+				method: method.clone(),
+				id: id.clone(),
+				jsonrpc,
+				chunks,
+			})
+		};
+
+		let result = match (valid_version, self.methods.get(name)) {
+			(false, _) => Err(Error::invalid_version()),
+			(true, Some(&RemoteProcedure::Method(ref method))) => call_method(name, method),
+			(true, Some(&RemoteProcedure::StreamingMethod(ref method))) => call_method(name, &streamed(method)),
+			(true, Some(&RemoteProcedure::Alias(ref alias))) => match self.methods.get(alias) {
+				Some(&RemoteProcedure::Method(ref method)) => call_method(alias, method),
+				Some(&RemoteProcedure::StreamingMethod(ref method)) => call_method(alias, &streamed(method)),
+				_ => Err(Error::method_not_found()),
 			},
-			Call::Invalid(id) => {
-				B(futures::finished(Some(Output::invalid_request(id, self.compatibility.default_version()))))
+			(true, _) => Err(Error::method_not_found()),
+		};
+
+		match result {
+			Ok(result) => A(Box::new(
+				result.then(move |result| {
+					let result = match deprecated {
+						Some(deprecated) => result.map_err(|err| deprecated.annotate(err)),
+						None => result,
+					};
+					futures::finished(Some(match result {
+						Ok(MethodResult::Raw(result)) => Output::Success(Success {
+							id,
+							jsonrpc,
+							result: Value::Null,
+							raw_result: Some(result),
+						}),
+						Ok(MethodResult::Value(result)) => Output::from(Ok(result), id, jsonrpc),
+						Err(err) => Output::from(Err(err), id, jsonrpc),
+					}))
+				})
+			)),
+			Err(err) => B(futures::finished(Some(Output::from(Err(err), id, jsonrpc)))),
+		}
+	}
+
+	fn handle_notification<P>(&self, name: &str, jsonrpc: Option<Version>, params: P, meta: T) where
+		P: FnOnce() -> Result<Params, Error>,
+	{
+		if !self.compatibility.is_version_valid(jsonrpc) {
+			return;
+		}
+
+		self.record_deprecated(name);
+		let notification = match self.methods.get(name) {
+			Some(&RemoteProcedure::Notification(ref notification)) => notification,
+			Some(&RemoteProcedure::Alias(ref alias)) => match self.methods.get(alias) {
+				Some(&RemoteProcedure::Notification(ref notification)) => notification,
+				_ => return,
 			},
+			_ => return,
+		};
+
+		match params() {
+			Ok(params) => notification.execute(params, meta),
+			Err(err) => debug!(target: "rpc", "Ignoring notification with invalid params: {:?}", err),
 		}
 	}
+
+	/// Logs and counts usage of deprecated method (or alias and its target).
+	/// Returns the notice that should be attached to the response.
+	fn record_deprecated(&self, name: &str) -> Option<Deprecation> {
+		let mut notice = None;
+		self.deprecated_entries(name, |name, deprecated| {
+			deprecated.record(name);
+			if notice.is_none() {
+				notice = Some(deprecated.notice().clone());
+			}
+		});
+		notice
+	}
 }
 
 /// Simplified `IoHandler` with no `Metadata` associated with each request.
@@ -337,10 +968,67 @@ impl From<IoHandler> for MetaIoHandler<()> {
 	}
 }
 
+// Yields results sent by a call to a streaming method followed by the response.
+fn chunked_response<F>(chunks: mpsc::Receiver<String>, response: F) -> ResponseStream where
+	F: Future<Item=Option<Response>, Error=()> + Send + 'static,
+{
+	Box::new(ChunkedResponse {
+		chunks,
+		response: StreamedResponse::Pending(response.map(|response| response.map(write_response))),
+	})
+}
+
+// Calls `f` with the name of every method and notification called by the request.
+fn for_each_method<F: FnMut(&str)>(request: &Request, mut f: F) {
+	let mut call = |call: &Call| match *call {
+		Call::MethodCall(ref call) => f(&call.method),
+		Call::Notification(ref notification) => f(&notification.method),
+		Call::Invalid(_) => {},
+	};
+	match *request {
+		Request::Single(ref single) => call(single),
+		Request::Batch(ref calls) => calls.iter().for_each(call),
+	}
+}
+
+fn for_each_raw_method<F: FnMut(&str)>(request: &RawRequest, mut f: F) {
+	let mut call = |call: &RawCall| match *call {
+		RawCall::Call(ref call) => f(&call.method),
+		RawCall::Invalid(_) => {},
+	};
+	match *request {
+		RawRequest::Single(ref single) => call(single),
+		RawRequest::Batch(ref calls) => calls.iter().for_each(call),
+	}
+}
+
+/// Response to a batch that produced no outputs.
+/// Batches of notifications are not answered, but an empty batch is an invalid request.
+fn empty_batch_response(is_empty: bool, jsonrpc: Option<Version>) -> Option<Response> {
+	if is_empty {
+		Some(Response::from(Error::new(ErrorCode::InvalidRequest), jsonrpc))
+	} else {
+		None
+	}
+}
+
 fn read_request(request_str: &str) -> Result<Request, Error> {
 	serde_json::from_str(request_str).map_err(|_| Error::new(ErrorCode::ParseError))
 }
 
+fn read_raw_request<'a>(request_str: &'a str) -> Result<RawRequest<'a>, Error> {
+	serde_json::from_str(request_str).map_err(|_| Error::new(ErrorCode::ParseError))
+}
+
+fn as_string(response: Option<Response>) -> Option<String> {
+	let res = response.map(write_response);
+	debug!(target: "rpc", "Response: {}.", match res {
+		Some(ref res) => res,
+		None => "None",
+	});
+	res
+}
+
 fn write_response(response: Response) -> String {
 	// this should never fail
 	serde_json::to_string(&response).unwrap()
@@ -349,7 +1037,9 @@ fn write_response(response: Response) -> String {
 #[cfg(test)]
 mod tests {
 	use futures;
-	use types::{Value};
+	use serde_json;
+	use types::{Value, Error};
+	use deprecation::Deprecation;
 	use super::{IoHandler, Compatibility};
 
 	#[test]
@@ -375,11 +1065,247 @@ mod tests {
 		});
 
 		let request = r#"{"method": "say_hello", "params": [42, 23], "id": 1}"#;
-		let response = r#"{"result":"hello","id":1}"#;
+		let response = r#"{"result":"hello","error":null,"id":1}"#;
 
 		assert_eq!(io.handle_request_sync(request), Some(response.to_string()));
 	}
 
+	#[test]
+	fn test_io_handler_1dot0_only() {
+		use std::sync::atomic::{AtomicUsize, Ordering};
+		use std::sync::Arc;
+
+		let mut io = IoHandler::with_compatibility(Compatibility::V1);
+		let notified = Arc::new(AtomicUsize::new(0));
+		let notified2 = notified.clone();
+		io.add_notification("notify", move |_| {
+			notified2.fetch_add(1, Ordering::SeqCst);
+		});
+
+		let request = r#"{"method": "say_hello", "params": [], "id": 1}"#;
+		let response = r#"{"result":null,"error":{"code":-32601,"message":"Method not found"},"id":1}"#;
+		assert_eq!(io.handle_request_sync(request), Some(response.to_string()));
+
+		let request = r#"{"method": "notify", "params": [], "id": null}"#;
+		assert_eq!(io.handle_request_sync(request), None);
+		assert_eq!(notified.load(Ordering::SeqCst), 1);
+
+		let request = r#"{"jsonrpc": "2.0", "method": "say_hello", "params": [], "id": 1}"#;
+		let response = r#"{"jsonrpc":"2.0","error":{"code":-32600,"message":"Unsupported JSON-RPC protocol version"},"id":1}"#;
+		assert_eq!(io.handle_request_sync(request), Some(response.to_string()));
+	}
+
+	#[test]
+	fn test_null_id_without_version() {
+		use std::sync::atomic::{AtomicUsize, Ordering};
+		use std::sync::Arc;
+
+		let notified = Arc::new(AtomicUsize::new(0));
+		let io = |compatibility| {
+			let mut io = IoHandler::with_compatibility(compatibility);
+			let notified = notified.clone();
+			io.add_notification("notify", move |_| {
+				notified.fetch_add(1, Ordering::SeqCst);
+			});
+			io
+		};
+		let request = r#"{"method": "notify", "params": [], "id": null}"#;
+
+		// notification only if JSON-RPC 1.0 is supported
+		let response = r#"{"result":null,"error":{"code":-32600,"message":"Unsupported JSON-RPC protocol version"},"id":null}"#;
+		assert_eq!(io(Compatibility::V2).handle_request_sync(request), Some(response.to_string()));
+		assert_eq!(notified.load(Ordering::SeqCst), 0);
+
+		assert_eq!(io(Compatibility::Both).handle_request_sync(request), None);
+		assert_eq!(notified.load(Ordering::SeqCst), 1);
+	}
+
+	#[test]
+	fn test_empty_batch() {
+		use futures::Future;
+
+		let io = IoHandler::new();
+
+		let response = r#"{"jsonrpc":"2.0","error":{"code":-32600,"message":"Invalid request"},"id":null}"#;
+
+		assert_eq!(io.handle_request_sync("[]"), Some(response.to_string()));
+		let rpc_response = io.handle_rpc_request(serde_json::from_str("[]").unwrap()).wait().unwrap();
+		assert_eq!(rpc_response.map(|r| serde_json::to_string(&r).unwrap()), Some(response.to_string()));
+	}
+
+	#[test]
+	fn test_encoded_request() {
+		use futures::Future;
+		use encoding::Json;
+
+		// given
+		let mut io = IoHandler::new();
+		io.add_method("say_hello", |_| Ok(Value::String("hello".into())));
+
+		// when
+		let response = io.handle_encoded_request(br#"{"jsonrpc":"2.0","method":"say_hello","id":1}"#, Json, ()).wait().unwrap();
+		let invalid = io.handle_encoded_request(b"{", Json, ()).wait().unwrap();
+		let notification = io.handle_encoded_request(br#"{"jsonrpc":"2.0","method":"say_hello"}"#, Json, ()).wait().unwrap();
+
+		// then
+		assert_eq!(response, Some(br#"{"jsonrpc":"2.0","result":"hello","id":1}"#.to_vec()));
+		assert_eq!(invalid, Some(br#"{"jsonrpc":"2.0","error":{"code":-32700,"message":"Parse error"},"id":null}"#.to_vec()));
+		assert_eq!(notification, None);
+	}
+
+	#[test]
+	fn test_streaming_request() {
+		use futures::{Future, Stream, stream};
+		use super::{FutureResponse, FutureResult, StreamingResponse};
+
+		fn stream(response: StreamingResponse<FutureResult<FutureResponse>>) -> Vec<String> {
+			match response {
+				StreamingResponse::Stream(messages) => messages.collect().wait().unwrap(),
+				StreamingResponse::Response(_) => panic!("Expected streamed response."),
+			}
+		}
+		fn response(response: StreamingResponse<FutureResult<FutureResponse>>) -> Option<String> {
+			match response {
+				StreamingResponse::Response(response) => response.wait().unwrap(),
+				StreamingResponse::Stream(_) => panic!("Expected single response."),
+			}
+		}
+
+		// given
+		let mut io = IoHandler::new();
+		io.add_method("say_hello", |_| Ok(Value::String("hello".into())));
+		io.add_streaming_method("logs", |_, _| stream::iter_ok(vec![Value::from(1), Value::from(2)]));
+		io.add_streaming_method("broken", |_, _| stream::iter_result(vec![Ok(Value::from(1)), Err(Error::internal_error())]));
+		io.add_alias("tail", "logs");
+
+		// when
+		let streamed = stream(io.handle_streaming_request(r#"{"jsonrpc":"2.0","method":"tail","id":"a"}"#, ()));
+		let broken = stream(io.handle_streaming_request(r#"{"jsonrpc":"2.0","method":"broken","id":2}"#, ()));
+		let single = response(io.handle_streaming_request(r#"{"jsonrpc":"2.0","method":"say_hello","id":1}"#, ()));
+		let batch = response(io.handle_streaming_request(r#"[{"jsonrpc":"2.0","method":"logs","id":1}]"#, ()));
+		let collected = io.handle_request_sync(r#"[{"jsonrpc":"2.0","method":"logs","id":1}]"#);
+
+		// then
+		assert_eq!(streamed, vec![
+			r#"{"jsonrpc":"2.0","method":"rpc.chunk","params":{"id":"a","result":1}}"#.to_owned(),
+			r#"{"jsonrpc":"2.0","method":"rpc.chunk","params":{"id":"a","result":2}}"#.to_owned(),
+			r#"{"jsonrpc":"2.0","result":null,"id":"a"}"#.to_owned(),
+		]);
+		assert_eq!(broken, vec![
+			r#"{"jsonrpc":"2.0","method":"rpc.chunk","params":{"id":2,"result":1}}"#.to_owned(),
+			r#"{"jsonrpc":"2.0","error":{"code":-32603,"message":"Internal error"},"id":2}"#.to_owned(),
+		]);
+		assert_eq!(collected, Some(r#"[{"jsonrpc":"2.0","result":[1,2],"id":1}]"#.to_owned()));
+		assert_eq!(single, Some(r#"{"jsonrpc":"2.0","result":"hello","id":1}"#.to_owned()));
+		assert_eq!(batch, collected);
+	}
+
+	#[test]
+	fn test_streaming_request_waits_for_consumer() {
+		use std::sync::Arc;
+		use std::sync::atomic::{AtomicUsize, Ordering};
+		use futures::{Future, Stream, stream};
+		use super::{StreamingResponse, STREAM_BUFFER};
+
+		// given
+		let produced = Arc::new(AtomicUsize::new(0));
+		let counter = produced.clone();
+		let mut io = IoHandler::new();
+		io.add_streaming_method("logs", move |_, _| {
+			let counter = counter.clone();
+			stream::iter_ok(0..1000).map(move |value| {
+				counter.fetch_add(1, Ordering::SeqCst);
+				Value::from(value)
+			})
+		});
+
+		// when
+		let messages = match io.handle_streaming_request(r#"{"jsonrpc":"2.0","method":"logs","id":1}"#, ()) {
+			StreamingResponse::Stream(messages) => messages,
+			StreamingResponse::Response(_) => panic!("Expected streamed response."),
+		};
+		let (first, _rest) = messages.into_future().wait().map_err(|_| ()).unwrap();
+
+		// then
+		assert_eq!(first, Some(r#"{"jsonrpc":"2.0","method":"rpc.chunk","params":{"id":1,"result":0}}"#.to_owned()));
+		assert!(produced.load(Ordering::SeqCst) <= STREAM_BUFFER + 2);
+	}
+
+	#[test]
+	fn test_max_in_flight() {
+		use limits::Priority;
+
+		// given
+		let mut io = IoHandler::new();
+		io.add_method("slow", |_| futures::empty::<Value, Error>());
+		io.add_method("health", |_| Ok(Value::Bool(true)));
+		io.add_method("status", |_| Ok(Value::Bool(true)));
+		io.add_alias("ping", "status");
+		io.set_priority("health", Priority::Critical);
+		io.set_priority("ping", Priority::Critical);
+		io.set_max_in_flight(1);
+		let request = r#"{"jsonrpc":"2.0","method":"slow","id":1}"#;
+
+		// when
+		let first = io.handle_request(request);
+		let rejected = io.handle_request_sync(request);
+		let health = io.handle_request_sync(r#"{"jsonrpc":"2.0","method":"health","id":2}"#);
+		let ping = io.handle_request_sync(r#"{"jsonrpc":"2.0","method":"ping","id":3}"#);
+		let status = io.handle_request_sync(r#"{"jsonrpc":"2.0","method":"status","id":4}"#);
+		let in_flight = io.calls_in_flight();
+		drop(first);
+		let second = io.handle_request(request);
+
+		// then
+		assert_eq!(rejected, Some(r#"{"jsonrpc":"2.0","error":{"code":-32005,"message":"Server is busy, try again later","data":{"retryAfter":1}},"id":1}"#.to_owned()));
+		assert_eq!(health, Some(r#"{"jsonrpc":"2.0","result":true,"id":2}"#.to_owned()));
+		assert_eq!(ping, Some(r#"{"jsonrpc":"2.0","result":true,"id":3}"#.to_owned()));
+		assert_eq!(status, Some(r#"{"jsonrpc":"2.0","error":{"code":-32005,"message":"Server is busy, try again later","data":{"retryAfter":1}},"id":4}"#.to_owned()));
+		assert_eq!(in_flight, 1);
+		assert_eq!(io.calls_in_flight(), 1);
+		assert_eq!(io.rejected_calls(), 2);
+		drop(second);
+		assert_eq!(io.calls_in_flight(), 0);
+	}
+
+	#[test]
+	fn test_method_concurrency_limit() {
+		// given
+		let mut io = IoHandler::new();
+		io.add_method("slow", |_| futures::empty::<Value, Error>());
+		io.add_method("fast", |_| Ok(Value::Bool(true)));
+		io.add_alias("slow_alias", "slow");
+		io.set_concurrency_limit("slow", 1);
+
+		// when
+		let _first = io.handle_request(r#"{"jsonrpc":"2.0","method":"slow","id":1}"#);
+		let rejected = io.handle_request_sync(r#"{"jsonrpc":"2.0","method":"slow_alias","id":2}"#);
+		let fast = io.handle_request_sync(r#"{"jsonrpc":"2.0","method":"fast","id":3}"#);
+
+		// then
+		let rejected: ::types::Response = serde_json::from_str(&rejected.unwrap()).unwrap();
+		match rejected {
+			::types::Response::Single(::types::Output::Failure(failure)) => assert!(failure.error.is_server_busy()),
+			other => panic!("Unexpected response: {:?}", other),
+		}
+		assert_eq!(fast, Some(r#"{"jsonrpc":"2.0","result":true,"id":3}"#.to_owned()));
+	}
+
+	#[test]
+	fn test_retry_after_rounded_up() {
+		// given
+		let mut io = IoHandler::new();
+		io.add_method("slow", |_| futures::empty::<Value, Error>());
+		io.set_concurrency_limit("slow", 0);
+		io.set_retry_after(::std::time::Duration::from_millis(1500));
+
+		// when
+		let rejected = io.handle_request_sync(r#"{"jsonrpc":"2.0","method":"slow","id":1}"#);
+
+		// then
+		assert_eq!(rejected, Some(r#"{"jsonrpc":"2.0","error":{"code":-32005,"message":"Server is busy, try again later","data":{"retryAfter":2}},"id":1}"#.to_owned()));
+	}
+
 	#[test]
 	fn test_async_io_handler() {
 		let mut io = IoHandler::new();
@@ -456,6 +1382,238 @@ mod tests {
 		assert_eq!(called.load(atomic::Ordering::SeqCst), true);
 	}
 
+	#[test]
+	fn test_deprecated_alias() {
+		let mut io = IoHandler::new();
+		io.add_method("say_hello", |_| {
+			Err(Error::internal_error())
+		});
+		io.add_deprecated_alias("hello", "say_hello", Deprecation::new("Use `say_hello` instead.").with_error_data());
+
+		let request = r#"{"jsonrpc": "2.0", "method": "hello", "params": [42, 23], "id": 1}"#;
+		let response = r#"{"jsonrpc":"2.0","error":{"code":-32603,"message":"Internal error","data":{"deprecated":{"message":"Use `say_hello` instead."}}},"id":1}"#;
+
+		assert_eq!(io.handle_request_sync(request), Some(response.to_string()));
+		assert_eq!(io.deprecated_calls("hello"), 1);
+		assert_eq!(io.deprecated_calls("say_hello"), 0);
+	}
+
+	#[test]
+	fn test_deprecated_notification() {
+		let mut io = IoHandler::new();
+		io.add_notification("say_hello", |_| {});
+		io.deprecate("say_hello", Deprecation::new("Will be removed.").sunset("9.0"));
+
+		let request = r#"{"jsonrpc": "2.0", "method": "say_hello", "params": [42, 23]}"#;
+
+		assert_eq!(io.handle_request_sync(request), None);
+		assert_eq!(io.handle_request_sync(request), None);
+		assert_eq!(io.deprecated_calls("say_hello"), 2);
+		assert_eq!(io.deprecation("say_hello").and_then(|d| d.sunset.clone()), Some("9.0".into()));
+	}
+
+	#[test]
+	fn test_deprecations_reported_before_dispatch() {
+		use futures::Future;
+		use super::StreamingResponse;
+
+		// given
+		let mut io = IoHandler::new();
+		io.add_method("say_hello", |_| Ok(Value::String("hello".into())));
+		io.add_deprecated_alias("hello", "say_hello", Deprecation::new("Use `greet` instead."));
+		io.deprecate("say_hello", Deprecation::new("Will be removed."));
+		io.add_alias("hi", "say_hello");
+
+		// when
+		let mut reported = Vec::new();
+		let request = r#"[{"jsonrpc":"2.0","method":"hello","id":1},{"jsonrpc":"2.0","method":"hi"}]"#;
+		let response = io.handle_streaming_request_with(request, (), |method, deprecation| {
+			reported.push(deprecation.warning(method));
+		});
+
+		// then
+		assert_eq!(reported, vec![
+			"Method hello is deprecated: Use `greet` instead.".to_owned(),
+			"Method say_hello is deprecated: Will be removed.".to_owned(),
+			"Method say_hello is deprecated: Will be removed.".to_owned(),
+		]);
+		match response {
+			StreamingResponse::Response(response) => assert!(response.wait().unwrap().is_some()),
+			StreamingResponse::Stream(_) => panic!("Expected single response."),
+		}
+	}
+
+	#[test]
+	fn test_discovery() {
+		let mut io = IoHandler::new();
+		io.add_method("say_hello", |_| {
+			Ok(Value::String("hello".to_string()))
+		});
+		io.add_deprecated_alias("hello", "say_hello", Deprecation::new("Use `say_hello` instead.").sunset("9.0"));
+		io.add_discovery_method("rpc_discover");
+
+		let request = r#"{"jsonrpc": "2.0", "method": "rpc_discover", "id": 1}"#;
+		let response = r#"{"jsonrpc":"2.0","result":{"methods":{"hello":{"deprecated":{"message":"Use `say_hello` instead.","sunset":"9.0"},"kind":"alias","target":"say_hello"},"say_hello":{"kind":"method"}}},"id":1}"#;
+
+		assert_eq!(io.handle_request_sync(request), Some(response.to_string()));
+	}
+
+	#[test]
+	fn test_read_only_methods() {
+		let mut io = IoHandler::new();
+		io.add_method("get_balance", |_| Ok(Value::from(5)));
+		io.add_method("transfer", |_| Ok(Value::Bool(true)));
+		io.add_alias("balance", "get_balance");
+		io.mark_read_only("get_balance");
+
+		assert!(io.is_read_only("get_balance"));
+		assert!(io.is_read_only("balance"));
+		assert!(!io.is_read_only("transfer"));
+		assert!(!io.is_read_only("unknown"));
+		assert_eq!(io.discover()["methods"]["balance"]["readOnly"], Value::Bool(true));
+	}
+
+	#[test]
+	fn test_discovery_errors() {
+		use rpc_error::{IntoRpcError, ErrorCodeInfo};
+		use types::ErrorCode;
+
+		struct NotReady;
+		impl IntoRpcError for NotReady {
+			fn code(&self) -> ErrorCode {
+				ErrorCode::ServerError(-32050)
+			}
+
+			fn error_codes() -> Vec<ErrorCodeInfo> {
+				vec![ErrorCodeInfo::new(-32050, "NotReady", "Node is not ready")]
+			}
+		}
+
+		let mut io = IoHandler::new();
+		io.register_errors::<NotReady>("node").unwrap();
+		assert!(io.register_errors::<NotReady>("sync").is_err());
+
+		let response = r#"{"errors":[{"code":-32050,"message":"Node is not ready","module":"node","name":"NotReady"}],"methods":{}}"#;
+		assert_eq!(serde_json::to_string(&io.discover()).unwrap(), response);
+	}
+
+	#[test]
+	fn test_params_schema() {
+		let mut io = IoHandler::new();
+		io.add_method("say_hello", |_| {
+			Ok(Value::String("hello".to_string()))
+		});
+		io.set_params_schema("say_hello", serde_json::from_str(r#"{
+			"type": "array",
+			"items": [{"type": "object", "properties": {"name": {"type": "string", "maxLength": 5}}}]
+		}"#).unwrap()).unwrap();
+		io.add_alias("hello", "say_hello");
+
+		let request = r#"{"jsonrpc": "2.0", "method": "hello", "params": [{"name": "too long"}], "id": 1}"#;
+		let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Invalid params: expected at most 5 characters at /0/name.","data":{"message":"expected at most 5 characters","pointer":"/0/name"}},"id":1}"#;
+
+		assert_eq!(io.handle_request_sync(request), Some(response.to_string()));
+
+		let request = r#"{"jsonrpc": "2.0", "method": "hello", "params": [{"name": "Bob"}], "id": 1}"#;
+		let response = r#"{"jsonrpc":"2.0","result":"hello","id":1}"#;
+
+		assert_eq!(io.handle_request_sync(request), Some(response.to_string()));
+	}
+
+	#[test]
+	#[cfg(debug_assertions)]
+	fn test_result_schema() {
+		let mut io = IoHandler::new();
+		io.add_method("say_hello", |_| {
+			Ok(Value::String("hello".to_string()))
+		});
+		io.add_notification("notify", |_| {});
+		io.add_alias("hello", "say_hello");
+		let schema: Value = serde_json::from_str(r#"{"type": "integer"}"#).unwrap();
+
+		assert_eq!(io.set_result_schema("hello", schema.clone()), Ok(()));
+		assert_eq!(io.set_result_schema("notify", schema.clone()).unwrap_err().name, "notify");
+		assert_eq!(io.set_result_schema("unknown", schema).unwrap_err().name, "unknown");
+
+		let request = r#"{"jsonrpc": "2.0", "method": "say_hello", "id": 1}"#;
+		let response = r#"{"jsonrpc":"2.0","error":{"code":-32603,"message":"Invalid result: expected integer, got string.","data":{"message":"expected integer, got string","pointer":""}},"id":1}"#;
+
+		assert_eq!(io.handle_request_sync(request), Some(response.to_string()));
+	}
+
+	#[test]
+	fn test_raw_params() {
+		use futures::Future;
+		use middleware::{Middleware, Noop};
+		use types::{Params, RawParams, Request, Response};
+		use calls::RpcMethod;
+		use super::MetaIoHandler;
+		use BoxFuture;
+
+		struct Method;
+		impl RpcMethod<()> for Method {
+			fn call(&self, _params: Params, _meta: ()) -> BoxFuture<Value> {
+				Box::new(futures::finished("parsed".into()))
+			}
+
+			fn call_raw(&self, params: RawParams, _meta: ()) -> BoxFuture<Value> {
+				Box::new(futures::finished(params.get().unwrap_or("").into()))
+			}
+		}
+
+		struct Inspecting;
+		impl Middleware<()> for Inspecting {
+			type Future = <Noop as Middleware<()>>::Future;
+
+			fn on_request<F, X>(&self, request: Request, meta: (), next: F) -> Self::Future where
+				F: FnOnce(Request, ()) -> X + Send,
+				X: Future<Item = Option<Response>, Error = ()> + Send + 'static,
+			{
+				Box::new(next(request, meta))
+			}
+		}
+
+		let mut io = IoHandler::new();
+		io.add_method_with_meta("say_hello", Method);
+		let mut io_with_middleware = MetaIoHandler::with_middleware(Inspecting);
+		io_with_middleware.add_method_with_meta("say_hello", Method);
+
+		let request = r#"{"jsonrpc": "2.0", "method": "say_hello", "params": [42, {"a": 23}], "id": 1}"#;
+		let raw_response = r#"{"jsonrpc":"2.0","result":"[42, {\"a\": 23}]","id":1}"#;
+		let parsed_response = r#"{"jsonrpc":"2.0","result":"parsed","id":1}"#;
+
+		assert_eq!(io.handle_request_sync(request), Some(raw_response.to_string()));
+		assert_eq!(io_with_middleware.handle_request_sync(request, ()), Some(parsed_response.to_string()));
+
+		// invalid calls don't prevent dispatching raw params of valid ones
+		let request = r#"[{"jsonrpc": "2.0", "method": "say_hello", "id": 1}, 5]"#;
+		let response = r#"[{"jsonrpc":"2.0","result":"","id":1},{"jsonrpc":"2.0","error":{"code":-32600,"message":"Invalid request"},"id":null}]"#;
+
+		assert_eq!(io.handle_request_sync(request), Some(response.to_string()));
+
+		let request = r#"[{"jsonrpc": "2.0", "method": "say_hello", "id": 1}"#;
+		let response = r#"{"jsonrpc":"2.0","error":{"code":-32700,"message":"Parse error"},"id":null}"#;
+
+		assert_eq!(io.handle_request_sync(request), Some(response.to_string()));
+	}
+
+	#[test]
+	fn test_raw_result() {
+		use types::RawValue;
+		use super::MetaIoHandler;
+
+		let mut io = MetaIoHandler::<()>::default();
+		io.add_method_with_raw_result("get_blob", |_params, _meta| {
+			RawValue::from_string(r#"{"blob": [1, 2, 3]}"#.to_string()).map_err(|_| Error::internal_error())
+		});
+		io.set_result_schema("get_blob", serde_json::from_str(r#"{"type": "object"}"#).unwrap()).unwrap();
+
+		let request = r#"[{"jsonrpc": "2.0", "method": "get_blob", "id": 1}, {"jsonrpc": "2.0", "method": "get_blob", "params": [], "id": 2}]"#;
+		let response = r#"[{"jsonrpc":"2.0","result":{"blob": [1, 2, 3]},"id":1},{"jsonrpc":"2.0","result":{"blob": [1, 2, 3]},"id":2}]"#;
+
+		assert_eq!(io.handle_request_sync(request, ()), Some(response.to_string()));
+	}
+
 	#[test]
 	fn test_send_sync() {
 		fn is_send_sync<T>(_obj: T) -> bool where
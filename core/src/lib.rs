@@ -26,6 +26,10 @@
 #[macro_use] extern crate log;
 #[macro_use] extern crate serde_derive;
 extern crate serde;
+#[cfg(feature = "cbor")]
+extern crate serde_cbor;
+#[cfg(feature = "msgpack")]
+extern crate rmp_serde;
 
 pub extern crate futures;
 
@@ -33,18 +37,32 @@ pub extern crate futures;
 pub extern crate serde_json;
 
 mod calls;
+mod deprecation;
 mod io;
+mod limits;
 
 mod middleware;
+mod rpc_error;
+pub mod cache;
+pub mod encoding;
+pub mod schema;
 pub mod types;
 
 /// A `Future` trait object.
 pub type BoxFuture<T> = Box<futures::Future<Item = T, Error = Error> + Send>;
 
+/// A `Stream` trait object.
+pub type BoxStream<T> = Box<futures::Stream<Item = T, Error = Error> + Send>;
+
 /// A Result type.
 pub type Result<T> = ::std::result::Result<T, Error>;
 
-pub use calls::{RemoteProcedure, Metadata, RpcMethodSimple, RpcMethod, RpcNotificationSimple, RpcNotification};
-pub use io::{Compatibility, IoHandler, MetaIoHandler, FutureResponse, FutureResult};
+pub use deprecation::Deprecation;
+pub use calls::{RemoteProcedure, Metadata, RpcMethodSimple, RpcMethod, RpcStreamingMethod, RpcNotificationSimple, RpcNotification};
+pub use encoding::Encoding;
+pub use limits::Priority;
+pub use io::{Compatibility, IoHandler, MetaIoHandler, FutureResponse, FutureRpcResponse, FutureResult, FutureEncoded, ResponseStream, StreamingResponse, STREAM_CHUNK_METHOD};
 pub use middleware::{Middleware, Noop as NoopMiddleware};
+pub use rpc_error::{IntoRpcError, ErrorCodeInfo, ErrorRegistry, DuplicateErrorCode};
+pub use schema::JsonSchema;
 pub use types::*;
@@ -0,0 +1,147 @@
+//! Load shedding: limits of calls in flight.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{self, AtomicUsize};
+use std::time::Duration;
+
+use types::{Error, Value};
+
+/// Priority class of a method.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Priority {
+	/// Rejected when the server is overloaded.
+	Normal,
+	/// Bypasses the global limit of calls in flight (meant for admin or health methods).
+	/// Concurrency limit of the method still applies.
+	Critical,
+}
+
+impl Default for Priority {
+	fn default() -> Self {
+		Priority::Normal
+	}
+}
+
+/// Tracks a call in flight until dropped.
+#[derive(Debug)]
+pub struct Permit {
+	global: Option<Arc<AtomicUsize>>,
+	method: Option<Arc<AtomicUsize>>,
+}
+
+impl Drop for Permit {
+	fn drop(&mut self) {
+		for counter in self.global.iter().chain(self.method.iter()) {
+			counter.fetch_sub(1, atomic::Ordering::SeqCst);
+		}
+	}
+}
+
+fn try_increment(counter: &AtomicUsize, limit: usize) -> bool {
+	let mut current = counter.load(atomic::Ordering::SeqCst);
+	loop {
+		if current >= limit {
+			return false;
+		}
+		match counter.compare_exchange(current, current + 1, atomic::Ordering::SeqCst, atomic::Ordering::SeqCst) {
+			Ok(_) => return true,
+			Err(actual) => current = actual,
+		}
+	}
+}
+
+#[derive(Debug)]
+pub struct Limits {
+	max_in_flight: Option<usize>,
+	in_flight: Arc<AtomicUsize>,
+	methods: HashMap<String, (usize, Arc<AtomicUsize>)>,
+	priorities: HashMap<String, Priority>,
+	retry_after: Duration,
+	rejected: AtomicUsize,
+}
+
+impl Default for Limits {
+	fn default() -> Self {
+		Limits {
+			max_in_flight: None,
+			in_flight: Default::default(),
+			methods: Default::default(),
+			priorities: Default::default(),
+			retry_after: Duration::from_secs(1),
+			rejected: Default::default(),
+		}
+	}
+}
+
+impl Limits {
+	pub fn set_max_in_flight(&mut self, limit: usize) {
+		self.max_in_flight = Some(limit);
+	}
+
+	pub fn set_method_limit(&mut self, name: &str, limit: usize) {
+		self.methods.insert(name.into(), (limit, Default::default()));
+	}
+
+	pub fn set_priority(&mut self, name: &str, priority: Priority) {
+		self.priorities.insert(name.into(), priority);
+	}
+
+	pub fn set_retry_after(&mut self, delay: Duration) {
+		self.retry_after = delay;
+	}
+
+	pub fn in_flight(&self) -> usize {
+		self.in_flight.load(atomic::Ordering::SeqCst)
+	}
+
+	pub fn rejected(&self) -> usize {
+		self.rejected.load(atomic::Ordering::SeqCst)
+	}
+
+	/// Registers a call to given method, fails if any of the limits is exceeded.
+	/// `called` is the name used by the client, which differs from `name` for aliases.
+	pub fn acquire(&self, name: &str, called: &str) -> Result<Permit, Error> {
+		let mut permit = Permit {
+			global: None,
+			method: None,
+		};
+
+		if let Some(&(limit, ref counter)) = self.methods.get(name) {
+			if !try_increment(counter, limit) {
+				return Err(self.reject(name));
+			}
+			permit.method = Some(counter.clone());
+		}
+
+		let priority = self.priorities.get(called)
+			.or_else(|| self.priorities.get(name))
+			.cloned()
+			.unwrap_or_default();
+		match (priority, self.max_in_flight) {
+			(Priority::Normal, Some(limit)) => if !try_increment(&self.in_flight, limit) {
+				// Dropping the permit releases the method slot.
+				return Err(self.reject(name));
+			},
+			_ => {
+				self.in_flight.fetch_add(1, atomic::Ordering::SeqCst);
+			},
+		}
+		permit.global = Some(self.in_flight.clone());
+
+		Ok(permit)
+	}
+
+	fn reject(&self, name: &str) -> Error {
+		self.rejected.fetch_add(1, atomic::Ordering::SeqCst);
+		debug!(target: "rpc", "Server busy, rejecting call to {}.", name);
+
+		// Partial seconds are rounded up, so that clients don't retry too early.
+		let retry_after = self.retry_after.as_secs() + if self.retry_after.subsec_nanos() > 0 { 1 } else { 0 };
+		let mut data = ::serde_json::Map::new();
+		data.insert("retryAfter".into(), Value::from(retry_after));
+		let mut error = Error::server_busy();
+		error.data = Some(Value::Object(data));
+		error
+	}
+}
@@ -15,6 +15,13 @@ pub trait Middleware<M: Metadata>: Send + Sync + 'static {
 	fn on_request<F, X>(&self, request: Request, meta: M, next: F) -> Self::Future where
 		F: FnOnce(Request, M) -> X + Send,
 		X: Future<Item=Option<Response>, Error=()> + Send + 'static;
+
+	/// Does the middleware need to see every request?
+	/// If not, `MetaIoHandler` is free to bypass it and dispatch
+	/// requests with params kept as raw JSON (avoiding building `Params`).
+	fn inspects_requests(&self) -> bool {
+		true
+	}
 }
 
 /// No-op middleware implementation
@@ -29,6 +36,10 @@ impl<M: Metadata> Middleware<M> for Noop {
 	{
 		Box::new(process(request, meta))
 	}
+
+	fn inspects_requests(&self) -> bool {
+		false
+	}
 }
 
 impl<M: Metadata, A: Middleware<M>, B: Middleware<M>>
@@ -44,6 +55,10 @@ impl<M: Metadata, A: Middleware<M>, B: Middleware<M>>
 			self.1.on_request(request, meta, process)
 		})
 	}
+
+	fn inspects_requests(&self) -> bool {
+		self.0.inspects_requests() || self.1.inspects_requests()
+	}
 }
 
 impl<M: Metadata, A: Middleware<M>, B: Middleware<M>, C: Middleware<M>>
@@ -61,6 +76,10 @@ impl<M: Metadata, A: Middleware<M>, B: Middleware<M>, C: Middleware<M>>
 			})
 		})
 	}
+
+	fn inspects_requests(&self) -> bool {
+		self.0.inspects_requests() || self.1.inspects_requests() || self.2.inspects_requests()
+	}
 }
 
 impl<M: Metadata, A: Middleware<M>, B: Middleware<M>, C: Middleware<M>, D: Middleware<M>>
@@ -80,4 +99,9 @@ impl<M: Metadata, A: Middleware<M>, B: Middleware<M>, C: Middleware<M>, D: Middl
 			})
 		})
 	}
+
+	fn inspects_requests(&self) -> bool {
+		self.0.inspects_requests() || self.1.inspects_requests() ||
+			self.2.inspects_requests() || self.3.inspects_requests()
+	}
 }
@@ -0,0 +1,248 @@
+//! Typed application errors and registry of error codes.
+
+use std::collections::BTreeMap;
+use std::{error, fmt};
+
+use serde_json;
+use types::{Error, ErrorCode, Value};
+
+/// Application error that can be converted into JSON-RPC `Error`.
+pub trait IntoRpcError {
+	/// Code of this error.
+	fn code(&self) -> ErrorCode;
+
+	/// Human-readable message.
+	fn message(&self) -> String {
+		self.code().description()
+	}
+
+	/// Structured data attached to the error (if any).
+	fn data(&self) -> Option<Value> {
+		None
+	}
+
+	/// Describes all codes that errors of this type may use.
+	/// Used to detect collisions in `ErrorRegistry`.
+	fn error_codes() -> Vec<ErrorCodeInfo> where Self: Sized {
+		Vec::new()
+	}
+
+	/// Converts into JSON-RPC `Error`.
+	fn into_rpc_error(self) -> Error where Self: Sized {
+		Error {
+			code: self.code(),
+			message: self.message(),
+			data: self.data(),
+		}
+	}
+}
+
+/// Description of a single application error code.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ErrorCodeInfo {
+	/// Numeric code.
+	pub code: i64,
+	/// Name of the error (e.g. enum variant).
+	pub name: String,
+	/// Message returned with the error.
+	pub message: String,
+}
+
+impl ErrorCodeInfo {
+	/// Creates new description of given code.
+	pub fn new<N: Into<String>, M: Into<String>>(code: i64, name: N, message: M) -> Self {
+		ErrorCodeInfo {
+			code,
+			name: name.into(),
+			message: message.into(),
+		}
+	}
+}
+
+/// Error code registered by two different modules (or twice with different meaning).
+#[derive(Debug, Clone, PartialEq)]
+pub struct DuplicateErrorCode {
+	/// The conflicting code.
+	pub code: i64,
+	/// Module that tried to register the code.
+	pub module: String,
+	/// Module that registered the code first.
+	pub registered_by: String,
+}
+
+impl fmt::Display for DuplicateErrorCode {
+	fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+		write!(fmt, "Error code {} of {} is already used by {}.", self.code, self.module, self.registered_by)
+	}
+}
+
+impl error::Error for DuplicateErrorCode {
+	fn description(&self) -> &str {
+		"duplicate error code"
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct Registered {
+	module: String,
+	#[serde(flatten)]
+	info: ErrorCodeInfo,
+}
+
+/// Collects error codes used by different modules and detects duplicates.
+#[derive(Debug, Clone, Default)]
+pub struct ErrorRegistry {
+	codes: BTreeMap<i64, Registered>,
+}
+
+impl ErrorRegistry {
+	/// Registers all codes of given error type (see `IntoRpcError::error_codes`).
+	pub fn register<E: IntoRpcError>(&mut self, module: &str) -> Result<(), DuplicateErrorCode> {
+		self.register_codes(module, E::error_codes())
+	}
+
+	/// Registers given codes under the module name.
+	/// Nothing is registered if any of the codes is already used
+	/// by other module (or by the same module with a different meaning).
+	pub fn register_codes(&mut self, module: &str, codes: Vec<ErrorCodeInfo>) -> Result<(), DuplicateErrorCode> {
+		let mut new_codes: BTreeMap<i64, Registered> = BTreeMap::new();
+		for info in codes {
+			let registered = Registered { module: module.into(), info };
+			let existing = self.codes.get(&registered.info.code).or_else(|| new_codes.get(&registered.info.code));
+			match existing {
+				Some(existing) if *existing == registered => {},
+				Some(existing) => return Err(DuplicateErrorCode {
+					code: registered.info.code,
+					module: module.into(),
+					registered_by: existing.module.clone(),
+				}),
+				None => {
+					new_codes.insert(registered.info.code, registered);
+				},
+			}
+		}
+
+		self.codes.extend(new_codes);
+		Ok(())
+	}
+
+	/// Returns the module and description of given code (if registered).
+	pub fn get(&self, code: i64) -> Option<(&str, &ErrorCodeInfo)> {
+		self.codes.get(&code).map(|registered| (registered.module.as_str(), &registered.info))
+	}
+
+	/// Returns `true` if no codes were registered.
+	pub fn is_empty(&self) -> bool {
+		self.codes.is_empty()
+	}
+
+	/// Returns all registered codes sorted by code, as used in discovery output.
+	pub fn to_value(&self) -> Value {
+		serde_json::to_value(self.codes.values().collect::<Vec<_>>()).expect("Registry serialization is infallible; qed")
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use types::{Error, ErrorCode, Value};
+	use super::{IntoRpcError, ErrorCodeInfo, ErrorRegistry, DuplicateErrorCode};
+
+	#[derive(Debug)]
+	enum BalanceError {
+		Insufficient(u64),
+		Locked,
+	}
+
+	impl IntoRpcError for BalanceError {
+		fn code(&self) -> ErrorCode {
+			match *self {
+				BalanceError::Insufficient(_) => ErrorCode::ServerError(-32010),
+				BalanceError::Locked => ErrorCode::ServerError(-32011),
+			}
+		}
+
+		fn message(&self) -> String {
+			match *self {
+				BalanceError::Insufficient(_) => "Insufficient balance".into(),
+				BalanceError::Locked => "Account is locked".into(),
+			}
+		}
+
+		fn data(&self) -> Option<Value> {
+			match *self {
+				BalanceError::Insufficient(missing) => Some(missing.into()),
+				BalanceError::Locked => None,
+			}
+		}
+
+		fn error_codes() -> Vec<ErrorCodeInfo> {
+			vec![
+				ErrorCodeInfo::new(-32010, "Insufficient", "Insufficient balance"),
+				ErrorCodeInfo::new(-32011, "Locked", "Account is locked"),
+			]
+		}
+	}
+
+	#[test]
+	fn should_convert_into_rpc_error() {
+		assert_eq!(BalanceError::Insufficient(5).into_rpc_error(), Error {
+			code: ErrorCode::ServerError(-32010),
+			message: "Insufficient balance".into(),
+			data: Some(5.into()),
+		});
+		assert_eq!(BalanceError::Locked.into_rpc_error(), Error {
+			code: ErrorCode::ServerError(-32011),
+			message: "Account is locked".into(),
+			data: None,
+		});
+	}
+
+	#[test]
+	fn should_detect_duplicate_codes() {
+		// given
+		let mut registry = ErrorRegistry::default();
+		registry.register::<BalanceError>("balances").unwrap();
+
+		// when
+		let same_module = registry.register::<BalanceError>("balances");
+		let other_module = registry.register_codes("accounts", vec![
+			ErrorCodeInfo::new(-32012, "Unknown", "Unknown account"),
+			ErrorCodeInfo::new(-32011, "Locked", "Account is locked"),
+		]);
+
+		// then
+		assert_eq!(same_module, Ok(()));
+		assert_eq!(other_module, Err(DuplicateErrorCode {
+			code: -32011,
+			module: "accounts".into(),
+			registered_by: "balances".into(),
+		}));
+		assert_eq!(registry.get(-32012), None);
+		assert_eq!(registry.get(-32011).map(|(module, _)| module), Some("balances"));
+	}
+
+	#[test]
+	fn should_detect_duplicates_within_module() {
+		let mut registry = ErrorRegistry::default();
+
+		let result = registry.register_codes("accounts", vec![
+			ErrorCodeInfo::new(-32012, "Unknown", "Unknown account"),
+			ErrorCodeInfo::new(-32012, "Missing", "Missing account"),
+		]);
+
+		assert!(result.is_err());
+		assert!(registry.is_empty());
+	}
+
+	#[test]
+	fn should_serialize_registry() {
+		let mut registry = ErrorRegistry::default();
+		registry.register::<BalanceError>("balances").unwrap();
+
+		assert_eq!(
+			serde_json::to_string(&registry.to_value()).unwrap(),
+			r#"[{"code":-32011,"message":"Account is locked","module":"balances","name":"Locked"},{"code":-32010,"message":"Insufficient balance","module":"balances","name":"Insufficient"}]"#
+		);
+	}
+}
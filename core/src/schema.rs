@@ -0,0 +1,611 @@
+//! JSON Schema validation of method params and results.
+//!
+//! Only a subset of the specification is supported: `type`, `enum`, `const`,
+//! `minimum`, `maximum`, `exclusiveMinimum`, `exclusiveMaximum`, `multipleOf`,
+//! `minLength`, `maxLength`, `items`, `additionalItems`, `minItems`, `maxItems`,
+//! `properties`, `required`, `additionalProperties`, `allOf`, `anyOf` and `not`.
+//! Other keywords (including `format`, `pattern` and `$ref`) are ignored.
+
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+use std::{error, fmt};
+
+use serde_json::{Map, Number};
+use calls::{Metadata, RemoteProcedure};
+use types::{Error, ErrorCode, Params, Value};
+
+/// Describes the place where a value does not conform to the schema.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaViolation {
+	/// JSON Pointer (RFC 6901) to the offending value.
+	pub pointer: String,
+	/// Description of the violated constraint.
+	pub message: String,
+}
+
+impl SchemaViolation {
+	/// Converts the violation into `InvalidParams` error.
+	pub fn into_params_error(self) -> Error {
+		self.into_error(ErrorCode::InvalidParams, "Invalid params")
+	}
+
+	/// Converts the violation into `InternalError` (used for invalid results).
+	pub fn into_result_error(self) -> Error {
+		self.into_error(ErrorCode::InternalError, "Invalid result")
+	}
+
+	fn into_error(self, code: ErrorCode, prefix: &str) -> Error {
+		let mut data = Map::new();
+		data.insert("pointer".into(), self.pointer.clone().into());
+		data.insert("message".into(), self.message.clone().into());
+
+		Error {
+			code,
+			message: format!("{}: {}.", prefix, self),
+			data: Some(Value::Object(data)),
+		}
+	}
+}
+
+impl fmt::Display for SchemaViolation {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		if self.pointer.is_empty() {
+			write!(f, "{}", self.message)
+		} else {
+			write!(f, "{} at {}", self.message, self.pointer)
+		}
+	}
+}
+
+/// Schema attached to a name which is not registered as a method (or an alias of a method).
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnknownMethod {
+	/// The name the schema was attached to.
+	pub name: String,
+}
+
+impl fmt::Display for UnknownMethod {
+	fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+		write!(fmt, "Schema can't be attached to {}, it's not a method.", self.name)
+	}
+}
+
+impl error::Error for UnknownMethod {
+	fn description(&self) -> &str {
+		"schema of unknown method"
+	}
+}
+
+/// Attaches schemas of params and result to the method registered under given name.
+/// Schemas given for an alias are attached to the aliased method.
+pub fn attach<T: Metadata>(
+	methods: &mut HashMap<String, RemoteProcedure<T>>,
+	name: &str,
+	params: Option<Value>,
+	result: Option<Value>,
+) -> Result<(), UnknownMethod> {
+	let target = match methods.get(name) {
+		Some(&RemoteProcedure::Alias(ref alias)) => alias.clone(),
+		_ => name.to_owned(),
+	};
+
+	match methods.remove(&target) {
+		Some(RemoteProcedure::Method(method)) => {
+			methods.insert(target, RemoteProcedure::Method(method).with_schema(params, result));
+			Ok(())
+		},
+		Some(procedure) => {
+			methods.insert(target, procedure);
+			Err(UnknownMethod { name: name.into() })
+		},
+		None => Err(UnknownMethod { name: name.into() }),
+	}
+}
+
+/// Validates `value` against given JSON Schema.
+pub fn validate(schema: &Value, value: &Value) -> Result<(), SchemaViolation> {
+	Validator { pointer: String::new() }.validate(schema, value)
+}
+
+/// Validates request params against given JSON Schema and returns them if they are valid.
+/// Missing params are validated as `null` (the same way `Params::parse` treats them).
+pub fn validate_params(schema: &Value, params: Params) -> Result<Params, Error> {
+	let value = match params {
+		Params::Array(vec) => Value::Array(vec),
+		Params::Map(map) => Value::Object(map),
+		Params::None => Value::Null,
+	};
+
+	validate(schema, &value).map_err(SchemaViolation::into_params_error)?;
+	Ok(match value {
+		Value::Array(vec) => Params::Array(vec),
+		Value::Object(map) => Params::Map(map),
+		_ => Params::None,
+	})
+}
+
+/// Returns schema of params of methods without arguments.
+/// Params may be missing, `null`, an empty array or an empty object.
+pub fn no_params() -> Value {
+	let mut array = Map::new();
+	array.insert("type".into(), "array".into());
+	array.insert("maxItems".into(), 0.into());
+
+	let mut object = Map::new();
+	object.insert("type".into(), "object".into());
+	object.insert("additionalProperties".into(), false.into());
+
+	let mut schema = Map::new();
+	schema.insert("anyOf".into(), Value::Array(vec![
+		schema_of_type("null"),
+		Value::Object(array),
+		Value::Object(object),
+	]));
+	Value::Object(schema)
+}
+
+struct Validator {
+	pointer: String,
+}
+
+impl Validator {
+	fn violation<T: Into<String>>(&self, message: T) -> SchemaViolation {
+		SchemaViolation {
+			pointer: self.pointer.clone(),
+			message: message.into(),
+		}
+	}
+
+	fn fork(&self) -> Self {
+		Validator { pointer: self.pointer.clone() }
+	}
+
+	fn nested<F>(&mut self, token: &str, f: F) -> Result<(), SchemaViolation> where
+		F: FnOnce(&mut Self) -> Result<(), SchemaViolation>,
+	{
+		let len = self.pointer.len();
+		self.pointer.push('/');
+		self.pointer.push_str(&token.replace('~', "~0").replace('/', "~1"));
+		let result = f(self);
+		self.pointer.truncate(len);
+		result
+	}
+
+	fn validate(&mut self, schema: &Value, value: &Value) -> Result<(), SchemaViolation> {
+		let schema = match *schema {
+			Value::Bool(true) => return Ok(()),
+			Value::Bool(false) => return Err(self.violation("no value is allowed")),
+			Value::Object(ref schema) => schema,
+			_ => return Ok(()),
+		};
+
+		if let Some(ty) = schema.get("type") {
+			self.validate_type(ty, value)?;
+		}
+		if let Some(&Value::Array(ref variants)) = schema.get("enum") {
+			if !variants.contains(value) {
+				return Err(self.violation(format!("expected one of {}", Value::Array(variants.clone()))));
+			}
+		}
+		if let Some(expected) = schema.get("const") {
+			if expected != value {
+				return Err(self.violation(format!("expected {}", expected)));
+			}
+		}
+		if let Some(&Value::Array(ref schemas)) = schema.get("allOf") {
+			for schema in schemas {
+				self.validate(schema, value)?;
+			}
+		}
+		if let Some(&Value::Array(ref schemas)) = schema.get("anyOf") {
+			let mut first_violation = None;
+			for schema in schemas {
+				match self.fork().validate(schema, value) {
+					Ok(()) => {
+						first_violation = None;
+						break;
+					},
+					Err(violation) => if first_violation.is_none() {
+						first_violation = Some(violation);
+					},
+				}
+			}
+			if let Some(violation) = first_violation {
+				return Err(violation);
+			}
+		}
+		if let Some(not) = schema.get("not") {
+			if self.fork().validate(not, value).is_ok() {
+				return Err(self.violation("value matches disallowed schema"));
+			}
+		}
+
+		match *value {
+			Value::Number(ref number) => self.validate_number(schema, number),
+			Value::String(ref string) => self.validate_string(schema, string),
+			Value::Array(ref array) => self.validate_array(schema, array),
+			Value::Object(ref object) => self.validate_object(schema, object),
+			Value::Null | Value::Bool(_) => Ok(()),
+		}
+	}
+
+	fn validate_type(&self, ty: &Value, value: &Value) -> Result<(), SchemaViolation> {
+		let matches = |ty: &str| match (ty, value) {
+			("null", &Value::Null) => true,
+			("boolean", &Value::Bool(_)) => true,
+			("string", &Value::String(_)) => true,
+			("array", &Value::Array(_)) => true,
+			("object", &Value::Object(_)) => true,
+			("number", &Value::Number(_)) => true,
+			("integer", &Value::Number(ref n)) => n.is_i64() || n.is_u64() || n.as_f64().map_or(false, |n| n.fract() == 0.0),
+			_ => false,
+		};
+
+		let (valid, expected) = match *ty {
+			Value::String(ref ty) => (matches(ty), ty.clone()),
+			Value::Array(ref types) => {
+				let types = types.iter().filter_map(Value::as_str).collect::<Vec<_>>();
+				(types.iter().any(|ty| matches(ty)), types.join(" or "))
+			},
+			_ => return Ok(()),
+		};
+
+		if valid {
+			Ok(())
+		} else {
+			Err(self.violation(format!("expected {}, got {}", expected, type_name(value))))
+		}
+	}
+
+	fn validate_number(&self, schema: &Map<String, Value>, number: &Number) -> Result<(), SchemaViolation> {
+		let limit = |key: &str| match schema.get(key) {
+			Some(&Value::Number(ref limit)) => compare(number, limit).map(|ordering| (ordering, limit)),
+			_ => None,
+		};
+
+		if let Some((ordering, min)) = limit("minimum") {
+			if ordering == Ordering::Less {
+				return Err(self.violation(format!("expected value greater than or equal to {}", min)));
+			}
+		}
+		if let Some((ordering, max)) = limit("maximum") {
+			if ordering == Ordering::Greater {
+				return Err(self.violation(format!("expected value less than or equal to {}", max)));
+			}
+		}
+		if let Some((ordering, min)) = limit("exclusiveMinimum") {
+			if ordering != Ordering::Greater {
+				return Err(self.violation(format!("expected value greater than {}", min)));
+			}
+		}
+		if let Some((ordering, max)) = limit("exclusiveMaximum") {
+			if ordering != Ordering::Less {
+				return Err(self.violation(format!("expected value less than {}", max)));
+			}
+		}
+		if let (Some(number), Some(divisor)) = (number.as_f64(), schema.get("multipleOf").and_then(Value::as_f64)) {
+			if divisor > 0.0 && (number / divisor).fract() != 0.0 {
+				return Err(self.violation(format!("expected multiple of {}", divisor)));
+			}
+		}
+		Ok(())
+	}
+
+	fn validate_string(&self, schema: &Map<String, Value>, string: &str) -> Result<(), SchemaViolation> {
+		let len = string.chars().count() as u64;
+
+		if let Some(min) = schema.get("minLength").and_then(Value::as_u64) {
+			if len < min {
+				return Err(self.violation(format!("expected at least {} characters", min)));
+			}
+		}
+		if let Some(max) = schema.get("maxLength").and_then(Value::as_u64) {
+			if len > max {
+				return Err(self.violation(format!("expected at most {} characters", max)));
+			}
+		}
+		Ok(())
+	}
+
+	fn validate_array(&mut self, schema: &Map<String, Value>, array: &[Value]) -> Result<(), SchemaViolation> {
+		let len = array.len() as u64;
+
+		if let Some(min) = schema.get("minItems").and_then(Value::as_u64) {
+			if len < min {
+				return Err(self.violation(format!("expected at least {} items", min)));
+			}
+		}
+		if let Some(max) = schema.get("maxItems").and_then(Value::as_u64) {
+			if len > max {
+				return Err(self.violation(format!("expected at most {} items", max)));
+			}
+		}
+
+		match schema.get("items") {
+			Some(&Value::Array(ref schemas)) => {
+				for (idx, item) in array.iter().enumerate() {
+					let schema = match (schemas.get(idx), schema.get("additionalItems")) {
+						(Some(schema), _) => schema,
+						(None, Some(additional)) => additional,
+						(None, None) => break,
+					};
+					self.nested(&idx.to_string(), |v| v.validate(schema, item))?;
+				}
+			},
+			Some(schema) => {
+				for (idx, item) in array.iter().enumerate() {
+					self.nested(&idx.to_string(), |v| v.validate(schema, item))?;
+				}
+			},
+			None => {},
+		}
+		Ok(())
+	}
+
+	fn validate_object(&mut self, schema: &Map<String, Value>, object: &Map<String, Value>) -> Result<(), SchemaViolation> {
+		if let Some(&Value::Array(ref required)) = schema.get("required") {
+			for key in required.iter().filter_map(Value::as_str) {
+				if !object.contains_key(key) {
+					return Err(self.violation(format!("missing required property `{}`", key)));
+				}
+			}
+		}
+
+		let properties = match schema.get("properties") {
+			Some(&Value::Object(ref properties)) => Some(properties),
+			_ => None,
+		};
+		for (key, value) in object {
+			let schema = match (properties.and_then(|p| p.get(key)), schema.get("additionalProperties")) {
+				(Some(schema), _) => schema,
+				(None, Some(&Value::Bool(false))) => {
+					return Err(self.violation(format!("unexpected property `{}`", key)));
+				},
+				(None, Some(additional)) => additional,
+				(None, None) => continue,
+			};
+			self.nested(key, |v| v.validate(schema, value))?;
+		}
+		Ok(())
+	}
+}
+
+// Integers are compared exactly, converting them to `f64` could round large values.
+fn compare(a: &Number, b: &Number) -> Option<Ordering> {
+	if let (Some(a), Some(b)) = (a.as_i64(), b.as_i64()) {
+		return Some(a.cmp(&b));
+	}
+	if let (Some(a), Some(b)) = (a.as_u64(), b.as_u64()) {
+		return Some(a.cmp(&b));
+	}
+	// Integer above `i64::MAX` and a negative one.
+	if a.is_u64() && b.is_i64() {
+		return Some(Ordering::Greater);
+	}
+	if a.is_i64() && b.is_u64() {
+		return Some(Ordering::Less);
+	}
+
+	match (a.as_f64(), b.as_f64()) {
+		(Some(a), Some(b)) => a.partial_cmp(&b),
+		_ => None,
+	}
+}
+
+fn type_name(value: &Value) -> &'static str {
+	match *value {
+		Value::Null => "null",
+		Value::Bool(_) => "boolean",
+		Value::Number(_) => "number",
+		Value::String(_) => "string",
+		Value::Array(_) => "array",
+		Value::Object(_) => "object",
+	}
+}
+
+/// Types with known JSON Schema of their serialized form.
+pub trait JsonSchema {
+	/// Returns JSON Schema describing serialized form of the type.
+	fn json_schema() -> Value;
+
+	/// Can the value be omitted when it's a trailing parameter?
+	fn is_optional() -> bool {
+		false
+	}
+}
+
+fn schema_of_type(ty: &str) -> Value {
+	let mut schema = Map::new();
+	schema.insert("type".into(), ty.into());
+	Value::Object(schema)
+}
+
+macro_rules! primitive_schema {
+	($ty: expr => $($t: ty),+) => {
+		$(
+			impl JsonSchema for $t {
+				fn json_schema() -> Value {
+					schema_of_type($ty)
+				}
+			}
+		)+
+	}
+}
+
+macro_rules! integer_schema {
+	($($t: ident),+) => {
+		$(
+			impl JsonSchema for $t {
+				fn json_schema() -> Value {
+					let mut schema = Map::new();
+					schema.insert("type".into(), "integer".into());
+					schema.insert("minimum".into(), $t::MIN.into());
+					schema.insert("maximum".into(), $t::MAX.into());
+					Value::Object(schema)
+				}
+			}
+		)+
+	}
+}
+
+primitive_schema!("boolean" => bool);
+primitive_schema!("string" => String, char);
+primitive_schema!("number" => f32, f64);
+primitive_schema!("null" => ());
+integer_schema!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+impl JsonSchema for Value {
+	fn json_schema() -> Value {
+		Value::Object(Map::new())
+	}
+}
+
+impl<T: JsonSchema> JsonSchema for Option<T> {
+	fn json_schema() -> Value {
+		let mut schema = Map::new();
+		schema.insert("anyOf".into(), Value::Array(vec![T::json_schema(), schema_of_type("null")]));
+		Value::Object(schema)
+	}
+
+	fn is_optional() -> bool {
+		true
+	}
+}
+
+impl<T: JsonSchema> JsonSchema for Vec<T> {
+	fn json_schema() -> Value {
+		let mut schema = Map::new();
+		schema.insert("type".into(), "array".into());
+		schema.insert("items".into(), T::json_schema());
+		Value::Object(schema)
+	}
+}
+
+impl<T: JsonSchema> JsonSchema for HashMap<String, T> {
+	fn json_schema() -> Value {
+		let mut schema = Map::new();
+		schema.insert("type".into(), "object".into());
+		schema.insert("additionalProperties".into(), T::json_schema());
+		Value::Object(schema)
+	}
+}
+
+impl<T: JsonSchema> JsonSchema for BTreeMap<String, T> {
+	fn json_schema() -> Value {
+		HashMap::<String, T>::json_schema()
+	}
+}
+
+// Tuples describe positional params. Trailing optional items may be omitted,
+// so params consisting only of optional items may be missing altogether.
+macro_rules! tuple_schema {
+	($($x: ident),+) => {
+		impl<$($x: JsonSchema),+> JsonSchema for ($($x,)+) {
+			fn json_schema() -> Value {
+				let items = vec![$($x::json_schema()),+];
+				let optional = [$($x::is_optional()),+];
+				let required = optional.iter().rposition(|optional| !optional).map_or(0, |idx| idx + 1);
+
+				let mut schema = Map::new();
+				if required == 0 {
+					schema.insert("type".into(), Value::Array(vec!["array".into(), "null".into()]));
+				} else {
+					schema.insert("type".into(), "array".into());
+				}
+				schema.insert("minItems".into(), required.into());
+				schema.insert("maxItems".into(), items.len().into());
+				schema.insert("items".into(), Value::Array(items));
+				Value::Object(schema)
+			}
+		}
+	}
+}
+
+tuple_schema!(A);
+tuple_schema!(A, B);
+tuple_schema!(A, B, C);
+tuple_schema!(A, B, C, D);
+tuple_schema!(A, B, C, D, E);
+tuple_schema!(A, B, C, D, E, F);
+tuple_schema!(A, B, C, D, E, F, G);
+tuple_schema!(A, B, C, D, E, F, G, H);
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use types::Value;
+	use types::Params;
+	use super::{no_params, validate, validate_params, JsonSchema};
+
+	fn json(s: &str) -> Value {
+		serde_json::from_str(s).unwrap()
+	}
+
+	#[test]
+	fn should_report_pointer_of_invalid_value() {
+		// given
+		let schema = json(r#"{
+			"type": "array",
+			"items": [{"type": "object", "properties": {"a/b": {"type": "array", "items": {"type": "integer", "maximum": 10}}}}]
+		}"#);
+		let value = json(r#"[{"a/b": [1, 11]}]"#);
+
+		// when
+		let violation = validate(&schema, &value).unwrap_err();
+
+		// then
+		assert_eq!(violation.pointer, "/0/a~1b/1");
+		assert_eq!(violation.message, "expected value less than or equal to 10");
+	}
+
+	#[test]
+	fn should_compare_integers_exactly() {
+		let schema = json(r#"{"minimum": -1, "maximum": 18446744073709551614, "exclusiveMaximum": 9007199254740993}"#);
+
+		assert!(validate(&schema, &json("9007199254740992")).is_ok());
+		assert!(validate(&schema, &json("-1")).is_ok());
+		assert_eq!(validate(&schema, &json("9007199254740993")).unwrap_err().message, "expected value less than 9007199254740993");
+		assert_eq!(validate(&schema, &json("18446744073709551615")).unwrap_err().message, "expected value less than or equal to 18446744073709551614");
+		assert_eq!(validate(&schema, &json("-2")).unwrap_err().message, "expected value greater than or equal to -1");
+		assert_eq!(validate(&schema, &json("-1.5")).unwrap_err().message, "expected value greater than or equal to -1");
+	}
+
+	#[test]
+	fn should_validate_required_and_additional_properties() {
+		let schema = json(r#"{"type": "object", "required": ["a"], "additionalProperties": false, "properties": {"a": {}}}"#);
+
+		assert!(validate(&schema, &json(r#"{"a": 1}"#)).is_ok());
+		assert_eq!(validate(&schema, &json(r#"{}"#)).unwrap_err().message, "missing required property `a`");
+		assert_eq!(validate(&schema, &json(r#"{"a": 1, "b": 2}"#)).unwrap_err().message, "unexpected property `b`");
+	}
+
+	#[test]
+	fn should_derive_schema_of_params() {
+		// given
+		let schema = <(u8, String, Option<bool>)>::json_schema();
+
+		// then
+		assert!(validate(&schema, &json(r#"[1, "a"]"#)).is_ok());
+		assert!(validate(&schema, &json(r#"[1, "a", null]"#)).is_ok());
+		assert!(validate(&schema, &json(r#"[1]"#)).is_err());
+		let violation = validate(&schema, &json(r#"[256, "a"]"#)).unwrap_err();
+		assert_eq!(violation.pointer, "/0");
+		assert_eq!(violation.message, "expected value less than or equal to 255");
+		let violation = validate(&schema, &json(r#"[1, "a", 5]"#)).unwrap_err();
+		assert_eq!(violation.pointer, "/2");
+		assert_eq!(violation.message, "expected boolean, got number");
+	}
+
+	#[test]
+	fn should_accept_empty_params_of_methods_without_arguments() {
+		// given
+		let schema = no_params();
+
+		// then
+		assert_eq!(validate_params(&schema, Params::None), Ok(Params::None));
+		assert_eq!(validate_params(&schema, Params::Array(vec![])), Ok(Params::Array(vec![])));
+		assert!(validate(&schema, &json(r#"{}"#)).is_ok());
+		assert!(validate_params(&schema, Params::Array(vec![1.into()])).is_err());
+		assert!(validate(&schema, &json(r#"{"a": 1}"#)).is_err());
+	}
+}
@@ -3,6 +3,9 @@ use serde::de::{Deserialize, Deserializer};
 use serde::ser::{Serialize, Serializer};
 use super::Value;
 
+/// Code of errors returned when the server is overloaded.
+pub const SERVER_BUSY_CODE: i64 = -32005;
+
 /// JSONRPC error code
 #[derive(Debug, PartialEq, Clone)]
 pub enum ErrorCode {
@@ -137,4 +140,18 @@ impl Error {
 			data: None,
 		}
 	}
+
+	/// Creates new error indicating the server is overloaded
+	pub fn server_busy() -> Self {
+		Error {
+			code: ErrorCode::ServerError(SERVER_BUSY_CODE),
+			message: "Server is busy, try again later".to_owned(),
+			data: None,
+		}
+	}
+
+	/// Returns `true` if the error was caused by an overloaded server
+	pub fn is_server_busy(&self) -> bool {
+		self.code == ErrorCode::ServerError(SERVER_BUSY_CODE)
+	}
 }
@@ -8,12 +8,12 @@ pub mod response;
 pub mod version;
 
 pub use serde_json::Value;
-pub use serde_json::value::to_value;
+pub use serde_json::value::{to_value, RawValue};
 pub use serde_json::to_string;
 
-pub use self::error::{ErrorCode, Error};
+pub use self::error::{ErrorCode, Error, SERVER_BUSY_CODE};
 pub use self::id::Id;
-pub use self::params::Params;
-pub use self::request::{Request, Call, MethodCall, Notification};
+pub use self::params::{Params, RawParams};
+pub use self::request::{Request, Call, MethodCall, Notification, RawRequest, RawCall, RawMethodCall};
 pub use self::response::{Output, Response, Success, Failure};
 pub use self::version::Version;
@@ -4,7 +4,7 @@ use std::fmt;
 use serde::{Serialize, Serializer, Deserialize, Deserializer};
 use serde::de::{Visitor, SeqAccess, MapAccess, DeserializeOwned};
 use serde_json;
-use serde_json::value::from_value;
+use serde_json::value::{from_value, RawValue};
 
 use super::{Value, Error};
 
@@ -34,6 +34,68 @@ impl Params {
 	}
 }
 
+/// Request parameters kept as unparsed JSON (borrowed from the request).
+///
+/// Allows typed methods to deserialize arguments directly from the request
+/// without building intermediate `Value`s.
+#[derive(Debug, Clone, Copy)]
+pub struct RawParams<'a>(Option<&'a RawValue>);
+
+impl<'a> RawParams<'a> {
+	/// Wraps raw JSON of the `params` field (`None` if it was omitted).
+	pub fn new(raw: Option<&'a RawValue>) -> Self {
+		RawParams(raw)
+	}
+
+	/// Returns raw JSON of the params (if any).
+	pub fn get(&self) -> Option<&'a str> {
+		self.0.map(RawValue::get)
+	}
+
+	/// Returns `true` if params are omitted or empty,
+	/// i.e. would be represented as `Params::None`.
+	pub fn is_empty(&self) -> bool {
+		let json = match self.0 {
+			Some(raw) => raw.get(),
+			None => return true,
+		};
+
+		let mut chars = json.chars().filter(|c| !c.is_whitespace());
+		match (chars.next(), chars.next(), chars.next()) {
+			(Some('['), Some(']'), None) | (Some('{'), Some('}'), None) => true,
+			_ => false,
+		}
+	}
+
+	/// Parse params directly into expected types.
+	/// Behaves the same way as `Params::parse`.
+	pub fn parse<D>(&self) -> Result<D, Error> where D: Deserialize<'a> {
+		let result = match self.0 {
+			Some(raw) if !self.is_empty() => serde_json::from_str(raw.get()),
+			_ => D::deserialize(Value::Null),
+		};
+
+		result.map_err(|e| {
+			// Position within params is meaningless to the client and `Params::parse` doesn't report it.
+			let mut message = e.to_string();
+			let position = format!(" at line {} column {}", e.line(), e.column());
+			if e.line() > 0 && message.ends_with(&*position) {
+				let len = message.len() - position.len();
+				message.truncate(len);
+			}
+			Error::invalid_params(format!("Invalid params: {}.", message))
+		})
+	}
+
+	/// Converts into regular `Params`.
+	pub fn to_params(&self) -> Result<Params, Error> {
+		match self.0 {
+			Some(raw) => serde_json::from_str(raw.get()).map_err(|_| Error::invalid_request()),
+			None => Ok(Params::None),
+		}
+	}
+}
+
 impl Serialize for Params {
 	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
 	where S: Serializer {
@@ -91,7 +153,8 @@ impl<'a> Visitor<'a> for ParamsVisitor {
 #[cfg(test)]
 mod tests {
 	use serde_json;
-	use super::Params;
+	use serde_json::value::RawValue;
+	use super::{Params, RawParams};
 	use types::{Value, Error, ErrorCode};
 
 	#[test]
@@ -109,6 +172,25 @@ mod tests {
 								 Value::Array(vec![Value::from(0)]), Value::Object(map)]), deserialized);
 	}
 
+	#[test]
+	fn raw_params_should_parse_like_params() {
+		// given
+		let raw = |s| serde_json::from_str::<Box<RawValue>>(s).unwrap();
+		let (array, empty) = (raw(r#"[1, "hello"]"#), raw("[ ]"));
+
+		// when
+		let v1: Result<(u8, String), Error> = RawParams::new(Some(&array)).parse();
+		let v2: Result<(u8, bool), Error> = RawParams::new(Some(&array)).parse();
+		let v3: Result<(), Error> = RawParams::new(Some(&empty)).parse();
+
+		// then
+		assert_eq!(v1.unwrap(), (1, "hello".to_owned()));
+		assert_eq!(v2.unwrap_err(), RawParams::new(Some(&array)).to_params().unwrap().parse::<(u8, bool)>().unwrap_err());
+		assert_eq!(v3, Ok(()));
+		assert!(RawParams::new(None).is_empty());
+		assert_eq!(RawParams::new(Some(&empty)).to_params(), Ok(Params::None));
+	}
+
 	#[test]
 	fn should_return_meaningful_error_when_deserialization_fails() {
 		// given
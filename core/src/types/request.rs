@@ -1,15 +1,20 @@
 //! jsonrpc request
-use serde::de::{Deserialize, Deserializer, Error as DeError};
-use serde::ser::{Serialize, Serializer, Error as SerError};
-use serde_json::{from_value, Error as JsonError};
+use std::borrow::Cow;
+use std::fmt;
 
-use super::{Id, Params, Version, Value};
+use serde::de::{Deserialize, Deserializer, Error as DeError, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeStruct, Serializer, Error as SerError};
+use serde_json::{from_str, from_value, Error as JsonError};
+use serde_json::value::RawValue;
+
+use super::{Id, Params, RawParams, Version, Value};
 
 /// Represents jsonrpc request which is a method call.
 #[derive(Debug, PartialEq, Deserialize, Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct MethodCall {
 	/// A String specifying the version of the JSON-RPC protocol.
+	#[serde(skip_serializing_if = "Option::is_none")]
 	pub jsonrpc: Option<Version>,
 	/// A String containing the name of the method to be invoked.
 	pub method: String,
@@ -23,7 +28,9 @@ pub struct MethodCall {
 }
 
 /// Represents jsonrpc request which is a notification.
-#[derive(Debug, PartialEq, Deserialize, Serialize)]
+///
+/// JSON-RPC 1.0 notifications (`jsonrpc` is `None`) are serialized with `"id": null`.
+#[derive(Debug, PartialEq, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct Notification {
 	/// A String specifying the version of the JSON-RPC protocol.
@@ -35,6 +42,23 @@ pub struct Notification {
 	pub params: Option<Params>,
 }
 
+impl Serialize for Notification {
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+	where S: Serializer {
+		let mut state = serializer.serialize_struct("Notification", 3)?;
+		match self.jsonrpc {
+			Some(ref jsonrpc) => state.serialize_field("jsonrpc", jsonrpc)?,
+			None => state.skip_field("jsonrpc")?,
+		}
+		state.serialize_field("method", &self.method)?;
+		state.serialize_field("params", &self.params)?;
+		if self.jsonrpc.is_none() {
+			state.serialize_field("id", &Id::Null)?;
+		}
+		state.end()
+	}
+}
+
 /// Represents single jsonrpc call.
 #[derive(Debug, PartialEq)]
 pub enum Call {
@@ -126,6 +150,220 @@ impl<'a> Deserialize<'a> for Request {
 	}
 }
 
+/// Represents jsonrpc method call or notification
+/// with params kept as raw JSON borrowed from the request.
+#[derive(Debug)]
+pub struct RawMethodCall<'a> {
+	/// A String specifying the version of the JSON-RPC protocol.
+	pub jsonrpc: Option<Version>,
+	/// A String containing the name of the method to be invoked.
+	pub method: Cow<'a, str>,
+	/// Unparsed parameters of the call.
+	pub params: RawParams<'a>,
+	/// An identifier of the call, `None` for notifications.
+	pub id: Option<Id>,
+}
+
+/// Represents single jsonrpc call with params kept as raw JSON.
+///
+/// Deserialization accepts any JSON value, calls which are not well-formed
+/// are represented as `RawCall::Invalid` (the same way as `Call::Invalid`).
+#[derive(Debug)]
+pub enum RawCall<'a> {
+	/// Method call or notification
+	Call(RawMethodCall<'a>),
+	/// Invalid call
+	Invalid(Id),
+}
+
+impl<'a> Deserialize<'a> for RawCall<'a> {
+	fn deserialize<D>(deserializer: D) -> Result<RawCall<'a>, D::Error>
+	where D: Deserializer<'a> {
+		deserializer.deserialize_any(RawCallVisitor)
+	}
+}
+
+// Values other than objects are invalid calls without an id.
+macro_rules! visit_invalid_call {
+	($wrap: expr) => {
+		fn visit_bool<E>(self, _value: bool) -> Result<Self::Value, E> where E: DeError {
+			Ok($wrap(RawCall::Invalid(Id::Null)))
+		}
+
+		fn visit_i64<E>(self, _value: i64) -> Result<Self::Value, E> where E: DeError {
+			Ok($wrap(RawCall::Invalid(Id::Null)))
+		}
+
+		fn visit_u64<E>(self, _value: u64) -> Result<Self::Value, E> where E: DeError {
+			Ok($wrap(RawCall::Invalid(Id::Null)))
+		}
+
+		fn visit_f64<E>(self, _value: f64) -> Result<Self::Value, E> where E: DeError {
+			Ok($wrap(RawCall::Invalid(Id::Null)))
+		}
+
+		fn visit_str<E>(self, _value: &str) -> Result<Self::Value, E> where E: DeError {
+			Ok($wrap(RawCall::Invalid(Id::Null)))
+		}
+
+		fn visit_unit<E>(self) -> Result<Self::Value, E> where E: DeError {
+			Ok($wrap(RawCall::Invalid(Id::Null)))
+		}
+	}
+}
+
+struct RawCallVisitor;
+
+impl<'a> Visitor<'a> for RawCallVisitor {
+	type Value = RawCall<'a>;
+
+	fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+		formatter.write_str("a jsonrpc call")
+	}
+
+	visit_invalid_call!(|call| call);
+
+	fn visit_seq<V>(self, mut visitor: V) -> Result<Self::Value, V::Error>
+	where V: SeqAccess<'a> {
+		while let Some(IgnoredAny) = visitor.next_element()? {}
+		Ok(RawCall::Invalid(Id::Null))
+	}
+
+	fn visit_map<V>(self, mut visitor: V) -> Result<Self::Value, V::Error>
+	where V: MapAccess<'a> {
+		let mut valid = true;
+		let mut jsonrpc = None;
+		let mut method = None;
+		let mut params = None;
+		let mut id = None;
+
+		// Fields are kept raw, so that a malformed one doesn't prevent reading the id.
+		while let Some(key) = visitor.next_key::<CowStr>()? {
+			let field = match &*key.0 {
+				"jsonrpc" => &mut jsonrpc,
+				"method" => &mut method,
+				"params" => &mut params,
+				"id" => &mut id,
+				_ => {
+					visitor.next_value::<IgnoredAny>()?;
+					valid = false;
+					continue;
+				},
+			};
+			valid &= field.is_none();
+			*field = Some(visitor.next_value::<&'a RawValue>()?);
+		}
+
+		let id = match id.map(|id| from_str::<Id>(id.get())) {
+			Some(Ok(id)) => Some(id),
+			Some(Err(_)) => return Ok(RawCall::Invalid(Id::Null)),
+			None => None,
+		};
+		let invalid = || Ok(RawCall::Invalid(id.clone().unwrap_or(Id::Null)));
+		if !valid {
+			return invalid();
+		}
+
+		let jsonrpc = match jsonrpc.map(|jsonrpc| from_str::<Option<Version>>(jsonrpc.get())) {
+			Some(Ok(jsonrpc)) => jsonrpc,
+			Some(Err(_)) => return invalid(),
+			None => None,
+		};
+		let method = match method.map(|method| from_str::<CowStr>(method.get())) {
+			Some(Ok(method)) => method.0,
+			_ => return invalid(),
+		};
+		// Params have to be structured, same as in `Params`.
+		let params = match params {
+			Some(raw) if raw.get() == "null" => None,
+			Some(raw) if raw.get().starts_with(|c| c == '[' || c == '{') => Some(raw),
+			Some(_) => return invalid(),
+			None => None,
+		};
+
+		Ok(RawCall::Call(RawMethodCall {
+			jsonrpc,
+			method,
+			params: RawParams::new(params),
+			id,
+		}))
+	}
+}
+
+/// String that is borrowed from the input unless it contains escape sequences.
+struct CowStr<'a>(Cow<'a, str>);
+
+impl<'a> Deserialize<'a> for CowStr<'a> {
+	fn deserialize<D>(deserializer: D) -> Result<CowStr<'a>, D::Error>
+	where D: Deserializer<'a> {
+		deserializer.deserialize_str(CowStrVisitor)
+	}
+}
+
+struct CowStrVisitor;
+
+impl<'a> Visitor<'a> for CowStrVisitor {
+	type Value = CowStr<'a>;
+
+	fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+		formatter.write_str("a string")
+	}
+
+	fn visit_borrowed_str<E>(self, value: &'a str) -> Result<Self::Value, E> where E: DeError {
+		Ok(CowStr(Cow::Borrowed(value)))
+	}
+
+	fn visit_str<E>(self, value: &str) -> Result<Self::Value, E> where E: DeError {
+		Ok(CowStr(Cow::Owned(value.to_owned())))
+	}
+
+	fn visit_string<E>(self, value: String) -> Result<Self::Value, E> where E: DeError {
+		Ok(CowStr(Cow::Owned(value)))
+	}
+}
+
+/// Represents jsonrpc request with params kept as raw JSON.
+#[derive(Debug)]
+pub enum RawRequest<'a> {
+	/// Single request (call)
+	Single(RawCall<'a>),
+	/// Batch of requests (calls)
+	Batch(Vec<RawCall<'a>>),
+}
+
+impl<'a> Deserialize<'a> for RawRequest<'a> {
+	fn deserialize<D>(deserializer: D) -> Result<RawRequest<'a>, D::Error>
+	where D: Deserializer<'a> {
+		deserializer.deserialize_any(RawRequestVisitor)
+	}
+}
+
+struct RawRequestVisitor;
+
+impl<'a> Visitor<'a> for RawRequestVisitor {
+	type Value = RawRequest<'a>;
+
+	fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+		formatter.write_str("a jsonrpc call or a batch of calls")
+	}
+
+	visit_invalid_call!(RawRequest::Single);
+
+	fn visit_seq<V>(self, mut visitor: V) -> Result<Self::Value, V::Error>
+	where V: SeqAccess<'a> {
+		let mut calls = Vec::with_capacity(visitor.size_hint().unwrap_or(0));
+		while let Some(call) = visitor.next_element()? {
+			calls.push(call);
+		}
+		Ok(RawRequest::Batch(calls))
+	}
+
+	fn visit_map<V>(self, visitor: V) -> Result<Self::Value, V::Error>
+	where V: MapAccess<'a> {
+		RawCallVisitor.visit_map(visitor).map(RawRequest::Single)
+	}
+}
+
 #[test]
 fn method_call_serialize() {
 	use serde_json;
@@ -245,6 +483,62 @@ fn call_deserialize() {
 	}));
 }
 
+#[test]
+fn call_deserialize_jsonrpc_1() {
+	use serde_json;
+
+	// null id makes it a notification, but only if JSON-RPC 1.0 is supported (see `Compatibility`)
+	let s = r#"{"method": "update", "params": [1], "id": null}"#;
+	let deserialized: Call = serde_json::from_str(s).unwrap();
+	assert_eq!(deserialized, Call::MethodCall(MethodCall {
+		jsonrpc: None,
+		method: "update".to_owned(),
+		params: Some(Params::Array(vec![Value::from(1)])),
+		id: Id::Null
+	}));
+
+	let s = r#"{"method": "update", "params": [1], "id": 1}"#;
+	let deserialized: Call = serde_json::from_str(s).unwrap();
+	assert_eq!(deserialized, Call::MethodCall(MethodCall {
+		jsonrpc: None,
+		method: "update".to_owned(),
+		params: Some(Params::Array(vec![Value::from(1)])),
+		id: Id::Num(1)
+	}));
+
+	// null id is always a method call in JSON-RPC 2.0
+	let s = r#"{"jsonrpc": "2.0", "method": "update", "id": null}"#;
+	let deserialized: Call = serde_json::from_str(s).unwrap();
+	assert_eq!(deserialized, Call::MethodCall(MethodCall {
+		jsonrpc: Some(Version::V2),
+		method: "update".to_owned(),
+		params: None,
+		id: Id::Null
+	}));
+}
+
+#[test]
+fn call_serialize_jsonrpc_1() {
+	use serde_json;
+
+	let batch = Request::Batch(vec![
+		Call::MethodCall(MethodCall {
+			jsonrpc: None,
+			method: "update".to_owned(),
+			params: Some(Params::Array(vec![Value::from(1)])),
+			id: Id::Num(1)
+		}),
+		Call::Notification(Notification {
+			jsonrpc: None,
+			method: "update".to_owned(),
+			params: Some(Params::Array(vec![Value::from(1)]))
+		})
+	]);
+
+	let serialized = serde_json::to_string(&batch).unwrap();
+	assert_eq!(serialized, r#"[{"method":"update","params":[1],"id":1},{"method":"update","params":[1],"id":null}]"#);
+}
+
 #[test]
 fn request_deserialize_batch() {
 	use serde_json;
@@ -278,3 +572,63 @@ fn request_invalid_returns_id() {
 		_ => panic!("Request wrongly deserialized: {:?}", deserialized),
 	}
 }
+
+#[test]
+fn raw_request_deserialize() {
+	use serde_json;
+
+	let s = r#"[{"jsonrpc": "2.0", "method": "update", "params": [1, {"a": 2}], "id": 1}, {"method": "notify"}]"#;
+	let deserialized: RawRequest = serde_json::from_str(s).unwrap();
+
+	let calls = match deserialized {
+		RawRequest::Batch(calls) => calls.into_iter().map(|call| match call {
+			RawCall::Call(call) => call,
+			RawCall::Invalid(_) => panic!("Expected valid calls."),
+		}).collect::<Vec<_>>(),
+		RawRequest::Single(_) => panic!("Expected a batch."),
+	};
+	assert_eq!(calls.len(), 2);
+	assert_eq!(calls[0].jsonrpc, Some(Version::V2));
+	assert_eq!(calls[0].method, "update");
+	assert_eq!(calls[0].params.get(), Some(r#"[1, {"a": 2}]"#));
+	assert_eq!(calls[0].id, Some(Id::Num(1)));
+	assert_eq!(calls[1].jsonrpc, None);
+	assert!(calls[1].params.is_empty());
+	assert_eq!(calls[1].id, None);
+}
+
+#[test]
+fn raw_request_deserialize_null_id() {
+	use serde_json;
+
+	let id = |s| match serde_json::from_str::<RawCall>(s).unwrap() {
+		RawCall::Call(call) => call.id,
+		RawCall::Invalid(_) => panic!("Expected a valid call."),
+	};
+
+	assert_eq!(id(r#"{"method": "notify", "params": []}"#), None);
+	assert_eq!(id(r#"{"method": "notify", "params": [], "id": null}"#), Some(Id::Null));
+	assert_eq!(id(r#"{"jsonrpc": "2.0", "method": "call", "id": null}"#), Some(Id::Null));
+}
+
+#[test]
+fn raw_request_deserialize_invalid_calls() {
+	use serde_json;
+
+	let invalid = |s| match serde_json::from_str::<RawRequest>(s).unwrap() {
+		RawRequest::Single(RawCall::Invalid(id)) => vec![id],
+		RawRequest::Batch(calls) => calls.into_iter().map(|call| match call {
+			RawCall::Invalid(id) => id,
+			RawCall::Call(call) => panic!("Expected an invalid call: {:?}", call),
+		}).collect(),
+		request => panic!("Expected an invalid call: {:?}", request),
+	};
+
+	assert_eq!(invalid(r#"{"jsonrpc": "2.0", "id": 1}"#), vec![Id::Num(1)]);
+	assert_eq!(invalid(r#"{"method": "a", "params": 5, "id": 1}"#), vec![Id::Num(1)]);
+	assert_eq!(invalid(r#"{"method": "a", "extra": 5, "id": "x"}"#), vec![Id::Str("x".into())]);
+	assert_eq!(invalid(r#"{"method": 5, "id": [1]}"#), vec![Id::Null]);
+	assert_eq!(invalid(r#"[1, [2], null, {"method": "a", "method": "b", "id": 3}]"#), vec![Id::Null, Id::Null, Id::Null, Id::Num(3)]);
+	assert_eq!(invalid(r#""call""#), vec![Id::Null]);
+	assert!(serde_json::from_str::<RawRequest>(r#"[{"method": "a"}"#).is_err());
+}
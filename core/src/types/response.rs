@@ -1,27 +1,48 @@
 //! jsonrpc response
 use serde::de::{Deserialize, Deserializer, Error as DeError};
-use serde::ser::{Serialize, Serializer};
-use serde_json::value::from_value;
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use serde_json;
+use serde_json::value::{from_value, RawValue};
 use super::{Id, Value, Error, ErrorCode, Version};
 use {Result as CoreResult};
 
 /// Successful response
-#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct Success {
 	/// Protocol version
-    #[serde(skip_serializing_if = "Option::is_none")]
 	pub jsonrpc: Option<Version>,
 	/// Result
 	pub result: Value,
 	/// Correlation id
-	pub id: Id
+	pub id: Id,
+	/// Result already serialized to JSON (see `MetaIoHandler::add_method_with_raw_result`).
+	/// It's sent as-is instead of `result` (which is `null` then).
+	/// Never produced by deserialization.
+	#[serde(skip)]
+	pub raw_result: Option<Box<RawValue>>,
+}
+
+impl Success {
+	/// Returns the result, parsing pre-serialized one.
+	pub fn into_result(self) -> CoreResult<Value> {
+		match self.raw_result {
+			Some(raw) => serde_json::from_str(raw.get()).map_err(|_| Error::internal_error()),
+			None => Ok(self.result),
+		}
+	}
+}
+
+impl PartialEq for Success {
+	fn eq(&self, other: &Self) -> bool {
+		self.jsonrpc == other.jsonrpc && self.result == other.result && self.id == other.id
+			&& self.raw_result.as_ref().map(|raw| raw.get()) == other.raw_result.as_ref().map(|raw| raw.get())
+	}
 }
 
 /// Unsuccessful response
-#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Clone, Deserialize)]
 pub struct Failure {
 	/// Protocol Version
-    #[serde(skip_serializing_if = "Option::is_none")]
 	pub jsonrpc: Option<Version>,
 	/// Error
 	pub error: Error,
@@ -29,6 +50,57 @@ pub struct Failure {
 	pub id: Id
 }
 
+/// Serializes fields of an output.
+/// JSON-RPC 1.0 outputs (without `jsonrpc`) always carry both `result` and `error`,
+/// the one that doesn't apply being `null`.
+fn serialize_output<S, R, E>(
+	serializer: S,
+	name: &'static str,
+	jsonrpc: Option<Version>,
+	result: Option<&R>,
+	error: Option<&E>,
+	id: &Id,
+) -> Result<S::Ok, S::Error> where
+	S: Serializer,
+	R: Serialize + ?Sized,
+	E: Serialize,
+{
+	let mut state = serializer.serialize_struct(name, 4)?;
+	match jsonrpc {
+		Some(ref jsonrpc) => state.serialize_field("jsonrpc", jsonrpc)?,
+		None => state.skip_field("jsonrpc")?,
+	}
+	if jsonrpc.is_none() || result.is_some() {
+		state.serialize_field("result", &result)?;
+	} else {
+		state.skip_field("result")?;
+	}
+	if jsonrpc.is_none() || error.is_some() {
+		state.serialize_field("error", &error)?;
+	} else {
+		state.skip_field("error")?;
+	}
+	state.serialize_field("id", id)?;
+	state.end()
+}
+
+impl Serialize for Success {
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+	where S: Serializer {
+		match self.raw_result {
+			Some(ref raw) => serialize_output(serializer, "Success", self.jsonrpc, Some(&**raw), None::<&Error>, &self.id),
+			None => serialize_output(serializer, "Success", self.jsonrpc, Some(&self.result), None::<&Error>, &self.id),
+		}
+	}
+}
+
+impl Serialize for Failure {
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+	where S: Serializer {
+		serialize_output(serializer, "Failure", self.jsonrpc, None::<&Value>, Some(&self.error), &self.id)
+	}
+}
+
 /// Represents output - failure or success
 #[derive(Debug, PartialEq, Clone)]
 pub enum Output {
@@ -46,6 +118,7 @@ impl Output {
 				id: id,
 				jsonrpc: jsonrpc,
 				result: result,
+				raw_result: None,
 			}),
 			Err(error) => Output::Failure(Failure {
 				id: id,
@@ -83,9 +156,10 @@ impl Output {
 
 impl From<Output> for CoreResult<Value> {
 	/// Convert into a result. Will be `Ok` if it is a `Success` and `Err` if `Failure`.
+	/// Raw results are parsed into `Value`.
 	fn from(output: Output) -> CoreResult<Value> {
 		match output {
-			Output::Success(s) => Ok(s.result),
+			Output::Success(s) => s.into_result(),
 			Output::Failure(f) => Err(f.error),
 		}
 	}
@@ -106,7 +180,7 @@ impl Serialize for Output {
 	where S: Serializer {
 		match *self {
 			Output::Success(ref s) => s.serialize(serializer),
-			Output::Failure(ref f) => f.serialize(serializer)
+			Output::Failure(ref f) => f.serialize(serializer),
 		}
 	}
 }
@@ -171,7 +245,8 @@ fn success_output_serialize() {
 	let so = Output::Success(Success {
 		jsonrpc: Some(Version::V2),
 		result: Value::from(1),
-		id: Id::Num(1)
+		id: Id::Num(1),
+		raw_result: None,
 	});
 
 	let serialized = serde_json::to_string(&so).unwrap();
@@ -189,10 +264,27 @@ fn success_output_deserialize() {
 	assert_eq!(deserialized, Output::Success(Success {
 		jsonrpc: Some(Version::V2),
 		result: Value::from(1),
-		id: Id::Num(1)
+		id: Id::Num(1),
+		raw_result: None,
 	}));
 }
 
+#[test]
+fn raw_success_output_serialize() {
+	use serde_json;
+
+	let so = Output::Success(Success {
+		jsonrpc: Some(Version::V2),
+		result: Value::Null,
+		id: Id::Num(1),
+		raw_result: Some(RawValue::from_string(r#"{"a": [1, 2]}"#.into()).unwrap()),
+	});
+
+	let serialized = serde_json::to_string(&so).unwrap();
+	assert_eq!(serialized, r#"{"jsonrpc":"2.0","result":{"a": [1, 2]},"id":1}"#);
+	assert_eq!(CoreResult::from(so), Ok(serde_json::from_str(r#"{"a":[1,2]}"#).unwrap()));
+}
+
 #[test]
 fn failure_output_serialize() {
 	use serde_json;
@@ -218,7 +310,54 @@ fn failure_output_serialize_jsonrpc_1() {
 	});
 
 	let serialized = serde_json::to_string(&fo).unwrap();
-	assert_eq!(serialized, r#"{"error":{"code":-32700,"message":"Parse error"},"id":1}"#);
+	assert_eq!(serialized, r#"{"result":null,"error":{"code":-32700,"message":"Parse error"},"id":1}"#);
+}
+
+#[test]
+fn success_output_serialize_jsonrpc_1() {
+	use serde_json;
+
+	let so = Output::Success(Success {
+		jsonrpc: None,
+		result: Value::from(1),
+		id: Id::Num(1),
+		raw_result: None,
+	});
+
+	let serialized = serde_json::to_string(&so).unwrap();
+	assert_eq!(serialized, r#"{"result":1,"error":null,"id":1}"#);
+
+	let so = Output::Success(Success {
+		jsonrpc: None,
+		result: Value::Null,
+		id: Id::Num(1),
+		raw_result: Some(RawValue::from_string("[1]".into()).unwrap()),
+	});
+
+	let serialized = serde_json::to_string(&so).unwrap();
+	assert_eq!(serialized, r#"{"result":[1],"error":null,"id":1}"#);
+}
+
+#[test]
+fn output_deserialize_jsonrpc_1() {
+	use serde_json;
+
+	let dso = r#"{"result":null,"error":null,"id":1}"#;
+	let deserialized: Output = serde_json::from_str(dso).unwrap();
+	assert_eq!(deserialized, Output::Success(Success {
+		jsonrpc: None,
+		result: Value::Null,
+		id: Id::Num(1),
+		raw_result: None,
+	}));
+
+	let dfo = r#"{"result":null,"error":{"code":-32700,"message":"Parse error"},"id":1}"#;
+	let deserialized: Output = serde_json::from_str(dfo).unwrap();
+	assert_eq!(deserialized, Output::Failure(Failure {
+		jsonrpc: None,
+		error: Error::parse_error(),
+		id: Id::Num(1)
+	}));
 }
 
 #[test]
@@ -246,7 +385,8 @@ fn single_response_deserialize() {
 	assert_eq!(deserialized, Response::Single(Output::Success(Success {
 		jsonrpc: Some(Version::V2),
 		result: Value::from(1),
-		id: Id::Num(1)
+		id: Id::Num(1),
+		raw_result: None,
 	})));
 }
 
@@ -262,7 +402,8 @@ fn batch_response_deserialize() {
 		Output::Success(Success {
 			jsonrpc: Some(Version::V2),
 			result: Value::from(1),
-			id: Id::Num(1)
+			id: Id::Num(1),
+			raw_result: None,
 		}),
 		Output::Failure(Failure {
 			jsonrpc: Some(Version::V2),
@@ -0,0 +1,176 @@
+//! Compression of responses and decompression of request bodies.
+
+use std::io::{self, Read, Write};
+
+use brotli;
+use flate2;
+use hyper::header::{self, Encoding};
+
+/// Content coding supported by the server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentCoding {
+	/// `gzip`
+	Gzip,
+	/// `deflate` (zlib format)
+	Deflate,
+	/// `br`
+	Brotli,
+}
+
+impl ContentCoding {
+	fn from_encoding(encoding: &Encoding) -> Option<Self> {
+		match *encoding {
+			Encoding::Gzip => Some(ContentCoding::Gzip),
+			Encoding::Deflate => Some(ContentCoding::Deflate),
+			Encoding::Brotli => Some(ContentCoding::Brotli),
+			_ => None,
+		}
+	}
+
+	/// Returns the value of `Content-Encoding` header.
+	pub fn encoding(&self) -> Encoding {
+		match *self {
+			ContentCoding::Gzip => Encoding::Gzip,
+			ContentCoding::Deflate => Encoding::Deflate,
+			ContentCoding::Brotli => Encoding::Brotli,
+		}
+	}
+}
+
+/// Compression of responses.
+#[derive(Debug, Clone)]
+pub struct Compression {
+	codings: Vec<ContentCoding>,
+	min_size: usize,
+}
+
+impl Default for Compression {
+	fn default() -> Self {
+		Self::disabled()
+	}
+}
+
+impl Compression {
+	/// Responses are never compressed.
+	pub fn disabled() -> Self {
+		Compression {
+			codings: Vec::new(),
+			min_size: 1024,
+		}
+	}
+
+	/// Compresses responses with one of given codings accepted by the client.
+	/// Codings are listed in order of preference, used if client accepts few of them equally.
+	pub fn new(codings: Vec<ContentCoding>) -> Self {
+		Compression {
+			codings,
+			..Self::disabled()
+		}
+	}
+
+	/// Responses smaller than given number of bytes are sent uncompressed (1024 by default).
+	pub fn min_size(mut self, min_size: usize) -> Self {
+		self.min_size = min_size;
+		self
+	}
+
+	/// Returns `true` if any coding is enabled.
+	pub fn is_enabled(&self) -> bool {
+		!self.codings.is_empty()
+	}
+
+	/// Returns minimal size of compressed responses.
+	pub fn threshold(&self) -> usize {
+		self.min_size
+	}
+
+	/// Chooses the coding based on `Accept-Encoding` header.
+	pub fn negotiate(&self, accept: Option<&header::AcceptEncoding>) -> Option<ContentCoding> {
+		let accept = match accept {
+			Some(accept) => accept,
+			None => return None,
+		};
+		let quality = |coding: ContentCoding| {
+			let encoding = coding.encoding();
+			let wildcard = Encoding::EncodingExt("*".into());
+			accept.iter().find(|item| item.item == encoding)
+				.or_else(|| accept.iter().find(|item| item.item == wildcard))
+				.map(|item| item.quality)
+		};
+
+		let mut best: Option<(ContentCoding, header::Quality)> = None;
+		for coding in &self.codings {
+			match quality(*coding) {
+				Some(q) if q > header::q(0) && best.map(|(_, best)| q > best).unwrap_or(true) => {
+					best = Some((*coding, q));
+				},
+				_ => {},
+			}
+		}
+		best.map(|(coding, _)| coding)
+	}
+}
+
+/// Reads `Content-Encoding` of the request.
+/// Returns `Err` with the name of the coding if it's not supported.
+pub fn request_coding(content_encoding: Option<&header::ContentEncoding>) -> Result<Option<ContentCoding>, String> {
+	let encodings = match content_encoding {
+		Some(&header::ContentEncoding(ref encodings)) => encodings,
+		None => return Ok(None),
+	};
+
+	let mut codings = encodings.iter().filter(|encoding| **encoding != Encoding::Identity);
+	match (codings.next(), codings.next()) {
+		(None, _) => Ok(None),
+		(Some(encoding), None) => ContentCoding::from_encoding(encoding).map(Some).ok_or_else(|| encoding.to_string()),
+		// Multiple codings applied one after another are not supported.
+		(Some(_), Some(_)) => Err(encodings.iter().map(|encoding| encoding.to_string()).collect::<Vec<_>>().join(", ")),
+	}
+}
+
+/// Compresses given data.
+pub fn compress(coding: ContentCoding, data: &[u8]) -> io::Result<Vec<u8>> {
+	match coding {
+		ContentCoding::Gzip => {
+			let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+			encoder.write_all(data)?;
+			encoder.finish()
+		},
+		ContentCoding::Deflate => {
+			let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+			encoder.write_all(data)?;
+			encoder.finish()
+		},
+		ContentCoding::Brotli => {
+			let mut encoder = brotli::CompressorWriter::new(Vec::new(), 4096, 5, 22);
+			encoder.write_all(data)?;
+			Ok(encoder.into_inner())
+		},
+	}
+}
+
+/// Error of request body decompression.
+#[derive(Debug)]
+pub enum DecompressionError {
+	/// Decompressed body exceeds the limit.
+	TooLarge,
+	/// Body is not a valid compressed stream.
+	Invalid(io::Error),
+}
+
+/// Decompresses given data, fails if it's larger than `limit` bytes after decompression.
+pub fn decompress(coding: ContentCoding, data: &[u8], limit: usize) -> Result<Vec<u8>, DecompressionError> {
+	let reader: Box<Read> = match coding {
+		ContentCoding::Gzip => Box::new(flate2::read::GzDecoder::new(data)),
+		ContentCoding::Deflate => Box::new(flate2::read::ZlibDecoder::new(data)),
+		ContentCoding::Brotli => Box::new(brotli::Decompressor::new(data, 4096)),
+	};
+
+	// Read one byte more than allowed to detect oversized content without inflating all of it.
+	let mut decompressed = Vec::new();
+	reader.take(limit as u64 + 1).read_to_end(&mut decompressed).map_err(DecompressionError::Invalid)?;
+	if decompressed.len() > limit {
+		return Err(DecompressionError::TooLarge);
+	}
+	Ok(decompressed)
+}
@@ -2,27 +2,45 @@ use Rpc;
 
 use std::{fmt, mem, str};
 use std::sync::Arc;
+use std::time::Duration;
 
-use hyper::{self, mime, server, Method};
+use hyper::{self, mime, server, Method, StatusCode};
 use hyper::header::{self, Headers};
 use unicase::Ascii;
 
-use jsonrpc::{self as core, FutureResult, Metadata, Middleware, NoopMiddleware};
-use jsonrpc::futures::{Future, Poll, Async, Stream, future};
+use jsonrpc::{self as core, Encoding, FutureEncoded, FutureRpcResponse, Metadata, Middleware, NoopMiddleware};
+use jsonrpc::futures::{Future, Poll, Async, Sink, Stream, stream};
 use jsonrpc::serde_json;
+use compression::{self, Compression, ContentCoding, DecompressionError};
+use health::HealthApi;
 use response::Response;
-use server_utils::cors;
+use rest::{self, RestCall};
+use server_utils::cors::{self, CorsPolicy};
+use server_utils::tokio_core::reactor::Remote;
 
-use {utils, RequestMiddleware, RequestMiddlewareAction, CorsDomains, AllowedHosts, RestApi};
+use {utils, RequestMiddleware, RequestMiddlewareAction, CorsDomains, AllowedHosts, Encodings, EventStream, RestApi, RestRoutes};
 
 /// jsonrpc http request handler.
 pub struct ServerHandler<M: Metadata = (), S: Middleware<M> = NoopMiddleware> {
 	jsonrpc_handler: Rpc<M, S>,
 	allowed_hosts: AllowedHosts,
 	cors_domains: CorsDomains,
+	cors_policy: Arc<CorsPolicy>,
 	middleware: Arc<RequestMiddleware>,
 	rest_api: RestApi,
+	rest_routes: RestRoutes,
 	max_request_body_size: usize,
+	deprecation_warnings: bool,
+	encodings: Encodings,
+	compression: Arc<Compression>,
+	get_requests: bool,
+	get_cache_control: Option<Arc<String>>,
+	remote: Option<Remote>,
+	client_certificates: Arc<Vec<Vec<u8>>>,
+	path: Option<String>,
+	endpoints: Arc<Vec<ServerHandler<M, S>>>,
+	health_api: Arc<Vec<HealthApi>>,
+	event_stream: Option<EventStream>,
 }
 
 impl<M: Metadata, S: Middleware<M>> ServerHandler<M, S> {
@@ -39,11 +57,108 @@ impl<M: Metadata, S: Middleware<M>> ServerHandler<M, S> {
 			jsonrpc_handler,
 			allowed_hosts,
 			cors_domains,
+			cors_policy: Default::default(),
 			middleware,
 			rest_api,
+			rest_routes: Default::default(),
 			max_request_body_size,
+			deprecation_warnings: false,
+			encodings: Default::default(),
+			compression: Arc::new(Compression::disabled()),
+			get_requests: false,
+			get_cache_control: None,
+			remote: None,
+			client_certificates: Default::default(),
+			path: None,
+			endpoints: Default::default(),
+			health_api: Default::default(),
+			event_stream: None,
 		}
 	}
+
+	/// Sends CORS headers of given policy to allowed origins.
+	pub fn with_cors_policy(mut self, cors_policy: Arc<CorsPolicy>) -> Self {
+		self.cors_policy = cors_policy;
+		self
+	}
+
+	/// Maps requests matching any of the routes to calls of JSON-RPC methods.
+	pub fn with_rest_routes(mut self, rest_routes: RestRoutes) -> Self {
+		self.rest_routes = rest_routes;
+		self
+	}
+
+	/// Warns about calls to deprecated methods with `Warning` header of the response.
+	pub fn with_deprecation_warnings(mut self, enabled: bool) -> Self {
+		self.deprecation_warnings = enabled;
+		self
+	}
+
+	/// Accepts requests serialized with given encodings (matched by `Content-Type` header).
+	pub fn with_encodings(mut self, encodings: Encodings) -> Self {
+		self.encodings = encodings;
+		self
+	}
+
+	/// Compresses responses with a coding negotiated using `Accept-Encoding` header.
+	pub fn with_compression(mut self, compression: Arc<Compression>) -> Self {
+		self.compression = compression;
+		self
+	}
+
+	/// Accepts calls to read-only methods sent with GET.
+	pub fn with_get_requests(mut self, enabled: bool) -> Self {
+		self.get_requests = enabled;
+		self
+	}
+
+	/// Event loop sending streamed responses and the event stream.
+	/// Without it results of streaming methods are sent once the call is done
+	/// and the event stream is unavailable.
+	pub fn with_remote(mut self, remote: Remote) -> Self {
+		self.remote = Some(remote);
+		self
+	}
+
+	/// Handles only requests to given path prefix (normalized, e.g. `/admin`),
+	/// the prefix is stripped from the request.
+	pub fn with_path(mut self, path: String) -> Self {
+		self.path = Some(path);
+		self
+	}
+
+	/// Passes requests matching path prefix of any of given handlers to that handler.
+	/// Handlers are checked in order.
+	pub fn with_endpoints(mut self, endpoints: Arc<Vec<ServerHandler<M, S>>>) -> Self {
+		self.endpoints = endpoints;
+		self
+	}
+
+	/// Responds to health and readiness probes.
+	pub fn with_health_api(mut self, health_api: Arc<Vec<HealthApi>>) -> Self {
+		self.health_api = health_api;
+		self
+	}
+
+	/// Serves the stream of notifications and passes sessions referenced by calls to the metadata extractor.
+	#[cfg(feature = "sse")]
+	pub fn with_event_stream(mut self, event_stream: EventStream) -> Self {
+		self.event_stream = Some(event_stream);
+		self
+	}
+
+	/// Sends given `Cache-Control` header with successful responses to calls made with GET.
+	pub fn with_get_cache_control(mut self, cache_control: Option<Arc<String>>) -> Self {
+		self.get_cache_control = cache_control;
+		self
+	}
+
+	/// Passes certificates the client authenticated with (DER-encoded, end-entity first)
+	/// to `MetaExtractor::read_metadata_with_certificates`.
+	pub fn with_client_certificates(mut self, certificates: Vec<Vec<u8>>) -> Self {
+		self.client_certificates = Arc::new(certificates);
+		self
+	}
 }
 
 impl<M: Metadata, S: Middleware<M>> server::Service for ServerHandler<M, S> {
@@ -53,6 +168,49 @@ impl<M: Metadata, S: Middleware<M>> server::Service for ServerHandler<M, S> {
 	type Future = Handler<M, S>;
 
 	fn call(&self, request: Self::Request) -> Self::Future {
+		// Probes are answered before CORS and hosts validation.
+		if let Some(health_api) = self.health_api.iter().find(|health_api| health_api.matches(&request)) {
+			return Handler::Health(health_api.respond(&self.jsonrpc_handler, &request));
+		}
+
+		let endpoint = self.endpoints.iter()
+			.chain(Some(self))
+			.filter_map(|endpoint| endpoint.strip_path(&request).map(|uri| (endpoint, uri)))
+			.next();
+
+		match endpoint {
+			Some((endpoint, uri)) => endpoint.handle(request, uri, &self.client_certificates),
+			None => Handler::Error(Some(Response::not_found())),
+		}
+	}
+}
+
+impl<M: Metadata, S: Middleware<M>> ServerHandler<M, S> {
+	// Returns the URI of the request with path prefix of the endpoint stripped.
+	fn strip_path(&self, request: &server::Request) -> Option<Option<hyper::Uri>> {
+		let prefix = match self.path {
+			Some(ref prefix) => prefix,
+			None => return Some(None),
+		};
+
+		let path = utils::strip_path(prefix, request.uri().path())?;
+		let uri = match request.uri().query() {
+			Some(query) => format!("{}?{}", path, query),
+			None => path.to_owned(),
+		};
+		Some(uri.parse().ok())
+	}
+
+	fn handle(
+		&self,
+		mut request: server::Request,
+		uri: Option<hyper::Uri>,
+		client_certificates: &Arc<Vec<Vec<u8>>>,
+	) -> Handler<M, S> {
+		if let Some(uri) = uri {
+			request.set_uri(uri);
+		}
+
 		let is_host_allowed = utils::is_host_allowed(&request, &self.allowed_hosts);
 		let action = self.middleware.on_request(request);
 
@@ -83,8 +241,23 @@ impl<M: Metadata, S: Middleware<M>> server::Service for ServerHandler<M, S> {
 					},
 					is_options: false,
 					cors_header: cors::CorsHeader::NotRequired,
+					cors_policy: self.cors_policy.clone(),
+					cors_headers: Vec::new(),
 					rest_api: self.rest_api,
+					rest_routes: self.rest_routes.clone(),
+					response_format: ResponseFormat::JsonRpc,
 					max_request_body_size: self.max_request_body_size,
+					deprecation_warnings: self.deprecation_warnings,
+					warnings: Vec::new(),
+					encodings: self.encodings.clone(),
+					compression: self.compression.clone(),
+					response_coding: None,
+					get_requests: self.get_requests,
+					get_cache_control: self.get_cache_control.clone(),
+					cache_control: None,
+					remote: self.remote.clone(),
+					client_certificates: client_certificates.clone(),
+					event_stream: self.event_stream.clone(),
 				})
 			}
 		}
@@ -95,6 +268,7 @@ pub enum Handler<M: Metadata, S: Middleware<M>> {
 	Rpc(RpcHandler<M, S>),
 	Error(Option<Response>),
 	Middleware(Box<Future<Item=server::Response, Error=hyper::Error> + Send>),
+	Health(Box<Future<Item=server::Response, Error=hyper::Error> + Send>),
 }
 
 impl<M: Metadata, S: Middleware<M>> Future for Handler<M, S> {
@@ -105,6 +279,7 @@ impl<M: Metadata, S: Middleware<M>> Future for Handler<M, S> {
 		match *self {
 			Handler::Rpc(ref mut handler) => handler.poll(),
 			Handler::Middleware(ref mut middleware) => middleware.poll(),
+			Handler::Health(ref mut health) => health.poll(),
 			Handler::Error(ref mut response) => Ok(Async::Ready(
 				response.take().expect("Response always Some initialy. Returning `Ready` so will never be polled again; qed").into()
 			)),
@@ -144,13 +319,25 @@ enum RpcHandlerState<M, F> where
 		uri: Option<hyper::Uri>,
 		request: Vec<u8>,
 		metadata: M,
+		encoding: Option<Arc<Encoding>>,
+		coding: Option<ContentCoding>,
+		route: Option<RestCall>,
 	},
 	ProcessRest {
 		uri: hyper::Uri,
 		metadata: M,
 	},
+	ProcessGet {
+		uri: hyper::Uri,
+		metadata: M,
+	},
 	Writing(Response),
-	Waiting(FutureResult<F>),
+	WritingEncoded(Vec<u8>, header::ContentType),
+	WritingRaw(server::Response),
+	Waiting(FutureRpcResponse<F>),
+	WaitingCall(F),
+	WaitingEncoded(FutureEncoded, &'static str),
+	WaitingStream(stream::Collect<core::ResponseStream>),
 	Done,
 }
 
@@ -164,20 +351,53 @@ impl<M, F> fmt::Debug for RpcHandlerState<M, F> where
 			ReadingHeaders {..} => write!(fmt, "ReadingHeaders"),
 			ReadingBody {..} => write!(fmt, "ReadingBody"),
 			ProcessRest {..} => write!(fmt, "ProcessRest"),
+			ProcessGet {..} => write!(fmt, "ProcessGet"),
 			Writing(ref res) => write!(fmt, "Writing({:?})", res),
+			WritingEncoded(..) => write!(fmt, "WritingEncoded"),
+			WritingRaw(ref res) => write!(fmt, "WritingRaw({:?})", res),
 			Waiting(_) => write!(fmt, "Waiting"),
+			WaitingCall(_) => write!(fmt, "WaitingCall"),
+			WaitingEncoded(..) => write!(fmt, "WaitingEncoded"),
+			WaitingStream(_) => write!(fmt, "WaitingStream"),
 			Done => write!(fmt, "Done"),
 		}
 	}
 }
 
+// How the response of a call is presented.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ResponseFormat {
+	// JSON-RPC response, always with 200 status code.
+	JsonRpc,
+	// JSON-RPC response with status code matching the error (REST API).
+	JsonRpcWithStatus,
+	// Only the result or the error, with matching status code (REST routes).
+	Rest,
+}
+
 pub struct RpcHandler<M: Metadata, S: Middleware<M>> {
 	jsonrpc_handler: Rpc<M, S>,
 	state: RpcHandlerState<M, S::Future>,
 	is_options: bool,
 	cors_header: cors::CorsHeader<header::AccessControlAllowOrigin>,
+	cors_policy: Arc<CorsPolicy>,
+	cors_headers: Vec<(&'static str, String)>,
 	rest_api: RestApi,
+	rest_routes: RestRoutes,
+	response_format: ResponseFormat,
 	max_request_body_size: usize,
+	deprecation_warnings: bool,
+	warnings: Vec<String>,
+	encodings: Encodings,
+	compression: Arc<Compression>,
+	response_coding: Option<ContentCoding>,
+	get_requests: bool,
+	get_cache_control: Option<Arc<String>>,
+	// Set for calls made with GET, sent only if the call succeeds.
+	cache_control: Option<Arc<String>>,
+	remote: Option<Remote>,
+	client_certificates: Arc<Vec<Vec<u8>>>,
+	event_stream: Option<EventStream>,
 }
 
 impl<M: Metadata, S: Middleware<M>> Future for RpcHandler<M, S> {
@@ -188,13 +408,18 @@ impl<M: Metadata, S: Middleware<M>> Future for RpcHandler<M, S> {
 		let new_state = match mem::replace(&mut self.state, RpcHandlerState::Done) {
 			RpcHandlerState::ReadingHeaders { request, cors_domains, continue_on_invalid_cors, } => {
 				// Read cors header
-				self.cors_header = utils::cors_header(&request, &cors_domains);
+				self.cors_header = utils::cors_header_with_policy(&request, &cors_domains, &self.cors_policy);
 				self.is_options = *request.method() == Method::Options;
+				self.cors_headers = self.cors_policy.headers(
+					self.is_options,
+					utils::read_header(&request, "access-control-request-headers"),
+				);
+				self.response_coding = self.compression.negotiate(request.headers().get::<header::AcceptEncoding>());
 				// Read other headers
 				RpcPollState::Ready(self.read_headers(request, continue_on_invalid_cors))
 			},
-			RpcHandlerState::ReadingBody { body, request, metadata, uri, } => {
-				match self.process_body(body, request, uri, metadata) {
+			RpcHandlerState::ReadingBody { body, request, metadata, uri, encoding, coding, route, } => {
+				match self.process_body(body, request, uri, metadata, encoding, coding, route) {
 					Err(BodyError::Utf8(ref e)) => {
 						let mesg = format!("utf-8 encoding error at byte {} in request body", e.valid_up_to());
 						let resp = Response::bad_request(mesg);
@@ -204,6 +429,10 @@ impl<M: Metadata, S: Middleware<M>> Future for RpcHandler<M, S> {
 						let resp = Response::too_large("request body size exceeds allowed maximum");
 						RpcPollState::Ready(RpcHandlerState::Writing(resp))
 					}
+					Err(BodyError::Compression(ref e)) => {
+						let resp = Response::bad_request(format!("invalid compressed request body: {}\n", e));
+						RpcPollState::Ready(RpcHandlerState::Writing(resp))
+					}
 					Err(BodyError::Hyper(e)) => return Err(e),
 					Ok(state) => state,
 				}
@@ -211,32 +440,108 @@ impl<M: Metadata, S: Middleware<M>> Future for RpcHandler<M, S> {
 			RpcHandlerState::ProcessRest { uri, metadata } => {
 				self.process_rest(uri, metadata)?
 			},
+			RpcHandlerState::ProcessGet { uri, metadata } => {
+				RpcPollState::Ready(self.process_get(uri, metadata))
+			},
 			RpcHandlerState::Waiting(mut waiting) => {
 				match waiting.poll() {
 					Ok(Async::Ready(response)) => {
-						RpcPollState::Ready(RpcHandlerState::Writing(match response {
+						RpcPollState::Ready(match response {
 							// Notification, just return empty response.
-							None => Response::ok(String::new()),
-							// Add new line to have nice output when using CLI clients (curl)
-							Some(result) => Response::ok(format!("{}\n", result)),
-						}.into()))
+							None => RpcHandlerState::Writing(Response::ok(String::new())),
+							Some(response) => {
+								let delay = match response {
+									core::Response::Single(ref output) => retry_after(::std::slice::from_ref(output)),
+									core::Response::Batch(ref outputs) => retry_after(outputs),
+								};
+								let result = serde_json::to_string(&response)
+									.expect("Serialization of response is infallible;qed");
+								match delay {
+									Some(delay) => Self::service_unavailable(result, delay),
+									// Add new line to have nice output when using CLI clients (curl)
+									None => RpcHandlerState::Writing(Response::ok(format!("{}\n", result))),
+								}
+							},
+						})
 					},
 					Ok(Async::NotReady) => RpcPollState::NotReady(RpcHandlerState::Waiting(waiting)),
 					Err(_) => RpcPollState::Ready(RpcHandlerState::Writing(Response::internal_error())),
 				}
 			},
+			RpcHandlerState::WaitingCall(mut waiting) => {
+				match waiting.poll() {
+					Ok(Async::Ready(response)) => {
+						RpcPollState::Ready(match response {
+							// Notification, just return empty response.
+							None => RpcHandlerState::Writing(Response::ok(String::new())),
+							Some(core::Response::Single(output)) => self.call_response(output),
+							Some(response) => {
+								self.cache_control = None;
+								let response = serde_json::to_string(&response)
+									.expect("Serialization of response is infallible;qed");
+								RpcHandlerState::Writing(Response::ok(format!("{}\n", response)))
+							},
+						})
+					},
+					Ok(Async::NotReady) => RpcPollState::NotReady(RpcHandlerState::WaitingCall(waiting)),
+					Err(_) => RpcPollState::Ready(RpcHandlerState::Writing(Response::internal_error())),
+				}
+			},
+			RpcHandlerState::WaitingEncoded(mut waiting, content_type) => {
+				match waiting.poll() {
+					Ok(Async::Ready(None)) => RpcPollState::Ready(RpcHandlerState::Writing(Response::empty())),
+					Ok(Async::Ready(Some(result))) => {
+						let content_type = content_type.parse().unwrap_or(mime::APPLICATION_OCTET_STREAM);
+						RpcPollState::Ready(RpcHandlerState::WritingEncoded(result, header::ContentType(content_type)))
+					},
+					Ok(Async::NotReady) => RpcPollState::NotReady(RpcHandlerState::WaitingEncoded(waiting, content_type)),
+					Err(_) => RpcPollState::Ready(RpcHandlerState::Writing(Response::internal_error())),
+				}
+			},
+			RpcHandlerState::WaitingStream(mut waiting) => {
+				match waiting.poll() {
+					Ok(Async::Ready(messages)) => {
+						let response = Response::ok(format!("[{}]\n", messages.join(",")));
+						RpcPollState::Ready(RpcHandlerState::Writing(response))
+					},
+					Ok(Async::NotReady) => RpcPollState::NotReady(RpcHandlerState::WaitingStream(waiting)),
+					Err(_) => RpcPollState::Ready(RpcHandlerState::Writing(Response::internal_error())),
+				}
+			},
 			state => RpcPollState::NotReady(state),
 		};
 
 		let (new_state, is_ready) = new_state.decompose();
-		match new_state {
-			RpcHandlerState::Writing(res) => {
-				let mut response: server::Response = res.into();
+		let response = match new_state {
+			RpcHandlerState::Writing(res) => Ok(self.compress(res.code, res.content_type, res.content.into_bytes())),
+			RpcHandlerState::WritingEncoded(content, content_type) => Ok(self.compress(StatusCode::Ok, content_type, content)),
+			RpcHandlerState::WritingRaw(res) => Ok(res),
+			state => Err(state),
+		};
+		match response {
+			Ok(mut response) => {
 				let cors_header = mem::replace(&mut self.cors_header, cors::CorsHeader::Invalid);
-				Self::set_response_headers(response.headers_mut(), self.is_options, cors_header.into());
+				Self::set_response_headers(
+					response.headers_mut(),
+					self.is_options,
+					cors_header.into(),
+					&self.encodings,
+					self.compression.is_enabled(),
+					self.allowed_methods(),
+					mem::replace(&mut self.cors_headers, Vec::new()),
+					self.cors_policy.varies_with_request_headers(),
+				);
+				for warning in self.warnings.drain(..) {
+					response.headers_mut().append_raw("Warning", warning);
+				}
+				if let Some(cache_control) = self.cache_control.take() {
+					if response.status() == StatusCode::Ok {
+						response.headers_mut().set_raw("Cache-Control", cache_control.as_str().to_owned());
+					}
+				}
 				Ok(Async::Ready(response))
 			},
-			state => {
+			Err(state) => {
 				self.state = state;
 				if is_ready {
 					self.poll()
@@ -248,11 +553,64 @@ impl<M: Metadata, S: Middleware<M>> Future for RpcHandler<M, S> {
 	}
 }
 
+// Warns about a call to deprecated method, see RFC 7234, section 5.5 (299 - Miscellaneous Persistent Warning).
+fn add_warning(warnings: &mut Vec<String>, method: &str, deprecation: &core::Deprecation) {
+	let warning = format!("299 - \"{}\"", deprecation.warning(method).replace('"', "\\\""));
+	if !warnings.contains(&warning) {
+		warnings.push(warning);
+	}
+}
+
+// Returns the delay to advise if all given calls were rejected by overloaded server.
+fn retry_after(outputs: &[core::Output]) -> Option<u64> {
+	if outputs.is_empty() {
+		return None;
+	}
+
+	outputs.iter().fold(Some(0), |delay, output| match *output {
+		core::Output::Failure(ref failure) if failure.error.is_server_busy() => {
+			let retry_after = failure.error.data.as_ref()
+				.and_then(|data| data.get("retryAfter"))
+				.and_then(|delay| delay.as_u64())
+				.unwrap_or(1);
+			delay.map(|delay| ::std::cmp::max(delay, retry_after))
+		},
+		_ => None,
+	})
+}
+
+// Reads a method call from the query string of GET request.
+fn parse_query(query: &str) -> Result<core::MethodCall, String> {
+	use self::core::types::{Id, Params, Version};
+
+	let mut method = None;
+	let mut params = None;
+	let mut id = Id::Num(1);
+	for (key, value) in ::url::form_urlencoded::parse(query.as_bytes()) {
+		match &*key {
+			"method" => method = Some(value.into_owned()),
+			"params" => params = Some(serde_json::from_str::<Params>(&value)
+				.map_err(|_| "Invalid params: URL-encoded JSON array or object is required\n".to_owned())?),
+			// Unquoted strings are accepted as well.
+			"id" => id = serde_json::from_str(&value).unwrap_or_else(|_| Id::Str(value.into_owned())),
+			_ => {},
+		}
+	}
+
+	Ok(core::MethodCall {
+		jsonrpc: Some(Version::V2),
+		method: method.ok_or_else(|| "Missing method: /?method=<name>&params=<JSON>&id=<JSON> is required\n".to_owned())?,
+		params,
+		id,
+	})
+}
+
 // Intermediate and internal error type to better distinguish
 // error cases occuring during request body processing.
 enum BodyError {
 	Hyper(hyper::Error),
 	Utf8(str::Utf8Error),
+	Compression(::std::io::Error),
 	TooLarge,
 }
 
@@ -264,7 +622,7 @@ impl From<hyper::Error> for BodyError {
 
 impl<M: Metadata, S: Middleware<M>> RpcHandler<M, S> {
 	fn read_headers(
-		&self,
+		&mut self,
 		request: server::Request,
 		continue_on_invalid_cors: bool,
 	) -> RpcHandlerState<M, S::Future> {
@@ -272,7 +630,41 @@ impl<M: Metadata, S: Middleware<M>> RpcHandler<M, S> {
 			return RpcHandlerState::Writing(Response::invalid_cors());
 		}
 		// Read metadata
-		let metadata = self.jsonrpc_handler.extractor.read_metadata(&request);
+		let metadata = match self.read_metadata(&request) {
+			Ok(metadata) => metadata,
+			Err(state) => return state,
+		};
+		let encoding = self.find_encoding(request.headers().get::<header::ContentType>());
+		let coding = match compression::request_coding(request.headers().get::<header::ContentEncoding>()) {
+			Ok(coding) => coding,
+			Err(ref coding) if *request.method() == Method::Post => {
+				return RpcHandlerState::Writing(Response::unsupported_content_encoding(coding));
+			},
+			Err(_) => None,
+		};
+
+		// Configured routes take precedence.
+		if let Some(route) = rest::resolve(&self.rest_routes, request.method(), request.uri()) {
+			if *request.method() == Method::Get {
+				// Same as calls made with GET, routes for GET can't have side effects.
+				if !self.jsonrpc_handler.handler.is_read_only(route.method()) {
+					return RpcHandlerState::Writing(Response::not_read_only(route.method()));
+				}
+				self.cache_control = self.get_cache_control.clone();
+			} else if !Self::is_json(request.headers().get::<header::ContentType>()) {
+				return RpcHandlerState::Writing(Response::unsupported_content_type());
+			}
+			self.response_format = ResponseFormat::Rest;
+			return RpcHandlerState::ReadingBody {
+				metadata,
+				request: Default::default(),
+				uri: None,
+				body: request.body(),
+				encoding: None,
+				coding,
+				route: Some(route),
+			};
+		}
 
 		// Proceed
 		match *request.method() {
@@ -285,6 +677,20 @@ impl<M: Metadata, S: Middleware<M>> RpcHandler<M, S> {
 					request: Default::default(),
 					uri,
 					body: request.body(),
+					encoding: None,
+					coding,
+					route: None,
+				}
+			},
+			Method::Post if encoding.is_some() => {
+				RpcHandlerState::ReadingBody {
+					metadata,
+					request: Default::default(),
+					uri: None,
+					body: request.body(),
+					encoding,
+					coding,
+					route: None,
 				}
 			},
 			Method::Post if self.rest_api == RestApi::Unsecure && request.uri().path().split('/').count() > 2 => {
@@ -297,6 +703,12 @@ impl<M: Metadata, S: Middleware<M>> RpcHandler<M, S> {
 			Method::Post => {
 				RpcHandlerState::Writing(Response::unsupported_content_type())
 			},
+			Method::Get if self.get_requests => {
+				RpcHandlerState::ProcessGet {
+					metadata,
+					uri: request.uri().clone(),
+				}
+			},
 			// Don't validate content type on options
 			Method::Options => {
 				RpcHandlerState::Writing(Response::empty())
@@ -308,12 +720,54 @@ impl<M: Metadata, S: Middleware<M>> RpcHandler<M, S> {
 		}
 	}
 
+	#[cfg(feature = "sse")]
+	fn read_metadata(&self, request: &server::Request) -> Result<M, RpcHandlerState<M, S::Future>> {
+		let session = match self.event_stream {
+			Some(ref event_stream) if event_stream.matches(request) => {
+				return Err(match self.remote {
+					Some(ref remote) => RpcHandlerState::WritingRaw(event_stream.open(remote)),
+					None => {
+						warn!("Event stream requires an event loop (see `ServerHandler::with_remote`).");
+						RpcHandlerState::Writing(Response::internal_error())
+					},
+				});
+			},
+			Some(ref event_stream) => match event_stream.session(request.headers()) {
+				Ok(session) => session,
+				Err(message) => return Err(RpcHandlerState::Writing(Response::bad_request(message))),
+			},
+			None => None,
+		};
+
+		Ok(match session {
+			Some(session) => self.jsonrpc_handler.extractor.read_metadata_with_session(
+				request,
+				&self.client_certificates,
+				session,
+			),
+			None => self.read_client_metadata(request),
+		})
+	}
+
+	#[cfg(not(feature = "sse"))]
+	fn read_metadata(&self, request: &server::Request) -> Result<M, RpcHandlerState<M, S::Future>> {
+		Ok(self.read_client_metadata(request))
+	}
+
+	fn read_client_metadata(&self, request: &server::Request) -> M {
+		if self.client_certificates.is_empty() {
+			self.jsonrpc_handler.extractor.read_metadata(request)
+		} else {
+			self.jsonrpc_handler.extractor.read_metadata_with_certificates(request, &self.client_certificates)
+		}
+	}
+
 	fn process_rest(
-		&self,
+		&mut self,
 		uri: hyper::Uri,
 		metadata: M,
 	) -> Result<RpcPollState<M, S::Future>, hyper::Error> {
-		use self::core::types::{Call, MethodCall, Version, Params, Request, Id, Value};
+		use self::core::types::{MethodCall, Version, Params, Id, Value};
 
 		// skip the initial /
 		let mut it = uri.path().split('/').skip(1);
@@ -329,27 +783,81 @@ impl<M: Metadata, S: Middleware<M>> RpcHandler<M, S> {
 		}
 
 		// Parse request
-		let call = Request::Single(Call::MethodCall(MethodCall {
+		let call = MethodCall {
 			jsonrpc: Some(Version::V2),
 			method: method.into(),
 			params: Some(Params::Array(params)),
 			id: Id::Num(1),
-		}));
+		};
+
+		self.response_format = ResponseFormat::JsonRpcWithStatus;
+		Ok(RpcPollState::Ready(self.call_method(call, metadata)))
+	}
+
+	fn process_get(
+		&mut self,
+		uri: hyper::Uri,
+		metadata: M,
+	) -> RpcHandlerState<M, S::Future> {
+		let call = match parse_query(uri.query().unwrap_or("")) {
+			Ok(call) => call,
+			Err(message) => return RpcHandlerState::Writing(Response::bad_request(message)),
+		};
+		// Caches and crawlers may repeat GET requests, so side effects are not allowed.
+		if !self.jsonrpc_handler.handler.is_read_only(&call.method) {
+			return RpcHandlerState::Writing(Response::not_read_only(&call.method));
+		}
+
+		self.cache_control = self.get_cache_control.clone();
+		self.call_method(call, metadata)
+	}
+
+	fn call_method(&mut self, call: core::MethodCall, metadata: M) -> RpcHandlerState<M, S::Future> {
+		use self::core::types::{Call, Request};
+
+		self.collect_warnings(&call.method);
+
+		RpcHandlerState::WaitingCall(
+			self.jsonrpc_handler.handler.handle_rpc_request(Request::Single(Call::MethodCall(call)), metadata)
+		)
+	}
+
+	// Writes the output of a single call in the expected format.
+	fn call_response(&mut self, output: core::Output) -> RpcHandlerState<M, S::Future> {
+		// Failed calls are not cached.
+		if let core::Output::Failure(_) = output {
+			self.cache_control = None;
+		}
+
+		if let Some(delay) = retry_after(::std::slice::from_ref(&output)) {
+			let response = serde_json::to_string(&output).expect("Serialization of response is infallible;qed");
+			return Self::service_unavailable(response, delay);
+		}
+
+		RpcHandlerState::Writing(match self.response_format {
+			ResponseFormat::JsonRpc => Response::ok(format!("{}\n",
+				serde_json::to_string(&output).expect("Serialization of response is infallible;qed")
+			)),
+			ResponseFormat::JsonRpcWithStatus => rest::response(output, false),
+			ResponseFormat::Rest => rest::response(output, true),
+		})
+	}
 
-		return Ok(RpcPollState::Ready(RpcHandlerState::Waiting(
-			future::Either::B(self.jsonrpc_handler.handler.handle_rpc_request(call, metadata))
-				.map(|res| res.map(|x| serde_json::to_string(&x)
-					.expect("Serialization of response is infallible;qed")
-				))
-		)));
+	fn service_unavailable(response: String, delay: u64) -> RpcHandlerState<M, S::Future> {
+		let mut response: server::Response = Response::service_unavailable(format!("{}\n", response)).into();
+		response.headers_mut().set(header::RetryAfter::Delay(Duration::from_secs(delay)));
+		RpcHandlerState::WritingRaw(response)
 	}
 
 	fn process_body(
-		&self,
+		&mut self,
 		mut body: hyper::Body,
 		mut request: Vec<u8>,
 		uri: Option<hyper::Uri>,
 		metadata: M,
+		encoding: Option<Arc<Encoding>>,
+		coding: Option<ContentCoding>,
+		route: Option<RestCall>,
 	) -> Result<RpcPollState<M, S::Future>, BodyError> {
 		loop {
 			match body.poll()? {
@@ -360,6 +868,21 @@ impl<M: Metadata, S: Middleware<M>> RpcHandler<M, S> {
 					request.extend_from_slice(&*chunk)
 				},
 				Async::Ready(None) => {
+					if let Some(coding) = coding {
+						request = match compression::decompress(coding, &request, self.max_request_body_size) {
+							Ok(request) => request,
+							Err(DecompressionError::TooLarge) => return Err(BodyError::TooLarge),
+							Err(DecompressionError::Invalid(err)) => return Err(BodyError::Compression(err)),
+						};
+					}
+
+					if let Some(route) = route {
+						return Ok(RpcPollState::Ready(match route.into_method_call(&request) {
+							Ok(call) => self.call_method(call, metadata),
+							Err(message) => RpcHandlerState::Writing(Response::bad_request(message)),
+						}));
+					}
+
 					if let (Some(uri), true) = (uri, request.is_empty()) {
 						return Ok(RpcPollState::Ready(RpcHandlerState::ProcessRest {
 							uri,
@@ -367,6 +890,14 @@ impl<M: Metadata, S: Middleware<M>> RpcHandler<M, S> {
 						}));
 					}
 
+					if let Some(encoding) = encoding {
+						let content_type = encoding.content_type();
+						return Ok(RpcPollState::Ready(RpcHandlerState::WaitingEncoded(
+							self.jsonrpc_handler.handler.handle_encoded_request(&request, encoding, metadata),
+							content_type,
+						)));
+					}
+
 					let content = match str::from_utf8(&request) {
 						Ok(content) => content,
 						Err(err) => {
@@ -376,9 +907,19 @@ impl<M: Metadata, S: Middleware<M>> RpcHandler<M, S> {
 					};
 
 					// Content is ready
-					return Ok(RpcPollState::Ready(RpcHandlerState::Waiting(
-						self.jsonrpc_handler.handler.handle_request(content, metadata)
-					)));
+					let response = {
+						let warnings = &mut self.warnings;
+						let deprecation_warnings = self.deprecation_warnings;
+						self.jsonrpc_handler.handler.handle_streaming_request_with(content, metadata, |method, deprecation| {
+							if deprecation_warnings {
+								add_warning(warnings, method, deprecation);
+							}
+						})
+					};
+					return Ok(RpcPollState::Ready(match response {
+						core::StreamingResponse::Stream(messages) => self.streamed_response(messages),
+						core::StreamingResponse::Response(response) => RpcHandlerState::Waiting(response),
+					}));
 				},
 				Async::NotReady => {
 					return Ok(RpcPollState::NotReady(RpcHandlerState::ReadingBody {
@@ -386,38 +927,139 @@ impl<M: Metadata, S: Middleware<M>> RpcHandler<M, S> {
 						request,
 						metadata,
 						uri,
+						encoding,
+						coding,
+						route,
 					}));
 				},
 			}
 		}
 	}
 
-	fn set_response_headers(headers: &mut Headers, is_options: bool, cors_header: Option<header::AccessControlAllowOrigin>) {
+	// Messages produced by a streaming method are sent as chunks of a JSON array.
+	// Without an event loop to send them the array is sent when the call is done.
+	fn streamed_response(&self, messages: core::ResponseStream) -> RpcHandlerState<M, S::Future> {
+		let remote = match self.remote {
+			Some(ref remote) => remote,
+			None => return RpcHandlerState::WaitingStream(messages.collect()),
+		};
+
+		let mut separator = "";
+		let chunks = stream::once(Ok("[".to_owned()))
+			.chain(messages.map(move |message| {
+				let chunk = format!("{}{}", separator, message);
+				separator = ",";
+				chunk
+			}))
+			.chain(stream::once(Ok("]\n".to_owned())))
+			.map(|chunk| Ok(hyper::Chunk::from(chunk)));
+
+		let (sender, body) = hyper::Body::pair();
+		remote.spawn(move |_| chunks.forward(sender.sink_map_err(|e| {
+			debug!("Streamed response interrupted: {:?}", e);
+		})).map(|_| ()));
+
+		RpcHandlerState::WritingRaw(server::Response::new()
+			.with_header(header::ContentType::json())
+			.with_body(body))
+	}
+
+	fn collect_warnings(&mut self, method: &str) {
+		if !self.deprecation_warnings {
+			return;
+		}
+
+		let warnings = &mut self.warnings;
+		self.jsonrpc_handler.handler.deprecations(method, |method, deprecation| add_warning(warnings, method, deprecation));
+	}
+
+	fn set_response_headers(
+		headers: &mut Headers,
+		is_options: bool,
+		cors_header: Option<header::AccessControlAllowOrigin>,
+		encodings: &[Arc<Encoding>],
+		compression: bool,
+		methods: Vec<Method>,
+		cors_headers: Vec<(&'static str, String)>,
+		vary_request_headers: bool,
+	) {
 		if is_options {
-			headers.set(header::Allow(vec![
-				Method::Options,
-				Method::Post,
-			]));
-			headers.set(header::Accept(vec![
-				header::qitem(mime::APPLICATION_JSON)
-			]));
+			headers.set(header::Allow(methods.clone()));
+			let encodings = encodings.iter().filter_map(|encoding| encoding.content_type().parse().ok());
+			headers.set(header::Accept(
+				Some(mime::APPLICATION_JSON).into_iter().chain(encodings).map(header::qitem).collect()
+			));
 		}
 
+		let cors_header_set = cors_header.is_some();
 		if let Some(cors_domain) = cors_header {
-			headers.set(header::AccessControlAllowMethods(vec![
-				Method::Options,
-				Method::Post
-			]));
-			headers.set(header::AccessControlAllowHeaders(vec![
-				Ascii::new("origin".to_owned()),
-				Ascii::new("content-type".to_owned()),
-				Ascii::new("accept".to_owned()),
-			]));
+			headers.set(header::AccessControlAllowMethods(methods));
+			for (name, value) in cors_headers {
+				headers.set_raw(name, value);
+			}
 			headers.set(cors_domain);
-			headers.set(header::Vary::Items(vec![
-				Ascii::new("origin".to_owned())
-			]));
 		}
+
+		let mut vary = Vec::new();
+		if compression {
+			vary.push(Ascii::new("accept-encoding".to_owned()));
+		}
+		if cors_header_set {
+			vary.push(Ascii::new("origin".to_owned()));
+		}
+		if cors_header_set && vary_request_headers {
+			vary.push(Ascii::new("access-control-request-headers".to_owned()));
+		}
+		if !vary.is_empty() {
+			headers.set(header::Vary::Items(vary));
+		}
+	}
+
+	// Compresses the response if client accepts any of enabled codings.
+	fn compress(&self, code: StatusCode, content_type: header::ContentType, content: Vec<u8>) -> server::Response {
+		let response = server::Response::new()
+			.with_status(code)
+			.with_header(content_type);
+		let coding = match self.response_coding {
+			Some(coding) if content.len() >= self.compression.threshold() => coding,
+			_ => return response.with_body(content),
+		};
+
+		match compression::compress(coding, &content) {
+			Ok(compressed) => response
+				.with_header(header::ContentEncoding(vec![coding.encoding()]))
+				.with_header(header::ContentLength(compressed.len() as u64))
+				.with_body(compressed),
+			Err(err) => {
+				warn!("Error compressing response: {:?}", err);
+				response.with_body(content)
+			},
+		}
+	}
+
+	fn allowed_methods(&self) -> Vec<Method> {
+		let mut methods = vec![Method::Options, Method::Post];
+		if self.get_requests || self.event_stream.is_some() {
+			methods.push(Method::Get);
+		}
+		for route in self.rest_routes.iter() {
+			if !methods.contains(route.method()) {
+				methods.push(route.method().clone());
+			}
+		}
+		methods
+	}
+
+	fn find_encoding(&self, content_type: Option<&header::ContentType>) -> Option<Arc<Encoding>> {
+		let mime = match content_type {
+			Some(&header::ContentType(ref mime)) => mime,
+			None => return None,
+		};
+
+		self.encodings.iter().find(|encoding| {
+			let mut parts = encoding.content_type().splitn(2, '/');
+			parts.next() == Some(mime.type_().as_str()) && parts.next() == Some(mime.subtype().as_str())
+		}).cloned()
 	}
 
 	fn is_json(content_type: Option<&header::ContentType>) -> bool {
@@ -0,0 +1,86 @@
+//! Health and readiness probes.
+
+use hyper::{self, server, StatusCode};
+
+use jsonrpc::{Metadata, Middleware, Output};
+use jsonrpc::futures::{future, Future};
+use jsonrpc::serde_json;
+use jsonrpc::types::{Call, Id, MethodCall, Params, Value, Version};
+use response::Response;
+use Rpc;
+
+/// `GET` endpoint for health or readiness probes (see `ServerBuilder::health_api`).
+///
+/// Probes bypass CORS, hosts validation and `RequestMiddleware`.
+#[derive(Debug, Clone)]
+pub struct HealthApi {
+	path: String,
+	method: Option<String>,
+}
+
+impl HealthApi {
+	/// Creates a probe responding with 200 on given path (e.g. `/health`).
+	pub fn new(path: &str) -> Self {
+		HealthApi {
+			path: path.into(),
+			method: None,
+		}
+	}
+
+	/// Calls given method (without params) to check the status.
+	/// The probe responds with 200 and the result if the call succeeds and the result is not `false`,
+	/// otherwise with 503 and the result or the error.
+	///
+	/// Consider marking the method with `Priority::Critical`, so the probe works when the server is busy.
+	pub fn method(mut self, method: &str) -> Self {
+		self.method = Some(method.into());
+		self
+	}
+
+	/// Returns `true` if the request should be handled by the probe.
+	pub fn matches(&self, request: &server::Request) -> bool {
+		*request.method() == hyper::Method::Get && request.uri().path() == self.path
+	}
+
+	/// Responds to the probe.
+	pub fn respond<M: Metadata, S: Middleware<M>>(
+		&self,
+		rpc: &Rpc<M, S>,
+		request: &server::Request,
+	) -> Box<Future<Item = server::Response, Error = hyper::Error> + Send> {
+		let method = match self.method {
+			Some(ref method) => method.clone(),
+			None => return Box::new(future::ok(Response::ok("true\n").into())),
+		};
+
+		let call = Call::MethodCall(MethodCall {
+			jsonrpc: Some(Version::V2),
+			method,
+			params: Some(Params::None),
+			id: Id::Num(1),
+		});
+		let metadata = rpc.extractor.read_metadata(request);
+		Box::new(rpc.handler.handle_call(call, metadata).then(|output| {
+			let (healthy, body) = match output {
+				Ok(Some(Output::Success(success))) => {
+					let body = match success.raw_result {
+						Some(ref raw) => raw.get().to_owned(),
+						None => success.result.to_string(),
+					};
+					(success.into_result().ok() != Some(Value::Bool(false)), body)
+				},
+				Ok(Some(Output::Failure(failure))) => (
+					false,
+					serde_json::to_string(&failure.error).expect("Error serialization is infallible; qed"),
+				),
+				Ok(None) | Err(_) => (false, "null".into()),
+			};
+
+			let mut response = Response::ok(format!("{}\n", body));
+			if !healthy {
+				response.code = StatusCode::ServiceUnavailable;
+			}
+			Ok(response.into())
+		}))
+	}
+}
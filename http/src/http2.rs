@@ -0,0 +1,249 @@
+//! HTTP/2 support: h2c with prior knowledge and h2 negotiated over TLS with ALPN.
+//!
+//! Every HTTP/2 stream is converted to a `hyper` request and handled by `ServerHandler`,
+//! so CORS, hosts validation and `RequestMiddleware` behave the same as for HTTP/1.1.
+
+use std::io::{self, Read, Write};
+use std::net::SocketAddr;
+use std::rc::Rc;
+
+use bytes::Bytes;
+use h2;
+use h2::server::SendResponse;
+use http;
+use hyper::{self, server, StatusCode};
+use hyper::server::Service;
+
+use jsonrpc::{Metadata, Middleware};
+use jsonrpc::futures::{Async, Future, Poll, Sink, Stream};
+use server_utils::tokio_core::net::TcpStream;
+use server_utils::tokio_core::reactor::Handle;
+use server_utils::tokio_io::{AsyncRead, AsyncWrite};
+use handler::ServerHandler;
+
+const PREFACE: &[u8] = b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n";
+
+/// Reads the beginning of the connection to detect HTTP/2 connection preface.
+struct Sniff<T> {
+	io: Option<T>,
+	read: Vec<u8>,
+}
+
+impl<T: Read> Future for Sniff<T> {
+	type Item = (Rewind<T>, bool);
+	type Error = io::Error;
+
+	fn poll(&mut self) -> Poll<Self::Item, io::Error> {
+		loop {
+			if self.read.len() == PREFACE.len() || !PREFACE.starts_with(&self.read) {
+				break;
+			}
+
+			let mut buf = [0u8; 24];
+			let missing = PREFACE.len() - self.read.len();
+			match self.io.as_mut().expect("Sniff is not polled after completion; qed").read(&mut buf[..missing]) {
+				Ok(0) => break,
+				Ok(read) => self.read.extend_from_slice(&buf[..read]),
+				Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => return Ok(Async::NotReady),
+				Err(err) => return Err(err),
+			}
+		}
+
+		let is_http2 = self.read == PREFACE;
+		Ok(Async::Ready((Rewind {
+			io: self.io.take().expect("Sniff is not polled after completion; qed"),
+			prefix: ::std::mem::replace(&mut self.read, Vec::new()),
+			position: 0,
+		}, is_http2)))
+	}
+}
+
+/// Connection returning the sniffed bytes before reading from the socket again.
+pub struct Rewind<T> {
+	io: T,
+	prefix: Vec<u8>,
+	position: usize,
+}
+
+impl<T: Read> Read for Rewind<T> {
+	fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+		if self.position < self.prefix.len() {
+			let read = (&self.prefix[self.position..]).read(buf)?;
+			self.position += read;
+			return Ok(read);
+		}
+		self.io.read(buf)
+	}
+}
+
+impl<T: Write> Write for Rewind<T> {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		self.io.write(buf)
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		self.io.flush()
+	}
+}
+
+impl<T: AsyncRead> AsyncRead for Rewind<T> {}
+
+impl<T: AsyncWrite> AsyncWrite for Rewind<T> {
+	fn shutdown(&mut self) -> Poll<(), io::Error> {
+		self.io.shutdown()
+	}
+}
+
+/// Serves HTTP/2 if the client starts the connection with the preface and HTTP/1.1 otherwise.
+pub fn bind_connection<M: Metadata, S: Middleware<M>>(
+	http: &Rc<server::Http>,
+	handle: &Handle,
+	socket: TcpStream,
+	addr: SocketAddr,
+	service: ServerHandler<M, S>,
+) {
+	let http = http.clone();
+	let handle2 = handle.clone();
+	let sniff = Sniff {
+		io: Some(socket),
+		read: Vec::with_capacity(PREFACE.len()),
+	};
+	handle.spawn(sniff.then(move |result| {
+		match result {
+			Ok((io, true)) => serve_connection(&handle2, io, service),
+			Ok((io, false)) => http.bind_connection(&handle2, io, addr, service),
+			Err(err) => debug!("Error reading from {}: {:?}", addr, err),
+		}
+		Ok(())
+	}));
+}
+
+/// Serves HTTP/2 over given connection.
+pub fn serve_connection<T, M, S>(handle: &Handle, io: T, service: ServerHandler<M, S>) where
+	T: AsyncRead + AsyncWrite + 'static,
+	M: Metadata,
+	S: Middleware<M>,
+{
+	let handle2 = handle.clone();
+	let service = Rc::new(service);
+	handle.spawn(h2::server::handshake(io)
+		.and_then(move |connection| connection.for_each(move |(request, respond)| {
+			handle_request(&handle2, &service, request, respond);
+			Ok(())
+		}))
+		.map_err(|err| debug!("HTTP/2 connection error: {:?}", err))
+	);
+}
+
+fn handle_request<M: Metadata, S: Middleware<M>>(
+	handle: &Handle,
+	service: &Rc<ServerHandler<M, S>>,
+	request: http::Request<h2::RecvStream>,
+	mut respond: SendResponse<Bytes>,
+) {
+	let (mut parts, mut body) = request.into_parts();
+	// HTTP/2 carries the host in `:authority` pseudo-header.
+	if !parts.headers.contains_key(http::header::HOST) {
+		let authority = parts.uri.authority_part().and_then(|authority| authority.as_str().parse().ok());
+		if let Some(authority) = authority {
+			parts.headers.insert(http::header::HOST, authority);
+		}
+	}
+
+	let (sender, request_body) = hyper::Body::pair();
+	let mut release = body.release_capacity().clone();
+	handle.spawn(body
+		.then(move |result| Ok(match result {
+			Ok(data) => {
+				// The data is buffered in hyper, so the flow control window can be moved on.
+				let _ = release.release_capacity(data.len());
+				Ok(hyper::Chunk::from(data))
+			},
+			Err(err) => Err(hyper::Error::from(io::Error::new(io::ErrorKind::Other, err))),
+		}))
+		.forward(sender.sink_map_err(|_| ()))
+		.map(|_| ())
+	);
+
+	let request = server::Request::from(http::Request::from_parts(parts, request_body));
+	handle.spawn(service.call(request).then(move |response| {
+		let response = response.unwrap_or_else(|err| {
+			warn!("Error handling HTTP/2 request: {:?}", err);
+			server::Response::new().with_status(StatusCode::InternalServerError)
+		});
+		let (mut parts, body) = http::Response::<hyper::Body>::from(response).into_parts();
+		parts.version = http::Version::HTTP_2;
+		// Connection-specific headers are not allowed in HTTP/2.
+		for header in &[http::header::CONNECTION, http::header::TRANSFER_ENCODING, http::header::UPGRADE] {
+			parts.headers.remove(header);
+		}
+		parts.headers.remove("keep-alive");
+
+		match respond.send_response(http::Response::from_parts(parts, ()), false) {
+			Ok(stream) => Ok(SendBody { body, stream, pending: Bytes::new() }),
+			Err(err) => {
+				debug!("Error sending HTTP/2 response: {:?}", err);
+				Err(())
+			},
+		}
+	}).and_then(|send| send));
+}
+
+/// Forwards response body to HTTP/2 stream, respecting its flow control window.
+struct SendBody {
+	body: hyper::Body,
+	stream: h2::SendStream<Bytes>,
+	// Part of the body waiting for the window capacity.
+	pending: Bytes,
+}
+
+impl Future for SendBody {
+	type Item = ();
+	type Error = ();
+
+	fn poll(&mut self) -> Poll<(), ()> {
+		loop {
+			if !self.pending.is_empty() {
+				self.stream.reserve_capacity(self.pending.len());
+				let capacity = match self.stream.poll_capacity() {
+					Ok(Async::Ready(Some(capacity))) => capacity,
+					Ok(Async::NotReady) => return Ok(Async::NotReady),
+					Ok(Async::Ready(None)) => {
+						debug!("HTTP/2 stream closed before the response was sent.");
+						return Err(());
+					},
+					Err(err) => {
+						debug!("Error sending HTTP/2 response: {:?}", err);
+						return Err(());
+					},
+				};
+
+				let len = ::std::cmp::min(capacity, self.pending.len());
+				let data = self.pending.split_to(len);
+				if let Err(err) = self.stream.send_data(data, false) {
+					debug!("Error sending HTTP/2 response: {:?}", err);
+					return Err(());
+				}
+				continue;
+			}
+
+			match self.body.poll() {
+				Ok(Async::Ready(Some(chunk))) => self.pending = Bytes::from(chunk),
+				Ok(Async::Ready(None)) => {
+					// Empty frame ending the stream doesn't use the window.
+					if let Err(err) = self.stream.send_data(Bytes::new(), true) {
+						debug!("Error sending HTTP/2 response: {:?}", err);
+						return Err(());
+					}
+					return Ok(Async::Ready(()));
+				},
+				Ok(Async::NotReady) => return Ok(Async::NotReady),
+				Err(err) => {
+					debug!("Error reading response body: {:?}", err);
+					self.stream.send_reset(h2::Reason::INTERNAL_ERROR);
+					return Err(());
+				},
+			}
+		}
+	}
+}
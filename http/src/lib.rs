@@ -24,8 +24,24 @@
 #![warn(missing_docs)]
 
 extern crate unicase;
+extern crate brotli;
+extern crate bytes;
+extern crate flate2;
+extern crate h2;
+extern crate http;
 extern crate jsonrpc_server_utils as server_utils;
 extern crate net2;
+extern crate url;
+#[cfg(feature = "sse")]
+extern crate jsonrpc_pubsub as pubsub;
+#[cfg(feature = "sse")]
+extern crate parking_lot;
+#[cfg(feature = "sse")]
+extern crate rand;
+#[cfg(feature = "tls")]
+extern crate rustls;
+#[cfg(feature = "tls")]
+extern crate tokio_rustls;
 
 pub extern crate jsonrpc_core;
 pub extern crate hyper;
@@ -33,13 +49,22 @@ pub extern crate hyper;
 #[macro_use]
 extern crate log;
 
+mod compression;
 mod handler;
+mod health;
+mod http2;
 mod response;
+mod rest;
+#[cfg(feature = "sse")]
+mod sse;
 mod utils;
+#[cfg(feature = "tls")]
+mod tls;
 #[cfg(test)]
 mod tests;
 
 use std::io;
+use std::rc::Rc;
 use std::sync::{mpsc, Arc};
 use std::net::SocketAddr;
 
@@ -51,11 +76,23 @@ use jsonrpc::futures::sync::oneshot;
 use server_utils::reactor::{Remote, UninitializedRemote};
 
 pub use server_utils::hosts::{Host, DomainsValidation};
-pub use server_utils::cors::{AccessControlAllowOrigin, Origin};
+pub use server_utils::cors::{AccessControlAllowOrigin, AccessControlAllowHeaders, CorsPolicy, Origin};
 pub use server_utils::tokio_core;
 pub use handler::ServerHandler;
 pub use utils::{is_host_allowed, cors_header, CorsHeader};
 pub use response::Response;
+pub use compression::{Compression, ContentCoding};
+pub use rest::RestRoute;
+pub use health::HealthApi;
+#[cfg(feature = "sse")]
+pub use sse::{EventStream, SESSION_HEADER};
+#[cfg(feature = "tls")]
+pub use tls::TlsConfig;
+
+#[cfg(not(feature = "tls"))]
+type TlsConfig = ();
+#[cfg(not(feature = "sse"))]
+type EventStream = ();
 
 /// Action undertaken by a middleware.
 pub enum RequestMiddlewareAction {
@@ -132,6 +169,29 @@ impl RequestMiddleware for NoopRequestMiddleware {
 pub trait MetaExtractor<M: jsonrpc::Metadata>: Sync + Send + 'static {
 	/// Read the metadata from the request
 	fn read_metadata(&self, _: &server::Request) -> M;
+
+	/// Read the metadata from the request sent by a client authenticated with TLS certificates
+	/// (DER-encoded, end-entity first). See `TlsConfig::client_auth`.
+	fn read_metadata_with_certificates(&self, request: &server::Request, _certificates: &[Vec<u8>]) -> M {
+		self.read_metadata(request)
+	}
+
+	/// Read the metadata from the request referencing a session of `EventStream`.
+	/// The session should be returned by `PubSubMetadata::session` to enable subscriptions.
+	/// `certificates` are empty unless the client authenticated with TLS certificates.
+	#[cfg(feature = "sse")]
+	fn read_metadata_with_session(
+		&self,
+		request: &server::Request,
+		certificates: &[Vec<u8>],
+		_session: Arc<pubsub::Session>,
+	) -> M {
+		if certificates.is_empty() {
+			self.read_metadata(request)
+		} else {
+			self.read_metadata_with_certificates(request, certificates)
+		}
+	}
 }
 
 impl<M, F> MetaExtractor<M> for F where
@@ -170,6 +230,8 @@ impl<M: jsonrpc::Metadata, S: jsonrpc::Middleware<M>> Clone for Rpc<M, S> {
 
 type AllowedHosts = Option<Vec<Host>>;
 type CorsDomains = Option<Vec<AccessControlAllowOrigin>>;
+type Encodings = Arc<Vec<Arc<jsonrpc::Encoding>>>;
+type RestRoutes = Arc<Vec<RestRoute>>;
 
 /// REST -> RPC converter state.
 #[derive(Debug, PartialEq, Clone, Copy)]
@@ -189,6 +251,67 @@ pub enum RestApi {
 	Disabled,
 }
 
+/// JSON-RPC handler with its own CORS, hosts and middleware settings,
+/// served on a path prefix (see `ServerBuilder::endpoint`).
+pub struct Endpoint<M: jsonrpc::Metadata = (), S: jsonrpc::Middleware<M> = jsonrpc::NoopMiddleware> {
+	handler: Rpc<M, S>,
+	request_middleware: Arc<RequestMiddleware>,
+	cors_domains: CorsDomains,
+	allowed_hosts: AllowedHosts,
+}
+
+impl<M: jsonrpc::Metadata + Default, S: jsonrpc::Middleware<M>> Endpoint<M, S> {
+	/// Creates new `Endpoint` for given `IoHandler`.
+	/// By default CORS headers are not sent and `Host` header is not validated.
+	pub fn new<T>(handler: T) -> Self where
+		T: Into<MetaIoHandler<M, S>>
+	{
+		Self::with_meta_extractor(handler, NoopExtractor)
+	}
+}
+
+impl<M: jsonrpc::Metadata, S: jsonrpc::Middleware<M>> Endpoint<M, S> {
+	/// Creates new `Endpoint` for given `IoHandler` and metadata extractor.
+	pub fn with_meta_extractor<T, E>(handler: T, extractor: E) -> Self where
+		T: Into<MetaIoHandler<M, S>>,
+		E: MetaExtractor<M>,
+	{
+		Endpoint {
+			handler: Rpc {
+				handler: Arc::new(handler.into()),
+				extractor: Arc::new(extractor),
+			},
+			request_middleware: Arc::new(NoopRequestMiddleware::default()),
+			cors_domains: None,
+			allowed_hosts: None,
+		}
+	}
+
+	/// Configures a list of allowed CORS origins.
+	pub fn cors(mut self, cors_domains: DomainsValidation<AccessControlAllowOrigin>) -> Self {
+		self.cors_domains = cors_domains.into();
+		self
+	}
+
+	/// Configures request middleware.
+	pub fn request_middleware<T: RequestMiddleware>(mut self, middleware: T) -> Self {
+		self.request_middleware = Arc::new(middleware);
+		self
+	}
+
+	/// Allow connections only with `Host` header set to binding address.
+	pub fn allow_only_bind_host(mut self) -> Self {
+		self.allowed_hosts = Some(Vec::new());
+		self
+	}
+
+	/// Specify a list of valid `Host` headers. Binding address is allowed automatically.
+	pub fn allowed_hosts(mut self, allowed_hosts: DomainsValidation<Host>) -> Self {
+		self.allowed_hosts = allowed_hosts.into();
+		self
+	}
+}
+
 /// Convenient JSON-RPC HTTP Server builder.
 pub struct ServerBuilder<M: jsonrpc::Metadata = (), S: jsonrpc::Middleware<M> = jsonrpc::NoopMiddleware> {
 	handler: Arc<MetaIoHandler<M, S>>,
@@ -196,11 +319,24 @@ pub struct ServerBuilder<M: jsonrpc::Metadata = (), S: jsonrpc::Middleware<M> =
 	meta_extractor: Arc<MetaExtractor<M>>,
 	request_middleware: Arc<RequestMiddleware>,
 	cors_domains: CorsDomains,
+	cors_policy: CorsPolicy,
 	allowed_hosts: AllowedHosts,
 	rest_api: RestApi,
+	rest_routes: Vec<RestRoute>,
+	path: Option<String>,
+	endpoints: Vec<(String, Endpoint<M, S>)>,
+	health_api: Vec<HealthApi>,
+	event_stream: Option<EventStream>,
 	keep_alive: bool,
 	threads: usize,
 	max_request_body_size: usize,
+	deprecation_warnings: bool,
+	encodings: Vec<Arc<jsonrpc::Encoding>>,
+	tls: Option<TlsConfig>,
+	http2: bool,
+	compression: Compression,
+	get_requests: bool,
+	get_cache_control: Option<String>,
 }
 
 const SENDER_PROOF: &'static str = "Server initialization awaits local address.";
@@ -234,11 +370,24 @@ impl<M: jsonrpc::Metadata, S: jsonrpc::Middleware<M>> ServerBuilder<M, S> {
 			meta_extractor: Arc::new(extractor),
 			request_middleware: Arc::new(NoopRequestMiddleware::default()),
 			cors_domains: None,
+			cors_policy: CorsPolicy::default(),
 			allowed_hosts: None,
 			rest_api: RestApi::Disabled,
+			rest_routes: Vec::new(),
+			path: None,
+			endpoints: Vec::new(),
+			health_api: Vec::new(),
+			event_stream: None,
 			keep_alive: true,
 			threads: 1,
 			max_request_body_size: 5 * 1024 * 1024,
+			deprecation_warnings: false,
+			encodings: Vec::new(),
+			tls: None,
+			http2: false,
+			compression: Compression::disabled(),
+			get_requests: false,
+			get_cache_control: None,
 		}
 	}
 
@@ -251,12 +400,53 @@ impl<M: jsonrpc::Metadata, S: jsonrpc::Middleware<M>> ServerBuilder<M, S> {
 
 	/// Enable the REST -> RPC converter. Allows you to invoke RPCs
 	/// by sending `POST /<method>/<param1>/<param2>` requests
-	/// (with no body). Disabled by default.
+	/// (with no body). Responses to failed calls have HTTP status code matching the error.
+	/// Disabled by default.
 	pub fn rest_api(mut self, rest_api: RestApi) -> Self {
 		self.rest_api = rest_api;
 		self
 	}
 
+	/// Maps requests matching the route to calls of a JSON-RPC method, e.g.
+	/// `RestRoute::new(Method::Get, "/accounts/{address}/balance", "balance")`.
+	/// Routes are checked in order they were added and take precedence over other requests.
+	/// Routes for GET can only call methods marked with `MetaIoHandler::mark_read_only`,
+	/// requests with other HTTP methods have to be sent with `Content-Type: application/json`.
+	pub fn rest_route(mut self, route: RestRoute) -> Self {
+		self.rest_routes.push(route);
+		self
+	}
+
+	/// Serves the handler only on given path prefix (e.g. `/public`) instead of every path.
+	/// Requests to paths not matching any endpoint are responded with 404.
+	pub fn path(mut self, path: &str) -> Self {
+		self.path = Some(utils::normalize_path(path));
+		self
+	}
+
+	/// Serves another handler on given path prefix (e.g. `/admin`).
+	/// The endpoint has its own CORS, hosts and middleware settings, others are shared.
+	/// Prefix of the path is stripped before the request is handled.
+	pub fn endpoint(mut self, path: &str, endpoint: Endpoint<M, S>) -> Self {
+		self.endpoints.push((utils::normalize_path(path), endpoint));
+		self
+	}
+
+	/// Responds to `GET` requests to the path of given probe (e.g. `/health` or `/ready`),
+	/// optionally with status depending on the result of a call.
+	pub fn health_api(mut self, health_api: HealthApi) -> Self {
+		self.health_api.push(health_api);
+		self
+	}
+
+	/// Streams notifications of subscriptions as Server-Sent Events,
+	/// for clients unable to use WebSockets (requires `sse` feature). Disabled by default.
+	#[cfg(feature = "sse")]
+	pub fn event_stream(mut self, event_stream: EventStream) -> Self {
+		self.event_stream = Some(event_stream);
+		self
+	}
+
 	/// Sets Enables or disables HTTP keep-alive.
 	/// Default is true.
 	pub fn keep_alive(mut self, val: bool) -> Self {
@@ -286,6 +476,13 @@ impl<M: jsonrpc::Metadata, S: jsonrpc::Middleware<M>> ServerBuilder<M, S> {
 		self
 	}
 
+	/// Configures CORS headers sent to allowed origins, e.g. to allow `Authorization` header.
+	/// Applies to all endpoints.
+	pub fn cors_policy(mut self, cors_policy: CorsPolicy) -> Self {
+		self.cors_policy = cors_policy;
+		self
+	}
+
 	/// Configures request middleware
 	pub fn request_middleware<T: RequestMiddleware>(mut self, middleware: T) -> Self {
 		self.request_middleware = Arc::new(middleware);
@@ -316,35 +513,111 @@ impl<M: jsonrpc::Metadata, S: jsonrpc::Middleware<M>> ServerBuilder<M, S> {
 		self
 	}
 
+	/// Adds a `Warning` header to responses for calls to deprecated methods.
+	/// Disabled by default.
+	pub fn deprecation_warnings(mut self, enabled: bool) -> Self {
+		self.deprecation_warnings = enabled;
+		self
+	}
+
+	/// Accepts requests serialized with given encoding (e.g. CBOR or MessagePack).
+	/// The encoding is chosen by `Content-Type` of the request and the response
+	/// is serialized with the same encoding. JSON is always accepted.
+	pub fn encoding<E: jsonrpc::Encoding>(mut self, encoding: E) -> Self {
+		self.encodings.push(Arc::new(encoding));
+		self
+	}
+
+	/// Accepts calls sent with GET as `/?method=...&params=...&id=...`
+	/// (`params` and `id` are URL-encoded JSON, `id` defaults to `1`).
+	/// Only methods marked with `MetaIoHandler::mark_read_only` can be called that way.
+	/// Disabled by default.
+	pub fn get_requests(mut self, enabled: bool) -> Self {
+		self.get_requests = enabled;
+		self
+	}
+
+	/// Sends given `Cache-Control` header (e.g. `public, max-age=60`) with successful
+	/// responses to calls made with GET (including `RestRoute`s for GET),
+	/// so they can be cached by proxies and CDNs.
+	/// Error responses are never marked as cacheable. No header is sent by default.
+	pub fn get_cache_control<T: Into<String>>(mut self, cache_control: T) -> Self {
+		self.get_cache_control = Some(cache_control.into());
+		self
+	}
+
+	/// Compresses responses with a coding negotiated using `Accept-Encoding` header.
+	/// Disabled by default. Compressed request bodies are always accepted.
+	pub fn compression(mut self, compression: Compression) -> Self {
+		self.compression = compression;
+		self
+	}
+
+	/// Serves HTTPS with given certificates.
+	#[cfg(feature = "tls")]
+	pub fn tls(mut self, config: TlsConfig) -> Self {
+		self.tls = Some(config);
+		self
+	}
+
+	/// Accepts HTTP/2 connections: h2c with prior knowledge (plain connections
+	/// starting with HTTP/2 preface) and h2 negotiated with ALPN over TLS.
+	/// Disabled by default.
+	pub fn http2(mut self, enabled: bool) -> Self {
+		self.http2 = enabled;
+		self
+	}
+
 	/// Start this JSON-RPC HTTP server trying to bind to specified `SocketAddr`.
 	pub fn start_http(self, addr: &SocketAddr) -> io::Result<Server> {
-		let cors_domains = self.cors_domains;
-		let request_middleware = self.request_middleware;
-		let allowed_hosts = self.allowed_hosts;
-		let jsonrpc_handler = Rpc {
-			handler: self.handler,
-			extractor: self.meta_extractor,
-		};
-		let rest_api = self.rest_api;
-		let keep_alive = self.keep_alive;
-		let reuse_port = self.threads > 1;
+		let mut endpoints = self.endpoints;
+		// The longest matching prefix wins.
+		endpoints.sort_by(|a, b| b.0.len().cmp(&a.0.len()));
+		let settings = Arc::new(Settings {
+			jsonrpc_handler: Rpc {
+				handler: self.handler,
+				extractor: self.meta_extractor,
+			},
+			cors_domains: self.cors_domains,
+			cors_policy: Arc::new(match self.event_stream {
+				#[cfg(feature = "sse")]
+				Some(_) => self.cors_policy.allow_headers(vec![SESSION_HEADER]),
+				_ => self.cors_policy,
+			}),
+			request_middleware: self.request_middleware,
+			allowed_hosts: self.allowed_hosts,
+			rest_api: self.rest_api,
+			rest_routes: Arc::new(self.rest_routes),
+			path: self.path,
+			endpoints,
+			health_api: Arc::new(self.health_api),
+			event_stream: self.event_stream,
+			keep_alive: self.keep_alive,
+			reuse_port: self.threads > 1,
+			max_request_body_size: self.max_request_body_size,
+			deprecation_warnings: self.deprecation_warnings,
+			encodings: Arc::new(self.encodings),
+			compression: Arc::new(self.compression),
+			get_requests: self.get_requests,
+			get_cache_control: self.get_cache_control.map(Arc::new),
+			tls: self.tls,
+			http2: self.http2,
+		});
 
 		let (local_addr_tx, local_addr_rx) = mpsc::channel();
 		let (close, shutdown_signal) = oneshot::channel();
 		let eloop = self.remote.init_with_name("http.worker0")?;
-		let req_max_size = self.max_request_body_size;
+		#[cfg(feature = "tls")]
+		{
+			if let (true, &Some(ref tls)) = (settings.http2, &settings.tls) {
+				tls::enable_http2(tls)?;
+			}
+		}
 		serve(
 			(shutdown_signal, local_addr_tx),
 			eloop.remote(),
 			addr.to_owned(),
-			cors_domains.clone(),
-			request_middleware.clone(),
-			allowed_hosts.clone(),
-			jsonrpc_handler.clone(),
-			rest_api,
-			keep_alive,
-			reuse_port,
-			req_max_size,
+			settings.clone(),
 		);
 		let handles = (0..self.threads - 1).map(|i| {
 			let (local_addr_tx, local_addr_rx) = mpsc::channel();
@@ -354,14 +627,7 @@ impl<M: jsonrpc::Metadata, S: jsonrpc::Middleware<M>> ServerBuilder<M, S> {
 				(shutdown_signal, local_addr_tx),
 				eloop.remote(),
 				addr.to_owned(),
-				cors_domains.clone(),
-				request_middleware.clone(),
-				allowed_hosts.clone(),
-				jsonrpc_handler.clone(),
-				rest_api,
-				keep_alive,
-				reuse_port,
-				req_max_size,
+				settings.clone(),
 			);
 			Ok((eloop, close, local_addr_rx))
 		}).collect::<io::Result<Vec<_>>>()?;
@@ -390,20 +656,68 @@ fn recv_address(local_addr_rx: mpsc::Receiver<io::Result<SocketAddr>>) -> io::Re
 	})?
 }
 
-fn serve<M: jsonrpc::Metadata, S: jsonrpc::Middleware<M>>(
-	signals: (oneshot::Receiver<()>, mpsc::Sender<io::Result<SocketAddr>>),
-	remote: tokio_core::reactor::Remote,
-	addr: SocketAddr,
+// Settings shared by all threads of the server.
+struct Settings<M: jsonrpc::Metadata, S: jsonrpc::Middleware<M>> {
+	jsonrpc_handler: Rpc<M, S>,
 	cors_domains: CorsDomains,
+	cors_policy: Arc<CorsPolicy>,
 	request_middleware: Arc<RequestMiddleware>,
 	allowed_hosts: AllowedHosts,
-	jsonrpc_handler: Rpc<M, S>,
 	rest_api: RestApi,
+	rest_routes: RestRoutes,
+	path: Option<String>,
+	endpoints: Vec<(String, Endpoint<M, S>)>,
+	health_api: Arc<Vec<HealthApi>>,
+	event_stream: Option<EventStream>,
 	keep_alive: bool,
 	reuse_port: bool,
 	max_request_body_size: usize,
+	deprecation_warnings: bool,
+	encodings: Encodings,
+	compression: Arc<Compression>,
+	get_requests: bool,
+	get_cache_control: Option<Arc<String>>,
+	tls: Option<TlsConfig>,
+	http2: bool,
+}
+
+impl<M: jsonrpc::Metadata, S: jsonrpc::Middleware<M>> Settings<M, S> {
+	// Creates a handler of the main server or an endpoint, with settings common to both.
+	fn handler(
+		&self,
+		jsonrpc_handler: Rpc<M, S>,
+		cors_domains: CorsDomains,
+		allowed_hosts: AllowedHosts,
+		middleware: Arc<RequestMiddleware>,
+		remote: tokio_core::reactor::Remote,
+	) -> ServerHandler<M, S> {
+		ServerHandler::new(
+			jsonrpc_handler,
+			cors_domains,
+			allowed_hosts,
+			middleware,
+			self.rest_api,
+			self.max_request_body_size,
+		)
+			.with_cors_policy(self.cors_policy.clone())
+			.with_rest_routes(self.rest_routes.clone())
+			.with_deprecation_warnings(self.deprecation_warnings)
+			.with_encodings(self.encodings.clone())
+			.with_compression(self.compression.clone())
+			.with_get_requests(self.get_requests)
+			.with_get_cache_control(self.get_cache_control.clone())
+			.with_remote(remote)
+	}
+}
+
+fn serve<M: jsonrpc::Metadata, S: jsonrpc::Middleware<M>>(
+	signals: (oneshot::Receiver<()>, mpsc::Sender<io::Result<SocketAddr>>),
+	remote: tokio_core::reactor::Remote,
+	addr: SocketAddr,
+	settings: Arc<Settings<M, S>>,
 ) {
 	let (shutdown_signal, local_addr_tx) = signals;
+	let reuse_port = settings.reuse_port;
 	remote.spawn(move |handle| {
 		let handle1 = handle.clone();
 		let bind = move || {
@@ -441,24 +755,49 @@ fn serve<M: jsonrpc::Metadata, S: jsonrpc::Middleware<M>>(
 
 		let handle = handle.clone();
 		bind_result.and_then(move |(listener, local_addr)| {
-			let allowed_hosts = server_utils::hosts::update(allowed_hosts, &local_addr);
+			let allowed_hosts = server_utils::hosts::update(settings.allowed_hosts.clone(), &local_addr);
+			let endpoints = Arc::new(settings.endpoints.iter().map(|&(ref path, ref endpoint)| {
+				settings.handler(
+					endpoint.handler.clone(),
+					endpoint.cors_domains.clone(),
+					server_utils::hosts::update(endpoint.allowed_hosts.clone(), &local_addr),
+					endpoint.request_middleware.clone(),
+					handle.remote().clone(),
+				).with_path(path.clone())
+			}).collect::<Vec<_>>());
 
 			let http = {
 				let mut http = server::Http::new();
-				http.keep_alive(keep_alive);
+				http.keep_alive(settings.keep_alive);
 				http.sleep_on_errors(true);
-				http
+				Rc::new(http)
 			};
 			listener.incoming()
 				.for_each(move |(socket, addr)| {
-					http.bind_connection(&handle, socket, addr, ServerHandler::new(
-						jsonrpc_handler.clone(),
-						cors_domains.clone(),
+					let service = settings.handler(
+						settings.jsonrpc_handler.clone(),
+						settings.cors_domains.clone(),
 						allowed_hosts.clone(),
-						request_middleware.clone(),
-						rest_api,
-						max_request_body_size,
-					));
+						settings.request_middleware.clone(),
+						handle.remote().clone(),
+					)
+						.with_endpoints(endpoints.clone())
+						.with_health_api(settings.health_api.clone());
+					let service = match settings.event_stream {
+						#[cfg(feature = "sse")]
+						Some(ref event_stream) => service.with_event_stream(event_stream.clone()),
+						_ => service,
+					};
+					let service = match settings.path {
+						Some(ref path) => service.with_path(path.clone()),
+						None => service,
+					};
+					match settings.tls {
+						#[cfg(feature = "tls")]
+						Some(ref tls) => tls::bind_connection(tls, &http, &handle, socket, addr, service),
+						_ if settings.http2 => http2::bind_connection(&http, &handle, socket, addr, service),
+						_ => http.bind_connection(&handle, socket, addr, service),
+					}
 					Ok(())
 				})
 				.map_err(|e| {
@@ -48,6 +48,15 @@ impl Response {
 		}
 	}
 
+	/// Create a response for a path without any endpoint.
+	pub fn not_found() -> Self {
+		Response {
+			code: StatusCode::NotFound,
+			content_type: header::ContentType::plaintext(),
+			content: "Requested path is not served by any JSON-RPC endpoint.\n".to_owned(),
+		}
+	}
+
 	/// Create a response for unsupported content type.
 	pub fn unsupported_content_type() -> Self {
 		Response {
@@ -57,6 +66,15 @@ impl Response {
 		}
 	}
 
+	/// Create a response for unsupported content encoding.
+	pub fn unsupported_content_encoding(encoding: &str) -> Self {
+		Response {
+			code: StatusCode::UnsupportedMediaType,
+			content_type: header::ContentType::plaintext(),
+			content: format!("Supplied content encoding is not supported: {}. Use gzip, deflate or br.\n", encoding),
+		}
+	}
+
 	/// Create a response for disallowed method used.
 	pub fn method_not_allowed() -> Self {
 		Response {
@@ -66,6 +84,15 @@ impl Response {
 		}
 	}
 
+	/// Create a response for a call with GET to a method that is not read-only.
+	pub fn not_read_only(method: &str) -> Self {
+		Response {
+			code: StatusCode::MethodNotAllowed,
+			content_type: header::ContentType::plaintext(),
+			content: format!("Method {} is not read-only, POST is required\n", method),
+		}
+	}
+
 	/// CORS invalid
 	pub fn invalid_cors() -> Self {
 		Response {
@@ -92,6 +119,15 @@ impl Response {
 			content: msg.into()
 		}
 	}
+
+	/// Create a response with given body and 503 Service Unavailable status code.
+	pub fn service_unavailable<T: Into<String>>(response: T) -> Self {
+		Response {
+			code: StatusCode::ServiceUnavailable,
+			content_type: header::ContentType::json(),
+			content: response.into(),
+		}
+	}
 }
 
 impl Into<server::Response> for Response {
@@ -0,0 +1,181 @@
+//! Mapping of REST requests to JSON-RPC calls.
+
+use hyper::{Method, StatusCode, Uri};
+use url::percent_encoding::percent_decode;
+use url::form_urlencoded;
+
+use jsonrpc::serde_json::{self, Map, Value};
+use jsonrpc::types::{ErrorCode, Id, MethodCall, Output, Params, Version, SERVER_BUSY_CODE};
+use response::Response;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+	Literal(String),
+	Param(String),
+}
+
+/// Maps HTTP requests matching a path template to calls of a JSON-RPC method.
+///
+/// Params of the call are named: segments of the template in braces,
+/// query string parameters and fields of JSON object sent in the body are passed to the method.
+/// Values are parsed as JSON and passed as strings if that fails.
+///
+/// The response contains the result of the call, or the error with a matching HTTP status code.
+#[derive(Debug, Clone)]
+pub struct RestRoute {
+	method: Method,
+	segments: Vec<Segment>,
+	rpc_method: String,
+}
+
+impl RestRoute {
+	/// Creates a route for given HTTP method and path template, e.g. `/accounts/{address}/balance`.
+	pub fn new<T: Into<String>>(method: Method, template: &str, rpc_method: T) -> Self {
+		let segments = template.split('/')
+			.filter(|segment| !segment.is_empty())
+			.map(|segment| if segment.starts_with('{') && segment.ends_with('}') && segment.len() > 2 {
+				Segment::Param(segment[1..segment.len() - 1].to_owned())
+			} else {
+				Segment::Literal(segment.to_owned())
+			})
+			.collect();
+
+		RestRoute {
+			method,
+			segments,
+			rpc_method: rpc_method.into(),
+		}
+	}
+
+	/// Returns HTTP method of the route.
+	pub fn method(&self) -> &Method {
+		&self.method
+	}
+
+	fn matches(&self, method: &Method, path: &str) -> Option<Map<String, Value>> {
+		if *method != self.method {
+			return None;
+		}
+
+		let mut params = Map::new();
+		let mut parts = path.split('/').filter(|part| !part.is_empty());
+		for segment in &self.segments {
+			let part = parts.next()?;
+			match *segment {
+				Segment::Literal(ref literal) if literal == part => {},
+				Segment::Literal(_) => return None,
+				Segment::Param(ref name) => {
+					let value = percent_decode(part.as_bytes()).decode_utf8().ok()?;
+					params.insert(name.clone(), parse_value(&value));
+				},
+			}
+		}
+
+		if parts.next().is_some() {
+			return None;
+		}
+		Some(params)
+	}
+}
+
+/// Call of a JSON-RPC method resolved from a REST request.
+#[derive(Debug)]
+pub struct RestCall {
+	method: String,
+	params: Map<String, Value>,
+}
+
+impl RestCall {
+	/// Returns the name of JSON-RPC method to call.
+	pub fn method(&self) -> &str {
+		&self.method
+	}
+
+	/// Creates the call with params read from given body.
+	/// Fields of JSON object are added to named params, but they don't replace params
+	/// from the path or the query. JSON array is passed as positional params if there are no named ones.
+	pub fn into_method_call(self, body: &[u8]) -> Result<MethodCall, String> {
+		let RestCall { method, mut params } = self;
+		let body = if body.iter().all(|byte| byte.is_ascii_whitespace()) {
+			Value::Null
+		} else {
+			serde_json::from_slice(body).map_err(|err| format!("Invalid JSON in request body: {}\n", err))?
+		};
+
+		let params = match body {
+			Value::Null if params.is_empty() => Params::None,
+			Value::Null => Params::Map(params),
+			Value::Object(fields) => {
+				for (name, value) in fields {
+					params.entry(name).or_insert(value);
+				}
+				Params::Map(params)
+			},
+			Value::Array(values) if params.is_empty() => Params::Array(values),
+			_ => return Err("Request body has to be a JSON object or, if there are no other params, an array\n".into()),
+		};
+
+		Ok(MethodCall {
+			jsonrpc: Some(Version::V2),
+			method,
+			params: Some(params),
+			id: Id::Num(1),
+		})
+	}
+}
+
+/// Finds the route matching the request.
+pub fn resolve(routes: &[RestRoute], method: &Method, uri: &Uri) -> Option<RestCall> {
+	let (route, mut params) = routes.iter()
+		.filter_map(|route| route.matches(method, uri.path()).map(|params| (route, params)))
+		.next()?;
+
+	for (name, value) in form_urlencoded::parse(uri.query().unwrap_or("").as_bytes()) {
+		params.entry(name.into_owned()).or_insert_with(|| parse_value(&value));
+	}
+
+	Some(RestCall {
+		method: route.rpc_method.clone(),
+		params,
+	})
+}
+
+fn parse_value(value: &str) -> Value {
+	serde_json::from_str(value).unwrap_or_else(|_| Value::String(value.to_owned()))
+}
+
+/// Returns HTTP status code matching JSON-RPC error.
+pub fn status_code(code: &ErrorCode) -> StatusCode {
+	match *code {
+		ErrorCode::ParseError | ErrorCode::InvalidRequest | ErrorCode::InvalidParams => StatusCode::BadRequest,
+		ErrorCode::MethodNotFound => StatusCode::NotFound,
+		ErrorCode::ServerError(SERVER_BUSY_CODE) => StatusCode::ServiceUnavailable,
+		ErrorCode::InternalError | ErrorCode::ServerError(_) => StatusCode::InternalServerError,
+	}
+}
+
+/// Converts output of JSON-RPC call to REST response.
+/// If `unwrap` is `false` the output is kept intact and only the status code is set.
+pub fn response(output: Output, unwrap: bool) -> Response {
+	let code = match output {
+		Output::Failure(ref failure) => status_code(&failure.error.code),
+		_ => StatusCode::Ok,
+	};
+
+	let body = if unwrap {
+		match output {
+			Output::Success(success) => match success.raw_result {
+				Some(raw) => raw.get().to_owned(),
+				None => success.result.to_string(),
+			},
+			Output::Failure(failure) => serde_json::to_string(&failure.error)
+				.expect("Error serialization is infallible; qed"),
+		}
+	} else {
+		serde_json::to_string(&output).expect("Serialization of response is infallible; qed")
+	};
+
+	let mut response = Response::ok(format!("{}\n", body));
+	response.code = code;
+	response
+}
@@ -0,0 +1,130 @@
+//! Server-Sent Events transport for publish-subscribe.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use hyper::{self, server};
+use hyper::header::{self, Headers};
+use parking_lot::RwLock;
+use rand::{self, Rng};
+
+use jsonrpc::futures::{Future, Sink, Stream, stream};
+use jsonrpc::futures::sync::mpsc;
+use pubsub::Session;
+use server_utils::tokio_core::reactor::{Interval, Remote};
+
+/// Name of the header referencing the session in calls.
+pub const SESSION_HEADER: &'static str = "X-Session-Id";
+
+/// Stream of notifications sent as Server-Sent Events (see `ServerBuilder::event_stream`).
+///
+/// A `GET` request to the path of the stream opens a new `pubsub::Session`.
+/// The first event (`session`) carries the id of the session, calls sent with the id
+/// in `X-Session-Id` header get the session in metadata (see `MetaExtractor::read_metadata_with_session`),
+/// and notifications of subscriptions made by these calls are sent as `message` events.
+/// The session is dropped (cancelling the subscriptions) when the client disconnects.
+#[derive(Debug, Clone)]
+pub struct EventStream {
+	path: String,
+	keep_alive: Duration,
+	buffer: usize,
+	sessions: Arc<RwLock<HashMap<String, Arc<Session>>>>,
+}
+
+impl EventStream {
+	/// Creates a stream served on given path (e.g. `/events`).
+	pub fn new(path: &str) -> Self {
+		EventStream {
+			path: path.into(),
+			keep_alive: Duration::from_secs(15),
+			buffer: 256,
+			sessions: Default::default(),
+		}
+	}
+
+	/// Interval of comments sent to keep the connection open (15 seconds by default).
+	/// Disconnected clients are detected when sending, so it also bounds the lifetime of stale sessions.
+	pub fn keep_alive(mut self, keep_alive: Duration) -> Self {
+		self.keep_alive = keep_alive;
+		self
+	}
+
+	/// Maximal number of notifications waiting to be sent to a client (256 by default).
+	pub fn buffer(mut self, buffer: usize) -> Self {
+		self.buffer = buffer;
+		self
+	}
+
+	/// Returns `true` if the request opens the stream.
+	pub fn matches(&self, request: &server::Request) -> bool {
+		*request.method() == hyper::Method::Get && request.uri().path() == self.path
+	}
+
+	/// Returns the session referenced by the request.
+	/// `Err` is returned if the request references a session which doesn't exist (anymore).
+	pub fn session(&self, headers: &Headers) -> Result<Option<Arc<Session>>, String> {
+		let id = match headers.get_raw(SESSION_HEADER).and_then(|raw| raw.one()) {
+			Some(id) => String::from_utf8_lossy(id).into_owned(),
+			None => return Ok(None),
+		};
+
+		match self.sessions.read().get(&id) {
+			Some(session) => Ok(Some(session.clone())),
+			None => Err(format!("Unknown session: {}\n", id)),
+		}
+	}
+
+	/// Opens a new session and returns the response streaming its notifications.
+	pub fn open(&self, remote: &Remote) -> server::Response {
+		let (sender, receiver) = mpsc::channel(self.buffer);
+		let id = session_id();
+		self.sessions.write().insert(id.clone(), Arc::new(Session::new(sender)));
+
+		let (body_sender, body) = hyper::Body::pair();
+		let keep_alive = self.keep_alive;
+		let sessions = self.sessions.clone();
+		remote.spawn(move |handle| {
+			let comments: Box<Stream<Item = String, Error = ()>> = match Interval::new(keep_alive, handle) {
+				Ok(interval) => Box::new(interval.map(|_| ": keep-alive\n\n".to_owned()).map_err(|_| ())),
+				Err(err) => {
+					warn!("Unable to keep the event stream alive: {:?}", err);
+					Box::new(stream::empty())
+				},
+			};
+			let events = stream::once(Ok(format!("event: session\ndata: {}\n\n", id)))
+				.chain(receiver.map(|message| event(&message)).select(comments))
+				.map(|chunk| Ok(hyper::Chunk::from(chunk)));
+
+			events.forward(body_sender.sink_map_err(|e| {
+				debug!("Event stream closed: {:?}", e);
+			})).then(move |_| {
+				// Dropping the session removes its subscriptions.
+				sessions.write().remove(&id);
+				Ok(())
+			})
+		});
+
+		server::Response::new()
+			.with_header(header::ContentType("text/event-stream".parse().expect("Valid mime type; qed")))
+			.with_header(header::CacheControl(vec![header::CacheDirective::NoCache]))
+			.with_body(body)
+	}
+}
+
+// Every line of the message has to be prefixed, otherwise it would end the event.
+fn event(message: &str) -> String {
+	let mut event = String::with_capacity(message.len() + 8);
+	for line in message.lines() {
+		event.push_str("data: ");
+		event.push_str(line);
+		event.push('\n');
+	}
+	event.push('\n');
+	event
+}
+
+fn session_id() -> String {
+	let bytes: [u8; 16] = rand::thread_rng().gen();
+	bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
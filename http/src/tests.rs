@@ -3,7 +3,7 @@ extern crate jsonrpc_core;
 use std::str::Lines;
 use std::net::TcpStream;
 use std::io::{Read, Write};
-use self::jsonrpc_core::{IoHandler, Params, Value, Error};
+use self::jsonrpc_core::{IoHandler, MetaIoHandler, Params, Value, Error, Deprecation};
 
 use self::jsonrpc_core::futures::{self, Future};
 use super::*;
@@ -116,6 +116,109 @@ fn should_return_method_not_allowed_for_get() {
 	assert_eq!(response.body, "3D\nUsed HTTP Method is not allowed. POST or OPTIONS is required\n".to_owned());
 }
 
+fn serve_get() -> Server {
+	let mut io = IoHandler::default();
+	io.add_method("hello", |params: Params| {
+		match params.parse::<(u64, )>() {
+			Ok((num, )) => Ok(Value::String(format!("world: {}", num))),
+			_ => Ok(Value::String("world".into())),
+		}
+	});
+	io.add_method("transfer", |_params: Params| Ok(Value::Bool(true)));
+	io.add_method("fail", |_params: Params| Err(Error::internal_error()));
+	io.mark_read_only("hello");
+	io.mark_read_only("fail");
+
+	ServerBuilder::new(io)
+		.cors(DomainsValidation::AllowOnly(vec![AccessControlAllowOrigin::Value("parity.io".into())]))
+		.get_requests(true)
+		.get_cache_control("public, max-age=60")
+		.start_http(&"127.0.0.1:0".parse().unwrap())
+		.unwrap()
+}
+
+#[test]
+fn should_handle_get_request_to_read_only_method() {
+	// given
+	let server = serve_get();
+
+	// when
+	let response = request(server,
+		"\
+			GET /?method=hello&params=%5B5%5D&id=%22a%22 HTTP/1.1\r\n\
+			Host: 127.0.0.1:8080\r\n\
+			Origin: http://parity.io\r\n\
+			Connection: close\r\n\
+			\r\n\
+		"
+	);
+
+	// then
+	assert_eq!(response.status, "HTTP/1.1 200 OK".to_owned());
+	assert!(response.headers.contains("Access-Control-Allow-Methods: OPTIONS, POST, GET"), "{}", response.headers);
+	assert!(response.headers.contains("Cache-Control: public, max-age=60"), "{}", response.headers);
+	assert_eq!(response.body, "2F\n{\"jsonrpc\":\"2.0\",\"result\":\"world: 5\",\"id\":\"a\"}\n".to_owned());
+}
+
+#[test]
+fn should_not_cache_failed_get_requests() {
+	// given
+	let server = serve_get();
+
+	// when
+	let response = request(server,
+		"\
+			GET /?method=fail HTTP/1.1\r\n\
+			Host: 127.0.0.1:8080\r\n\
+			Connection: close\r\n\
+			\r\n\
+		"
+	);
+
+	// then
+	assert_eq!(response.status, "HTTP/1.1 200 OK".to_owned());
+	assert!(!response.headers.contains("Cache-Control"), "{}", response.headers);
+}
+
+#[test]
+fn should_reject_get_request_to_method_that_is_not_read_only() {
+	// given
+	let server = serve_get();
+
+	// when
+	let response = request(server,
+		"\
+			GET /?method=transfer HTTP/1.1\r\n\
+			Host: 127.0.0.1:8080\r\n\
+			Connection: close\r\n\
+			\r\n\
+		"
+	);
+
+	// then
+	assert_eq!(response.status, "HTTP/1.1 405 Method Not Allowed".to_owned());
+	assert_eq!(response.body, "33\nMethod transfer is not read-only, POST is required\n".to_owned());
+}
+
+#[test]
+fn should_return_bad_request_for_get_request_without_method() {
+	// given
+	let server = serve_get();
+
+	// when
+	let response = request(server,
+		"\
+			GET /?params=%5B5%5D HTTP/1.1\r\n\
+			Host: 127.0.0.1:8080\r\n\
+			Connection: close\r\n\
+			\r\n\
+		"
+	);
+
+	// then
+	assert_eq!(response.status, "HTTP/1.1 400 Bad Request".to_owned());
+}
+
 #[test]
 fn should_return_unsupported_media_type_if_not_json() {
 	// given
@@ -407,6 +510,73 @@ fn should_not_allow_request_larger_than_max() {
 	assert_eq!(response.status, "HTTP/1.1 413 Payload Too Large".to_owned());
 }
 
+#[test]
+fn should_add_warning_header_for_deprecated_method() {
+	// given
+	let mut io = IoHandler::default();
+	io.add_method("hello", |_params: Params| Ok(Value::String("world".into())));
+	io.add_deprecated_alias("hi", "hello", Deprecation::new("Use `hello` instead.").sunset("9.0"));
+	let server = ServerBuilder::new(io)
+		.deprecation_warnings(true)
+		.start_http(&"127.0.0.1:0".parse().unwrap())
+		.unwrap();
+
+	// when
+	let req = r#"{"jsonrpc":"2.0","id":1,"method":"hi","params":[]}"#;
+	let response = request(server,
+		&format!("\
+			POST / HTTP/1.1\r\n\
+			Host: 127.0.0.1:8080\r\n\
+			Connection: close\r\n\
+			Content-Type: application/json\r\n\
+			Content-Length: {}\r\n\
+			\r\n\
+			{}\r\n\
+		", req.as_bytes().len(), req)
+	);
+
+	// then
+	assert_eq!(response.status, "HTTP/1.1 200 OK".to_owned());
+	assert!(
+		response.headers.contains(r#"Warning: 299 - "Method hi is deprecated and will be removed in 9.0: Use `hello` instead.""#),
+		"Headers missing in {}", response.headers
+	);
+}
+
+#[test]
+fn should_add_warning_header_for_alias_of_deprecated_method() {
+	// given
+	let mut io = IoHandler::default();
+	io.add_method("hello", |_params: Params| Ok(Value::String("world".into())));
+	io.deprecate("hello", Deprecation::new("Use `greet` instead."));
+	io.add_alias("hi", "hello");
+	let server = ServerBuilder::new(io)
+		.deprecation_warnings(true)
+		.start_http(&"127.0.0.1:0".parse().unwrap())
+		.unwrap();
+
+	// when
+	let req = r#"[{"jsonrpc":"2.0","id":1,"method":"hi","params":[]}]"#;
+	let response = request(server,
+		&format!("\
+			POST / HTTP/1.1\r\n\
+			Host: 127.0.0.1:8080\r\n\
+			Connection: close\r\n\
+			Content-Type: application/json\r\n\
+			Content-Length: {}\r\n\
+			\r\n\
+			{}\r\n\
+		", req.as_bytes().len(), req)
+	);
+
+	// then
+	assert_eq!(response.status, "HTTP/1.1 200 OK".to_owned());
+	assert!(
+		response.headers.contains(r#"Warning: 299 - "Method hello is deprecated: Use `greet` instead.""#),
+		"Headers missing in {}", response.headers
+	);
+}
+
 #[test]
 fn should_reject_invalid_hosts() {
 	// given
@@ -701,27 +871,1085 @@ fn should_return_error_in_case_of_unsecure_rest_and_no_method() {
 	assert_eq!(&response.body, "51\nSupplied content type is not allowed. Content-Type: application/json is required\n");
 }
 
-fn invalid_host() -> String {
-	"29\nProvided Host header is not whitelisted.\n".into()
+fn serve_routes() -> Server {
+	use self::jsonrpc_core::serde_json::Map;
+
+	let mut io = IoHandler::default();
+	io.add_method("balance", |params: Params| {
+		let params: Map<String, Value> = params.parse()?;
+		Ok(Value::String(format!("{}@{}", params["address"].as_str().unwrap_or(""), params["block"])))
+	});
+	io.add_method("transfer", |params: Params| {
+		Ok(Value::Object(params.parse()?))
+	});
+	io.mark_read_only("balance");
+
+	ServerBuilder::new(io)
+		.rest_route(RestRoute::new(hyper::Method::Get, "/accounts/{address}/balance", "balance"))
+		.rest_route(RestRoute::new(hyper::Method::Get, "/accounts/{address}/transfer", "transfer"))
+		.rest_route(RestRoute::new(hyper::Method::Put, "/accounts/{address}", "transfer"))
+		.rest_route(RestRoute::new(hyper::Method::Delete, "/accounts/{address}", "remove"))
+		.start_http(&"127.0.0.1:0".parse().unwrap())
+		.unwrap()
 }
 
-fn cors_invalid() -> String {
-	"76\nOrigin of the request is not whitelisted. CORS headers would not be sent and any side-effects were cancelled as well.\n".into()
+#[test]
+fn should_map_rest_route_with_path_and_query_params() {
+	// given
+	let server = serve_routes();
+
+	// when
+	let response = request(server,
+		"\
+			GET /accounts/0x%61b/balance?block=5 HTTP/1.1\r\n\
+			Host: 127.0.0.1:8080\r\n\
+			Connection: close\r\n\
+			\r\n\
+		"
+	);
+
+	// then
+	assert_eq!(response.status, "HTTP/1.1 200 OK".to_owned());
+	assert_eq!(response.body, "9\n\"0xab@5\"\n".to_owned());
 }
 
-fn method_not_found() -> String {
- "4E\n{\"jsonrpc\":\"2.0\",\"error\":{\"code\":-32601,\"message\":\"Method not found\"},\"id\":1}\n".into()
+#[test]
+fn should_reject_get_rest_route_to_method_that_is_not_read_only() {
+	// given
+	let server = serve_routes();
+
+	// when
+	let response = request(server,
+		"\
+			GET /accounts/0xab/transfer?amount=5 HTTP/1.1\r\n\
+			Host: 127.0.0.1:8080\r\n\
+			Connection: close\r\n\
+			\r\n\
+		"
+	);
+
+	// then
+	assert_eq!(response.status, "HTTP/1.1 405 Method Not Allowed".to_owned());
+	assert_eq!(response.body, "33\nMethod transfer is not read-only, POST is required\n".to_owned());
 }
 
-fn invalid_request() -> String {
- "50\n{\"jsonrpc\":\"2.0\",\"error\":{\"code\":-32600,\"message\":\"Invalid request\"},\"id\":null}\n".into()
+#[test]
+fn should_pass_json_body_of_rest_route_as_params() {
+	// given
+	let server = serve_routes();
+
+	// when
+	let req = r#"{"address":"0xcd","amount":5}"#;
+	let response = request(server,
+		&format!("\
+			PUT /accounts/0xab HTTP/1.1\r\n\
+			Host: 127.0.0.1:8080\r\n\
+			Connection: close\r\n\
+			Content-Type: application/json\r\n\
+			Content-Length: {}\r\n\
+			\r\n\
+			{}\r\n\
+		", req.as_bytes().len(), req)
+	);
+
+	// then
+	assert_eq!(response.status, "HTTP/1.1 200 OK".to_owned());
+	assert_eq!(response.body, "1E\n{\"address\":\"0xab\",\"amount\":5}\n".to_owned());
 }
-fn world() -> String {
- "2A\n{\"jsonrpc\":\"2.0\",\"result\":\"world\",\"id\":1}\n".into()
+
+#[test]
+fn should_return_status_code_matching_error_of_rest_route() {
+	// given
+	let server = serve_routes();
+
+	// when
+	let response = request(server,
+		"\
+			DELETE /accounts/0xab HTTP/1.1\r\n\
+			Host: 127.0.0.1:8080\r\n\
+			Connection: close\r\n\
+			Content-Type: application/json\r\n\
+			Content-Length: 0\r\n\
+			\r\n\
+		"
+	);
+
+	// then
+	assert_eq!(response.status, "HTTP/1.1 404 Not Found".to_owned());
+	assert_eq!(response.body, "2D\n{\"code\":-32601,\"message\":\"Method not found\"}\n".to_owned());
 }
-fn world_5() -> String {
- "2D\n{\"jsonrpc\":\"2.0\",\"result\":\"world: 5\",\"id\":1}\n".into()
+
+#[test]
+fn should_require_json_content_type_for_rest_routes_other_than_get() {
+	// given
+	let server = serve_routes();
+
+	// when
+	let response = request(server,
+		"\
+			PUT /accounts/0xab HTTP/1.1\r\n\
+			Host: 127.0.0.1:8080\r\n\
+			Connection: close\r\n\
+			Content-Type: application/x-www-form-urlencoded\r\n\
+			Content-Length: 8\r\n\
+			\r\n\
+			amount=5\r\n\
+		"
+	);
+
+	// then
+	assert_eq!(response.status, "HTTP/1.1 415 Unsupported Media Type".to_owned());
 }
-fn world_batch() -> String {
- "2C\n[{\"jsonrpc\":\"2.0\",\"result\":\"world\",\"id\":1}]\n".into()
+
+#[test]
+fn should_return_status_code_matching_error_of_rest_request() {
+	// given
+	let server = serve();
+
+	// when
+	let response = request(server,
+		"\
+			POST /unknown/5 HTTP/1.1\r\n\
+			Host: 127.0.0.1:8080\r\n\
+			Connection: close\r\n\
+			Content-Type: application/json\r\n\
+			Content-Length: 0\r\n\
+			\r\n\
+		"
+	);
+
+	// then
+	assert_eq!(response.status, "HTTP/1.1 404 Not Found".to_owned());
+	assert_eq!(response.body, method_not_found());
+}
+
+fn serve_endpoints() -> Server {
+	let mut public = IoHandler::default();
+	public.add_method("hello", |_params: Params| Ok(Value::String("world".into())));
+	let mut admin = IoHandler::default();
+	admin.add_method("shutdown", |_params: Params| Ok(Value::Bool(true)));
+
+	ServerBuilder::new(public)
+		.path("/public/")
+		.endpoint("/admin", Endpoint::new(admin).allowed_hosts(DomainsValidation::AllowOnly(vec!["admin.local".into()])))
+		.start_http(&"127.0.0.1:0".parse().unwrap())
+		.unwrap()
+}
+
+fn endpoint_request(server: Server, path: &str, host: &str, method: &str) -> Response {
+	let req = format!(r#"{{"jsonrpc":"2.0","id":1,"method":"{}"}}"#, method);
+	request(server,
+		&format!("\
+			POST {} HTTP/1.1\r\n\
+			Host: {}\r\n\
+			Connection: close\r\n\
+			Content-Type: application/json\r\n\
+			Content-Length: {}\r\n\
+			\r\n\
+			{}\r\n\
+		", path, host, req.as_bytes().len(), req)
+	)
+}
+
+#[test]
+fn should_serve_different_handlers_on_different_paths() {
+	// when
+	let public = endpoint_request(serve_endpoints(), "/public", "127.0.0.1:8080", "hello");
+	let admin = endpoint_request(serve_endpoints(), "/admin/", "admin.local", "shutdown");
+	let wrong_handler = endpoint_request(serve_endpoints(), "/public", "127.0.0.1:8080", "shutdown");
+
+	// then
+	assert_eq!(public.status, "HTTP/1.1 200 OK".to_owned());
+	assert_eq!(public.body, world());
+	assert_eq!(admin.status, "HTTP/1.1 200 OK".to_owned());
+	assert_eq!(admin.body, "27\n{\"jsonrpc\":\"2.0\",\"result\":true,\"id\":1}\n".to_owned());
+	assert_eq!(wrong_handler.body, method_not_found());
+}
+
+#[test]
+fn should_validate_hosts_per_endpoint() {
+	// when
+	let public = endpoint_request(serve_endpoints(), "/public", "parity.io", "hello");
+	let admin = endpoint_request(serve_endpoints(), "/admin", "parity.io", "shutdown");
+
+	// then
+	assert_eq!(public.status, "HTTP/1.1 200 OK".to_owned());
+	assert_eq!(admin.status, "HTTP/1.1 403 Forbidden".to_owned());
+}
+
+#[test]
+fn should_return_not_found_for_paths_without_endpoint() {
+	// when
+	let root = endpoint_request(serve_endpoints(), "/", "127.0.0.1:8080", "hello");
+	let prefix = endpoint_request(serve_endpoints(), "/administrator", "admin.local", "shutdown");
+
+	// then
+	assert_eq!(root.status, "HTTP/1.1 404 Not Found".to_owned());
+	assert_eq!(prefix.status, "HTTP/1.1 404 Not Found".to_owned());
+}
+
+fn serve_health(synced: bool) -> Server {
+	let mut io = IoHandler::default();
+	io.add_method("is_synced", move |_params: Params| Ok(Value::Bool(synced)));
+	io.add_method("broken", |_params: Params| Err(Error::internal_error()));
+	io.add_method_with_raw_result("is_synced_raw", move |_params: Params, _meta| {
+		self::jsonrpc_core::types::RawValue::from_string(synced.to_string())
+			.map_err(|_| Error::internal_error())
+	});
+
+	ServerBuilder::new(io)
+		.allowed_hosts(DomainsValidation::AllowOnly(vec![]))
+		.health_api(HealthApi::new("/health"))
+		.health_api(HealthApi::new("/ready").method("is_synced"))
+		.health_api(HealthApi::new("/broken").method("broken"))
+		.health_api(HealthApi::new("/ready_raw").method("is_synced_raw"))
+		.start_http(&"127.0.0.1:0".parse().unwrap())
+		.unwrap()
+}
+
+fn probe(server: Server, path: &str) -> Response {
+	request(server,
+		&format!("\
+			GET {} HTTP/1.1\r\n\
+			Host: orchestrator.local\r\n\
+			Connection: close\r\n\
+			\r\n\
+		", path)
+	)
+}
+
+#[test]
+fn should_respond_to_health_probe_without_validating_host() {
+	// when
+	let response = probe(serve_health(false), "/health");
+
+	// then
+	assert_eq!(response.status, "HTTP/1.1 200 OK".to_owned());
+	assert_eq!(response.body, "5\ntrue\n".to_owned());
+}
+
+#[test]
+fn should_map_result_of_readiness_method_to_status() {
+	// when
+	let ready = probe(serve_health(true), "/ready");
+	let not_ready = probe(serve_health(false), "/ready");
+	let broken = probe(serve_health(true), "/broken");
+
+	// then
+	assert_eq!(ready.status, "HTTP/1.1 200 OK".to_owned());
+	assert_eq!(ready.body, "5\ntrue\n".to_owned());
+	assert_eq!(not_ready.status, "HTTP/1.1 503 Service Unavailable".to_owned());
+	assert_eq!(not_ready.body, "6\nfalse\n".to_owned());
+	assert_eq!(broken.status, "HTTP/1.1 503 Service Unavailable".to_owned());
+	assert_eq!(broken.body, "2B\n{\"code\":-32603,\"message\":\"Internal error\"}\n".to_owned());
+}
+
+#[test]
+fn should_map_raw_result_of_readiness_method_to_status() {
+	// when
+	let ready = probe(serve_health(true), "/ready_raw");
+	let not_ready = probe(serve_health(false), "/ready_raw");
+
+	// then
+	assert_eq!(ready.status, "HTTP/1.1 200 OK".to_owned());
+	assert_eq!(ready.body, "5\ntrue\n".to_owned());
+	assert_eq!(not_ready.status, "HTTP/1.1 503 Service Unavailable".to_owned());
+	assert_eq!(not_ready.body, "6\nfalse\n".to_owned());
+}
+
+#[test]
+fn should_validate_host_of_other_requests_when_health_api_is_enabled() {
+	// when
+	let response = probe(serve_health(true), "/other");
+
+	// then
+	assert_eq!(response.status, "HTTP/1.1 403 Forbidden".to_owned());
+}
+
+/// JSON prefixed with a marker, to tell encoded responses apart.
+#[derive(Debug)]
+struct PrefixedJson;
+
+impl jsonrpc_core::Encoding for PrefixedJson {
+	fn content_type(&self) -> &'static str {
+		"application/x-prefixed-json"
+	}
+
+	fn decode(&self, request: &[u8]) -> Result<jsonrpc_core::Request, Error> {
+		jsonrpc_core::encoding::Json.decode(&request[b"json:".len()..])
+	}
+
+	fn encode(&self, response: &jsonrpc_core::Response) -> Vec<u8> {
+		let mut encoded = b"json:".to_vec();
+		encoded.extend(jsonrpc_core::encoding::Json.encode(response));
+		encoded.push(b'\n');
+		encoded
+	}
+
+	fn decode_message(&self, message: &[u8]) -> Result<Value, Error> {
+		jsonrpc_core::encoding::Json.decode_message(&message[b"json:".len()..])
+	}
+
+	fn encode_message(&self, message: &Value) -> Vec<u8> {
+		let mut encoded = b"json:".to_vec();
+		encoded.extend(jsonrpc_core::encoding::Json.encode_message(message));
+		encoded.push(b'\n');
+		encoded
+	}
+}
+
+#[test]
+fn should_negotiate_encoding_by_content_type() {
+	use self::jsonrpc_core::Encoding;
+
+	// given
+	let mut io = IoHandler::default();
+	io.add_method("hello", |_| Ok(Value::String("world".into())));
+	let server = ServerBuilder::new(io)
+		.encoding(PrefixedJson)
+		.start_http(&"127.0.0.1:0".parse().unwrap())
+		.unwrap();
+	let addr = server.address().clone();
+
+	// when
+	let req = r#"json:{"jsonrpc":"2.0","id":1,"method":"hello"}"#;
+	let response = request(server,
+		&format!("\
+			POST / HTTP/1.1\r\n\
+			Host: localhost:{}\r\n\
+			Connection: close\r\n\
+			Content-Type: application/x-prefixed-json\r\n\
+			Content-Length: {}\r\n\
+			\r\n\
+			{}\r\n\
+		", addr.port(), req.as_bytes().len(), req)
+	);
+
+	// then
+	assert_eq!(response.status, "HTTP/1.1 200 OK".to_owned());
+	assert!(response.headers.contains("Content-Type: application/x-prefixed-json"), "{}", response.headers);
+	assert_eq!(response.body, "2F\njson:{\"jsonrpc\":\"2.0\",\"result\":\"world\",\"id\":1}\n".to_owned());
+}
+
+fn invalid_host() -> String {
+	"29\nProvided Host header is not whitelisted.\n".into()
+}
+
+fn cors_invalid() -> String {
+	"76\nOrigin of the request is not whitelisted. CORS headers would not be sent and any side-effects were cancelled as well.\n".into()
+}
+
+fn method_not_found() -> String {
+ "4E\n{\"jsonrpc\":\"2.0\",\"error\":{\"code\":-32601,\"message\":\"Method not found\"},\"id\":1}\n".into()
+}
+
+fn invalid_request() -> String {
+ "50\n{\"jsonrpc\":\"2.0\",\"error\":{\"code\":-32600,\"message\":\"Invalid request\"},\"id\":null}\n".into()
+}
+fn world() -> String {
+ "2A\n{\"jsonrpc\":\"2.0\",\"result\":\"world\",\"id\":1}\n".into()
+}
+fn world_5() -> String {
+ "2D\n{\"jsonrpc\":\"2.0\",\"result\":\"world: 5\",\"id\":1}\n".into()
+}
+fn world_batch() -> String {
+ "2C\n[{\"jsonrpc\":\"2.0\",\"result\":\"world\",\"id\":1}]\n".into()
+}
+
+#[test]
+fn should_stream_results_as_chunked_array() {
+	use self::jsonrpc_core::futures::stream;
+
+	// given
+	let mut io = IoHandler::default();
+	io.add_streaming_method("logs", |_params, _meta| stream::iter_ok((0..2).map(Value::from)));
+	let server = ServerBuilder::new(io)
+		.start_http(&"127.0.0.1:0".parse().unwrap())
+		.unwrap();
+	let addr = server.address().clone();
+
+	// when
+	let req = r#"{"jsonrpc":"2.0","id":1,"method":"logs"}"#;
+	let mut stream = TcpStream::connect(addr).unwrap();
+	stream.write_all(format!("\
+		POST / HTTP/1.1\r\n\
+		Host: localhost:{}\r\n\
+		Connection: close\r\n\
+		Content-Type: application/json\r\n\
+		Content-Length: {}\r\n\
+		\r\n\
+		{}\r\n\
+	", addr.port(), req.as_bytes().len(), req).as_bytes()).unwrap();
+	let mut response = String::new();
+	stream.read_to_string(&mut response).unwrap();
+
+	// then
+	let split = response.find("\r\n\r\n").unwrap();
+	let (headers, mut chunks) = (&response[..split], &response[split + 4..]);
+	let mut body = String::new();
+	loop {
+		let size_end = chunks.find("\r\n").unwrap();
+		let size = usize::from_str_radix(&chunks[..size_end], 16).unwrap();
+		if size == 0 {
+			break;
+		}
+		body.push_str(&chunks[size_end + 2..size_end + 2 + size]);
+		chunks = &chunks[size_end + 4 + size..];
+	}
+	assert!(headers.contains("Transfer-Encoding: chunked"), "{}", headers);
+	assert_eq!(body, concat!(
+		r#"[{"jsonrpc":"2.0","method":"rpc.chunk","params":{"id":1,"result":0}},"#,
+		r#"{"jsonrpc":"2.0","method":"rpc.chunk","params":{"id":1,"result":1}},"#,
+		r#"{"jsonrpc":"2.0","result":null,"id":1}]"#,
+		"\n",
+	));
+}
+
+#[test]
+fn should_send_streamed_results_at_once_without_event_loop() {
+	use hyper::server::Service;
+	use self::jsonrpc_core::futures::stream;
+
+	// given
+	let mut io = IoHandler::default();
+	io.add_streaming_method("logs", |_params, _meta| stream::iter_ok((0..2).map(Value::from)));
+	let rpc = Rpc { handler: Arc::new(io.into()), extractor: Arc::new(NoopExtractor) };
+	let handler = ServerHandler::new(rpc, None, None, Arc::new(NoopRequestMiddleware), RestApi::Disabled, 1024);
+
+	// when
+	let mut request = hyper::server::Request::new(hyper::Method::Post, "/".parse().unwrap());
+	request.headers_mut().set(hyper::header::ContentType::json());
+	request.set_body(r#"{"jsonrpc":"2.0","id":1,"method":"logs"}"#);
+	let response = handler.call(request).wait().unwrap();
+
+	// then
+	assert_eq!(response.status(), hyper::StatusCode::Ok);
+	let body = response.body().concat2().wait().unwrap();
+	assert_eq!(::std::str::from_utf8(&body).unwrap(), concat!(
+		r#"[{"jsonrpc":"2.0","method":"rpc.chunk","params":{"id":1,"result":0}},"#,
+		r#"{"jsonrpc":"2.0","method":"rpc.chunk","params":{"id":1,"result":1}},"#,
+		r#"{"jsonrpc":"2.0","result":null,"id":1}]"#,
+		"\n",
+	));
+}
+
+#[test]
+fn should_return_service_unavailable_when_server_is_busy() {
+	// given
+	let mut io = IoHandler::default();
+	io.add_method("hello", |_params| Ok(Value::String("world".into())));
+	io.set_concurrency_limit("hello", 0);
+	io.set_retry_after(::std::time::Duration::from_secs(5));
+	let server = ServerBuilder::new(io)
+		.start_http(&"127.0.0.1:0".parse().unwrap())
+		.unwrap();
+
+	// when
+	let req = r#"{"jsonrpc":"2.0","id":1,"method":"hello"}"#;
+	let response = request(server,
+		&format!("\
+			POST / HTTP/1.1\r\n\
+			Host: 127.0.0.1:8080\r\n\
+			Connection: close\r\n\
+			Content-Type: application/json\r\n\
+			Content-Length: {}\r\n\
+			\r\n\
+			{}\r\n\
+		", req.as_bytes().len(), req)
+	);
+
+	// then
+	assert_eq!(response.status, "HTTP/1.1 503 Service Unavailable".to_owned());
+	assert!(response.headers.contains("Retry-After: 5"), "{}", response.headers);
+	assert_eq!(response.body, "75\n{\"jsonrpc\":\"2.0\",\"error\":{\"code\":-32005,\"message\":\"Server is busy, try again later\",\"data\":{\"retryAfter\":5}},\"id\":1}\n".to_owned());
+}
+
+fn compressed_request(server: Server, accept_encoding: &str, content_encoding: &str, body: &[u8]) -> (String, Vec<u8>) {
+	let mut req = TcpStream::connect(server.address()).unwrap();
+	req.write_all(format!("\
+		POST / HTTP/1.1\r\n\
+		Host: 127.0.0.1:8080\r\n\
+		Connection: close\r\n\
+		Content-Type: application/json\r\n\
+		Accept-Encoding: {}\r\n\
+		Content-Encoding: {}\r\n\
+		Content-Length: {}\r\n\
+		\r\n\
+	", accept_encoding, content_encoding, body.len()).as_bytes()).unwrap();
+	req.write_all(body).unwrap();
+
+	let mut response = Vec::new();
+	req.read_to_end(&mut response).unwrap();
+	let headers_end = response.windows(4).position(|window| window == b"\r\n\r\n").unwrap();
+	let body = response.split_off(headers_end + 4);
+	(String::from_utf8(response).unwrap(), body)
+}
+
+fn gzip(data: &[u8]) -> Vec<u8> {
+	let mut encoder = ::flate2::write::GzEncoder::new(Vec::new(), ::flate2::Compression::default());
+	encoder.write_all(data).unwrap();
+	encoder.finish().unwrap()
+}
+
+fn serve_compressed(compression: Compression) -> Server {
+	let mut io = IoHandler::default();
+	io.add_method("hello", |params: Params| {
+		let (len, ) = params.parse::<(usize, )>()?;
+		Ok(Value::String(::std::iter::repeat('a').take(len).collect()))
+	});
+
+	ServerBuilder::new(io)
+		.compression(compression)
+		.max_request_body_size(1024)
+		.start_http(&"127.0.0.1:0".parse().unwrap())
+		.unwrap()
+}
+
+#[test]
+fn should_compress_response_with_accepted_coding() {
+	// given
+	let server = serve_compressed(Compression::new(vec![ContentCoding::Brotli, ContentCoding::Gzip]));
+
+	// when
+	let req = r#"{"jsonrpc":"2.0","id":1,"method":"hello","params":[2048]}"#;
+	let (headers, body) = compressed_request(server, "deflate, gzip", "identity", req.as_bytes());
+
+	// then
+	assert!(headers.starts_with("HTTP/1.1 200 OK"), "{}", headers);
+	assert!(headers.contains("Content-Encoding: gzip"), "{}", headers);
+	assert!(headers.contains("Vary: accept-encoding"), "{}", headers);
+	let mut response = String::new();
+	::flate2::read::GzDecoder::new(&*body).read_to_string(&mut response).unwrap();
+	assert_eq!(response, format!("{{\"jsonrpc\":\"2.0\",\"result\":\"{}\",\"id\":1}}\n", "a".repeat(2048)));
+}
+
+#[test]
+fn should_not_compress_small_responses() {
+	// given
+	let server = serve_compressed(Compression::new(vec![ContentCoding::Gzip]).min_size(100));
+
+	// when
+	let req = r#"{"jsonrpc":"2.0","id":1,"method":"hello","params":[10]}"#;
+	let (headers, body) = compressed_request(server, "gzip", "identity", req.as_bytes());
+
+	// then
+	assert!(headers.starts_with("HTTP/1.1 200 OK"), "{}", headers);
+	assert!(!headers.contains("Content-Encoding"), "{}", headers);
+	assert!(headers.contains("Vary: accept-encoding"), "{}", headers);
+	assert_eq!(String::from_utf8(body).unwrap(), "2F\r\n{\"jsonrpc\":\"2.0\",\"result\":\"aaaaaaaaaa\",\"id\":1}\n\r\n0\r\n\r\n");
+}
+
+#[test]
+fn should_compress_encoded_response() {
+	// given
+	let mut io = IoHandler::default();
+	io.add_method("hello", |_| Ok(Value::String("a".repeat(2048))));
+	let server = ServerBuilder::new(io)
+		.encoding(PrefixedJson)
+		.compression(Compression::new(vec![ContentCoding::Gzip]))
+		.start_http(&"127.0.0.1:0".parse().unwrap())
+		.unwrap();
+
+	// when
+	let req = r#"json:{"jsonrpc":"2.0","id":1,"method":"hello"}"#;
+	let mut stream = TcpStream::connect(server.address()).unwrap();
+	stream.write_all(format!("\
+		POST / HTTP/1.1\r\n\
+		Host: 127.0.0.1:8080\r\n\
+		Connection: close\r\n\
+		Content-Type: application/x-prefixed-json\r\n\
+		Accept-Encoding: gzip\r\n\
+		Content-Length: {}\r\n\
+		\r\n\
+		{}\
+	", req.len(), req).as_bytes()).unwrap();
+	let mut response = Vec::new();
+	stream.read_to_end(&mut response).unwrap();
+	let headers_end = response.windows(4).position(|window| window == b"\r\n\r\n").unwrap();
+	let body = response.split_off(headers_end + 4);
+	let headers = String::from_utf8(response).unwrap();
+
+	// then
+	assert!(headers.starts_with("HTTP/1.1 200 OK"), "{}", headers);
+	assert!(headers.contains("Content-Type: application/x-prefixed-json"), "{}", headers);
+	assert!(headers.contains("Content-Encoding: gzip"), "{}", headers);
+	let mut response = String::new();
+	::flate2::read::GzDecoder::new(&*body).read_to_string(&mut response).unwrap();
+	assert_eq!(response, format!("json:{{\"jsonrpc\":\"2.0\",\"result\":\"{}\",\"id\":1}}\n", "a".repeat(2048)));
+}
+
+#[test]
+fn should_accept_compressed_request() {
+	// given
+	let server = serve_compressed(Compression::disabled());
+
+	// when
+	let req = r#"{"jsonrpc":"2.0","id":1,"method":"hello","params":[3]}"#;
+	let (headers, body) = compressed_request(server, "gzip", "gzip", &gzip(req.as_bytes()));
+
+	// then
+	assert!(headers.starts_with("HTTP/1.1 200 OK"), "{}", headers);
+	assert!(!headers.contains("Content-Encoding"), "{}", headers);
+	assert_eq!(String::from_utf8(body).unwrap(), "28\r\n{\"jsonrpc\":\"2.0\",\"result\":\"aaa\",\"id\":1}\n\r\n0\r\n\r\n");
+}
+
+#[test]
+fn should_limit_size_of_decompressed_request() {
+	// given
+	let server = serve_compressed(Compression::disabled());
+
+	// when
+	let req = format!(r#"{{"jsonrpc":"2.0","id":1,"method":"hello","params":[3],"padding":"{}"}}"#, "a".repeat(2048));
+	let (headers, _) = compressed_request(server, "gzip", "gzip", &gzip(req.as_bytes()));
+
+	// then
+	assert!(headers.starts_with("HTTP/1.1 413 Payload Too Large"), "{}", headers);
+}
+
+#[test]
+fn should_reject_unsupported_content_encoding() {
+	// given
+	let server = serve_compressed(Compression::disabled());
+
+	// when
+	let (headers, _) = compressed_request(server, "gzip", "compress", b"{}");
+
+	// then
+	assert!(headers.starts_with("HTTP/1.1 415 Unsupported Media Type"), "{}", headers);
+}
+
+fn http2_request(server: &Server, authority: &str, body: &str) -> (u16, String) {
+	use bytes::Bytes;
+	use server_utils::tokio_core::net::TcpStream;
+	use server_utils::tokio_core::reactor::Core;
+	use self::jsonrpc_core::futures::Stream;
+
+	let mut core = Core::new().unwrap();
+	let handle = core.handle();
+	let body = Bytes::from(body.to_owned());
+	let response = TcpStream::connect(server.address(), &handle)
+		.map_err(h2::Error::from)
+		.and_then(|socket| h2::client::handshake(socket))
+		.and_then(move |(client, connection)| {
+			handle.spawn(connection.map_err(|_| ()));
+			client.ready()
+		})
+		.and_then(move |mut client| {
+			let request = http::Request::post(format!("http://{}/", authority))
+				.header("content-type", "application/json")
+				.body(())
+				.unwrap();
+			let (response, mut stream) = client.send_request(request, false).unwrap();
+			stream.send_data(body, true).unwrap();
+			response
+		})
+		.and_then(|response| {
+			let status = response.status().as_u16();
+			let mut body = response.into_body();
+			let mut release = body.release_capacity().clone();
+			body.map(move |chunk| {
+				let _ = release.release_capacity(chunk.len());
+				chunk
+			}).concat2().map(move |body| (status, String::from_utf8(body.to_vec()).unwrap()))
+		});
+
+	core.run(response).unwrap()
+}
+
+#[test]
+fn should_handle_http2_requests_with_prior_knowledge() {
+	// given
+	let mut io = IoHandler::default();
+	io.add_method("hello", |_params: Params| Ok(Value::String("world".into())));
+	let server = ServerBuilder::new(io)
+		.http2(true)
+		.start_http(&"127.0.0.1:0".parse().unwrap())
+		.unwrap();
+	let authority = format!("127.0.0.1:{}", server.address().port());
+
+	// when
+	let (status, body) = http2_request(&server, &authority, r#"{"jsonrpc":"2.0","id":1,"method":"hello"}"#);
+
+	// then
+	assert_eq!(status, 200);
+	assert_eq!(body, "{\"jsonrpc\":\"2.0\",\"result\":\"world\",\"id\":1}\n".to_owned());
+}
+
+#[test]
+fn should_send_http2_responses_larger_than_the_window() {
+	// given
+	let mut io = IoHandler::default();
+	io.add_method("hello", |_params: Params| Ok(Value::String(::std::iter::repeat('a').take(200_000).collect())));
+	let server = ServerBuilder::new(io)
+		.http2(true)
+		.start_http(&"127.0.0.1:0".parse().unwrap())
+		.unwrap();
+	let authority = format!("127.0.0.1:{}", server.address().port());
+
+	// when
+	let (status, body) = http2_request(&server, &authority, r#"{"jsonrpc":"2.0","id":1,"method":"hello"}"#);
+
+	// then
+	assert_eq!(status, 200);
+	assert_eq!(body.len(), 200_000 + r#"{"jsonrpc":"2.0","result":"","id":1}"#.len() + 1);
+}
+
+#[test]
+fn should_validate_host_of_http2_requests() {
+	// given
+	let server = ServerBuilder::new(IoHandler::default())
+		.http2(true)
+		.allowed_hosts(DomainsValidation::AllowOnly(vec![]))
+		.start_http(&"127.0.0.1:0".parse().unwrap())
+		.unwrap();
+
+	// when
+	let (status, _) = http2_request(&server, "evil.com", r#"{"jsonrpc":"2.0","id":1,"method":"hello"}"#);
+
+	// then
+	assert_eq!(status, 403);
+}
+
+#[test]
+fn should_still_handle_http1_requests_when_http2_is_enabled() {
+	// given
+	let server = ServerBuilder::new(IoHandler::default())
+		.http2(true)
+		.start_http(&"127.0.0.1:0".parse().unwrap())
+		.unwrap();
+
+	// when
+	let req = r#"{"jsonrpc":"2.0","id":1,"method":"x"}"#;
+	let response = request(server,
+		&format!("\
+			POST / HTTP/1.1\r\n\
+			Host: 127.0.0.1:8080\r\n\
+			Connection: close\r\n\
+			Content-Type: application/json\r\n\
+			Content-Length: {}\r\n\
+			\r\n\
+			{}\r\n\
+		", req.as_bytes().len(), req)
+	);
+
+	// then
+	assert_eq!(response.status, "HTTP/1.1 200 OK".to_owned());
+	assert_eq!(response.body, method_not_found());
+}
+
+#[cfg(feature = "sse")]
+mod sse {
+	use std::io::{Read, Write};
+	use std::net::TcpStream;
+	use std::sync::Arc;
+	use super::jsonrpc_core::{MetaIoHandler, Params, Value};
+	use super::jsonrpc_core::futures::{self, Future};
+	use super::super::*;
+
+	struct SessionExtractor;
+	impl MetaExtractor<Option<Arc<pubsub::Session>>> for SessionExtractor {
+		fn read_metadata(&self, _: &server::Request) -> Option<Arc<pubsub::Session>> {
+			None
+		}
+
+		fn read_metadata_with_session(
+			&self,
+			_: &server::Request,
+			_: &[Vec<u8>],
+			session: Arc<pubsub::Session>,
+		) -> Option<Arc<pubsub::Session>> {
+			Some(session)
+		}
+	}
+
+	fn serve_event_stream() -> Server {
+		use std::thread;
+		let mut io = pubsub::PubSubHandler::new(MetaIoHandler::default());
+		io.add_subscription(
+			"hello",
+			("hello_subscribe", |_params: Params, _meta, subscriber: pubsub::Subscriber| {
+				let sink = subscriber.assign_id(pubsub::SubscriptionId::Number(5)).unwrap();
+				thread::spawn(move || {
+					sink.notify(Params::Array(vec![Value::Number(10.into())])).wait().unwrap();
+				});
+			}),
+			("hello_unsubscribe", |_id: pubsub::SubscriptionId| {
+				futures::future::ok(Value::Bool(true))
+			}),
+		);
+
+		ServerBuilder::with_meta_extractor(io, SessionExtractor)
+			.event_stream(EventStream::new("/events").keep_alive(::std::time::Duration::from_millis(50)))
+			.start_http(&"127.0.0.1:0".parse().unwrap())
+			.unwrap()
+	}
+
+	// Reads from the stream until the response contains given text.
+	fn read_until(stream: &mut TcpStream, response: &mut String, text: &str) {
+		let mut buf = [0u8; 1024];
+		while !response.contains(text) {
+			let read = stream.read(&mut buf).expect("Event not received in time");
+			assert!(read > 0, "Stream closed: {}", response);
+			response.push_str(::std::str::from_utf8(&buf[..read]).unwrap());
+		}
+	}
+
+	fn open_event_stream(server: &Server) -> (TcpStream, String) {
+		let mut stream = TcpStream::connect(server.address()).unwrap();
+		stream.set_read_timeout(Some(::std::time::Duration::from_secs(5))).unwrap();
+		stream.write_all(b"\
+			GET /events HTTP/1.1\r\n\
+			Host: 127.0.0.1:8080\r\n\
+			Accept: text/event-stream\r\n\
+			\r\n\
+		").unwrap();
+
+		let mut response = String::new();
+		read_until(&mut stream, &mut response, "event: session\ndata: ");
+		read_until(&mut stream, &mut response, "\n\n");
+		let id = response.split("data: ").nth(1).unwrap().lines().next().unwrap().to_owned();
+		assert!(response.starts_with("HTTP/1.1 200 OK"), "{}", response);
+		assert!(response.contains("Content-Type: text/event-stream"), "{}", response);
+		(stream, id)
+	}
+
+	fn call_in_session(server: &Server, session: &str, body: &str) -> String {
+		let mut req = TcpStream::connect(server.address()).unwrap();
+		req.write_all(format!("\
+			POST / HTTP/1.1\r\n\
+			Host: 127.0.0.1:8080\r\n\
+			Connection: close\r\n\
+			Content-Type: application/json\r\n\
+			X-Session-Id: {}\r\n\
+			Content-Length: {}\r\n\
+			\r\n\
+			{}\r\n\
+		", session, body.len(), body).as_bytes()).unwrap();
+
+		let mut response = String::new();
+		req.read_to_string(&mut response).unwrap();
+		response
+	}
+
+	#[test]
+	fn should_send_notifications_of_subscriptions_made_in_session_as_events() {
+		// given
+		let server = serve_event_stream();
+		let (mut stream, session) = open_event_stream(&server);
+
+		// when
+		let response = call_in_session(&server, &session, r#"{"jsonrpc":"2.0","id":1,"method":"hello_subscribe","params":[]}"#);
+
+		// then
+		assert!(response.contains(r#"{"jsonrpc":"2.0","result":5,"id":1}"#), "{}", response);
+		let mut events = String::new();
+		read_until(&mut stream, &mut events, "\n\n");
+		assert!(events.contains(r#"data: {"jsonrpc":"2.0","method":"hello","params":[10]}"#), "{}", events);
+	}
+
+	#[test]
+	fn should_reject_calls_referencing_unknown_session() {
+		// given
+		let server = serve_event_stream();
+
+		// when
+		let response = call_in_session(&server, "unknown", r#"{"jsonrpc":"2.0","id":1,"method":"hello_subscribe","params":[]}"#);
+
+		// then
+		assert!(response.starts_with("HTTP/1.1 400 Bad Request"), "{}", response);
+		assert!(response.contains("Unknown session: unknown"), "{}", response);
+	}
+
+	#[test]
+	fn should_drop_session_when_client_disconnects() {
+		// given
+		let server = serve_event_stream();
+		let (stream, session) = open_event_stream(&server);
+		let response = call_in_session(&server, &session, r#"{"jsonrpc":"2.0","id":1,"method":"hello","params":[]}"#);
+		assert!(response.starts_with("HTTP/1.1 200 OK"), "{}", response);
+
+		// when
+		drop(stream);
+		::std::thread::sleep(::std::time::Duration::from_millis(500));
+		let response = call_in_session(&server, &session, r#"{"jsonrpc":"2.0","id":1,"method":"hello","params":[]}"#);
+
+		// then
+		assert!(response.starts_with("HTTP/1.1 400 Bad Request"), "{}", response);
+	}
+}
+
+fn serve_cors_policy(policy: CorsPolicy) -> Server {
+	ServerBuilder::new(IoHandler::default())
+		.cors(DomainsValidation::AllowOnly(vec![AccessControlAllowOrigin::Value("parity.io".into())]))
+		.cors_policy(policy)
+		.start_http(&"127.0.0.1:0".parse().unwrap())
+		.unwrap()
+}
+
+fn preflight(server: Server) -> Response {
+	request(server,
+		"\
+			OPTIONS / HTTP/1.1\r\n\
+			Host: 127.0.0.1:8080\r\n\
+			Origin: http://parity.io\r\n\
+			Access-Control-Request-Method: POST\r\n\
+			Access-Control-Request-Headers: content-type, authorization\r\n\
+			Connection: close\r\n\
+			Content-Length: 0\r\n\
+			\r\n\
+		"
+	)
+}
+
+#[test]
+fn should_add_headers_of_cors_policy() {
+	// given
+	let server = serve_cors_policy(CorsPolicy::default()
+		.allow_headers(vec!["Authorization"])
+		.max_age(600)
+		.allow_credentials(true)
+	);
+
+	// when
+	let response = preflight(server);
+
+	// then
+	assert_eq!(response.status, "HTTP/1.1 200 OK".to_owned());
+	assert!(response.headers.contains("Access-Control-Allow-Origin: http://parity.io"), "Headers missing in {}", response.headers);
+	assert!(response.headers.contains("Access-Control-Allow-Headers: Origin, Content-Type, Accept, Authorization"), "Headers missing in {}", response.headers);
+	assert!(response.headers.contains("Access-Control-Max-Age: 600"), "Headers missing in {}", response.headers);
+	assert!(response.headers.contains("Access-Control-Allow-Credentials: true"), "Headers missing in {}", response.headers);
+}
+
+#[test]
+fn should_echo_requested_headers_if_any_headers_are_allowed() {
+	// given
+	let server = serve_cors_policy(CorsPolicy::default().allow_any_headers());
+
+	// when
+	let response = preflight(server);
+
+	// then
+	assert_eq!(response.status, "HTTP/1.1 200 OK".to_owned());
+	assert!(response.headers.contains("Access-Control-Allow-Headers: content-type, authorization"), "Headers missing in {}", response.headers);
+	assert!(response.headers.contains("Vary: origin, access-control-request-headers"), "Headers missing in {}", response.headers);
+	assert!(!response.headers.contains("Access-Control-Allow-Credentials"), "Unexpected header in {}", response.headers);
+}
+
+#[cfg(feature = "tls")]
+mod tls {
+	extern crate rustls;
+	extern crate webpki;
+
+	use std::fs::File;
+	use std::io::{BufReader, Read, Write};
+	use std::net::{SocketAddr, TcpStream};
+	use std::sync::Arc;
+	use self::rustls::internal::pemfile;
+	use super::jsonrpc_core::{MetaIoHandler, Metadata, Value};
+	use super::super::*;
+
+	// Certificates for `localhost` signed by test CA, valid for 100 years.
+	fn cert(name: &str) -> String {
+		format!("{}/tests/certs/{}", env!("CARGO_MANIFEST_DIR"), name)
+	}
+
+	fn tls_request(addr: &SocketAddr, client_certificate: bool) -> String {
+		let mut config = rustls::ClientConfig::new();
+		config.root_store.add_pem_file(&mut BufReader::new(File::open(cert("ca.pem")).unwrap())).unwrap();
+		if client_certificate {
+			let certificates = pemfile::certs(&mut BufReader::new(File::open(cert("client.pem")).unwrap())).unwrap();
+			let key = pemfile::pkcs8_private_keys(&mut BufReader::new(File::open(cert("client.key")).unwrap())).unwrap().remove(0);
+			let _ = config.set_single_client_cert(certificates, key);
+		}
+		let name = webpki::DNSNameRef::try_from_ascii_str("localhost").unwrap();
+		let mut session = rustls::ClientSession::new(&Arc::new(config), name);
+		let mut socket = TcpStream::connect(addr).unwrap();
+		let mut stream = rustls::Stream::new(&mut session, &mut socket);
+
+		let req = r#"{"jsonrpc":"2.0","id":1,"method":"hello"}"#;
+		let _ = stream.write_all(format!("\
+			POST / HTTP/1.1\r\n\
+			Host: localhost:{}\r\n\
+			Connection: close\r\n\
+			Content-Type: application/json\r\n\
+			Content-Length: {}\r\n\
+			\r\n\
+			{}\r\n\
+		", addr.port(), req.as_bytes().len(), req).as_bytes());
+		let mut response = String::new();
+		// The server might close the connection without `close_notify`.
+		let _ = stream.read_to_string(&mut response);
+		response
+	}
+
+	#[derive(Clone, Default)]
+	struct Meta(usize);
+	impl Metadata for Meta {}
+
+	struct CertificatesExtractor;
+	impl MetaExtractor<Meta> for CertificatesExtractor {
+		fn read_metadata(&self, _: &hyper::server::Request) -> Meta {
+			Meta(0)
+		}
+
+		fn read_metadata_with_certificates(&self, _: &hyper::server::Request, certificates: &[Vec<u8>]) -> Meta {
+			Meta(certificates.len())
+		}
+	}
+
+	fn serve(config: TlsConfig) -> Server {
+		let mut io = MetaIoHandler::default();
+		io.add_method_with_meta("hello", |_params, meta: Meta| Ok(Value::from(meta.0)));
+		ServerBuilder::with_meta_extractor(io, CertificatesExtractor)
+			.tls(config)
+			.start_http(&"127.0.0.1:0".parse().unwrap())
+			.unwrap()
+	}
+
+	#[test]
+	fn should_serve_requests_over_tls() {
+		// given
+		let server = serve(TlsConfig::from_pem_files(cert("server.pem"), cert("server.key")).unwrap());
+
+		// when
+		let response = tls_request(server.address(), false);
+
+		// then
+		assert!(response.starts_with("HTTP/1.1 200 OK"), "{}", response);
+		assert!(response.contains(r#"{"jsonrpc":"2.0","result":0,"id":1}"#), "{}", response);
+	}
+
+	#[test]
+	fn should_pass_client_certificates_to_meta_extractor() {
+		// given
+		let config = TlsConfig::from_pem_files(cert("server.pem"), cert("server.key")).unwrap()
+			.client_auth(cert("ca.pem"), true)
+			.unwrap();
+		let server = serve(config);
+
+		// when
+		let authenticated = tls_request(server.address(), true);
+		let anonymous = tls_request(server.address(), false);
+
+		// then
+		assert!(authenticated.contains(r#"{"jsonrpc":"2.0","result":1,"id":1}"#), "{}", authenticated);
+		assert!(!anonymous.contains("HTTP/1.1"), "{}", anonymous);
+	}
+
+	#[test]
+	fn should_reload_certificates() {
+		// given
+		let config = TlsConfig::from_pem_files(cert("server.pem"), cert("server.key")).unwrap();
+		let server = serve(config.clone());
+
+		// when
+		config.reload().unwrap();
+		let response = tls_request(server.address(), false);
+
+		// then
+		assert!(response.contains(r#"{"jsonrpc":"2.0","result":0,"id":1}"#), "{}", response);
+		assert!(TlsConfig::from_pem_files(cert("missing.pem"), cert("server.key")).is_err());
+		assert!(TlsConfig::from_pem_files(cert("server.pem"), cert("ca.pem")).is_err());
+	}
 }
@@ -0,0 +1,175 @@
+//! HTTPS support (enabled with `tls` feature).
+
+use std::fs::File;
+use std::io::{self, BufReader};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::{atomic, Arc, RwLock};
+
+use hyper::server;
+use rustls::{self, ServerConfig, Session};
+use rustls::internal::pemfile;
+use tokio_rustls::ServerConfigExt;
+
+use jsonrpc::{Metadata, Middleware};
+use jsonrpc::futures::Future;
+use server_utils::tokio_core::net::TcpStream;
+use server_utils::tokio_core::reactor::Handle;
+use handler::ServerHandler;
+use http2;
+
+#[derive(Debug, Clone)]
+struct Files {
+	certificates: PathBuf,
+	private_key: PathBuf,
+	client_ca: Option<(PathBuf, bool)>,
+}
+
+fn invalid_data<T: Into<String>>(message: T) -> io::Error {
+	io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+fn open(path: &Path) -> io::Result<BufReader<File>> {
+	File::open(path).map(BufReader::new)
+}
+
+impl Files {
+	fn load(&self, http2: bool) -> io::Result<ServerConfig> {
+		let certificates = pemfile::certs(&mut open(&self.certificates)?)
+			.map_err(|_| invalid_data(format!("Invalid certificates in {}", self.certificates.display())))?;
+		if certificates.is_empty() {
+			return Err(invalid_data(format!("No certificates found in {}", self.certificates.display())));
+		}
+
+		// Both PKCS8 and RSA keys are accepted.
+		let mut keys = pemfile::pkcs8_private_keys(&mut open(&self.private_key)?).unwrap_or_default();
+		if keys.is_empty() {
+			keys = pemfile::rsa_private_keys(&mut open(&self.private_key)?).unwrap_or_default();
+		}
+		let key = keys.into_iter().next()
+			.ok_or_else(|| invalid_data(format!("No private key found in {}", self.private_key.display())))?;
+
+		let verifier = match self.client_ca {
+			None => rustls::NoClientAuth::new(),
+			Some((ref path, mandatory)) => {
+				let mut roots = rustls::RootCertStore::empty();
+				let (valid, _) = roots.add_pem_file(&mut open(path)?)
+					.map_err(|_| invalid_data(format!("Invalid CA certificates in {}", path.display())))?;
+				if valid == 0 {
+					return Err(invalid_data(format!("No CA certificates found in {}", path.display())));
+				}
+
+				if mandatory {
+					rustls::AllowAnyAuthenticatedClient::new(roots)
+				} else {
+					rustls::AllowAnyAnonymousOrAuthenticatedClient::new(roots)
+				}
+			},
+		};
+
+		let mut config = ServerConfig::new(verifier);
+		config.set_single_cert(certificates, key)
+			.map_err(|err| invalid_data(format!("Invalid certificate or private key: {:?}", err)))?;
+		if http2 {
+			config.set_protocols(&["h2".into(), "http/1.1".into()]);
+		} else {
+			config.set_protocols(&["http/1.1".into()]);
+		}
+		Ok(config)
+	}
+}
+
+/// TLS configuration of the server.
+///
+/// Clones share the configuration, so a clone can be kept to `reload` the certificates
+/// while the server is running.
+#[derive(Clone)]
+pub struct TlsConfig {
+	files: Files,
+	http2: Arc<atomic::AtomicBool>,
+	config: Arc<RwLock<Arc<ServerConfig>>>,
+}
+
+impl TlsConfig {
+	/// Loads PEM-encoded certificate chain (end-entity first) and private key (PKCS8 or RSA).
+	pub fn from_pem_files<A: AsRef<Path>, B: AsRef<Path>>(certificates: A, private_key: B) -> io::Result<Self> {
+		let files = Files {
+			certificates: certificates.as_ref().to_owned(),
+			private_key: private_key.as_ref().to_owned(),
+			client_ca: None,
+		};
+		let config = files.load(false)?;
+
+		Ok(TlsConfig {
+			files,
+			http2: Default::default(),
+			config: Arc::new(RwLock::new(Arc::new(config))),
+		})
+	}
+
+	/// Authenticates clients with certificates signed by CAs from given PEM file.
+	/// If `mandatory` is `false` clients without certificates are accepted too.
+	/// Certificates are passed to `MetaExtractor::read_metadata_with_certificates`.
+	pub fn client_auth<P: AsRef<Path>>(mut self, ca_certificates: P, mandatory: bool) -> io::Result<Self> {
+		self.files.client_ca = Some((ca_certificates.as_ref().to_owned(), mandatory));
+		self.reload()?;
+		Ok(self)
+	}
+
+	/// Reads the files again. New connections use the reloaded certificates,
+	/// established ones are not affected.
+	/// The configuration in use is kept if loading fails.
+	pub fn reload(&self) -> io::Result<()> {
+		let config = self.files.load(self.http2.load(atomic::Ordering::SeqCst))?;
+		*self.config.write().expect("Lock is never poisoned; qed") = Arc::new(config);
+		Ok(())
+	}
+
+	fn current(&self) -> Arc<ServerConfig> {
+		self.config.read().expect("Lock is never poisoned; qed").clone()
+	}
+}
+
+/// Advertises HTTP/2 with ALPN.
+pub fn enable_http2(tls: &TlsConfig) -> io::Result<()> {
+	tls.http2.store(true, atomic::Ordering::SeqCst);
+	tls.reload()
+}
+
+/// Performs TLS handshake and serves HTTP over the connection.
+/// HTTP/2 is used if negotiated with ALPN.
+pub fn bind_connection<M: Metadata, S: Middleware<M>>(
+	tls: &TlsConfig,
+	http: &Rc<server::Http>,
+	handle: &Handle,
+	socket: TcpStream,
+	addr: SocketAddr,
+	handler: ServerHandler<M, S>,
+) {
+	let http = http.clone();
+	let handle2 = handle.clone();
+	handle.spawn(tls.current().accept_async(socket).then(move |result| {
+		match result {
+			Ok(stream) => {
+				let (certificates, is_http2) = {
+					let session = stream.get_ref().1;
+					let certificates = session.get_peer_certificates()
+						.unwrap_or_default()
+						.into_iter()
+						.map(|certificate| certificate.0)
+						.collect();
+					(certificates, session.get_alpn_protocol() == Some("h2"))
+				};
+				let handler = handler.with_client_certificates(certificates);
+				if is_http2 {
+					http2::serve_connection(&handle2, stream, handler);
+				} else {
+					http.bind_connection(&handle2, stream, addr, handler);
+				}
+			},
+			Err(err) => debug!("TLS handshake with {} failed: {:?}", addr, err),
+		}
+		Ok(())
+	}));
+}
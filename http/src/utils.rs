@@ -4,7 +4,7 @@ use server_utils::{cors, hosts};
 pub use server_utils::cors::CorsHeader;
 
 /// Extracts string value of a single header in request.
-fn read_header<'a>(req: &'a server::Request, header: &str) -> Option<&'a str> {
+pub fn read_header<'a>(req: &'a server::Request, header: &str) -> Option<&'a str> {
 	match req.headers().get_raw(header) {
 		Some(ref v) if v.len() == 1 => {
 			::std::str::from_utf8(&v[0]).ok()
@@ -26,12 +26,46 @@ pub fn cors_header(
 	request: &server::Request,
 	cors_domains: &Option<Vec<cors::AccessControlAllowOrigin>>
 ) -> CorsHeader<header::AccessControlAllowOrigin> {
-	cors::get_cors_header(read_header(request, "origin"), read_header(request, "host"), cors_domains).map(|origin| {
+	cors_header_with_policy(request, cors_domains, &Default::default())
+}
+
+/// Returns a CORS header that should be returned with that request, taking given policy into account.
+pub fn cors_header_with_policy(
+	request: &server::Request,
+	cors_domains: &Option<Vec<cors::AccessControlAllowOrigin>>,
+	cors_policy: &cors::CorsPolicy,
+) -> CorsHeader<header::AccessControlAllowOrigin> {
+	let origin = read_header(request, "origin");
+	cors::get_cors_header(origin, read_header(request, "host"), cors_domains).map(|allowed| {
 		use self::cors::AccessControlAllowOrigin::*;
-		match origin {
+		match cors_policy.allowed_origin(allowed, origin) {
 			Value(val) => header::AccessControlAllowOrigin::Value((*val).to_owned()),
 			Null => header::AccessControlAllowOrigin::Null,
 			Any => header::AccessControlAllowOrigin::Any,
 		}
 	})
 }
+
+/// Normalizes path prefix to start with `/` and have no trailing `/`
+/// (so the root path is empty).
+pub fn normalize_path(path: &str) -> String {
+	let path = path.trim_matches('/');
+	if path.is_empty() {
+		String::new()
+	} else {
+		format!("/{}", path)
+	}
+}
+
+/// Returns the rest of the path if it starts with given (normalized) prefix.
+pub fn strip_path<'a>(prefix: &str, path: &'a str) -> Option<&'a str> {
+	if !path.starts_with(prefix) {
+		return None;
+	}
+
+	match &path[prefix.len()..] {
+		"" => Some("/"),
+		rest if rest.starts_with('/') => Some(rest),
+		_ => None,
+	}
+}
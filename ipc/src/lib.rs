@@ -23,4 +23,6 @@ pub use meta::{MetaExtractor, RequestContext};
 pub use server::{Server, ServerBuilder};
 
 pub use self::server_utils::tokio_core;
-pub use self::server_utils::session::{SessionStats, SessionId};
+pub use self::server_utils::session::{Execution, SessionStats, SessionId};
+pub use self::server_utils::outbound::{OutboundRequests, CallError, CallFuture};
+pub use self::server_utils::progress::{ProgressMetadata, ProgressReporter, WithProgress};
@@ -1,5 +1,6 @@
 use jsonrpc::futures::sync::mpsc;
 use jsonrpc::Metadata;
+use server_utils::outbound::OutboundRequests;
 use server_utils::session;
 
 /// Request context
@@ -10,6 +11,8 @@ pub struct RequestContext<'a> {
 	pub endpoint_addr: &'a ::parity_tokio_ipc::RemoteId,
 	/// Direct pipe sender
 	pub sender: mpsc::Sender<String>,
+	/// Requests to the client (responses are matched by id)
+	pub outbound: OutboundRequests,
 }
 
 /// Metadata extractor (per session)
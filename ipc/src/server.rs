@@ -4,11 +4,13 @@ use std::sync::Arc;
 use tokio_service::{self, Service as TokioService};
 use jsonrpc::futures::{future, Future, Stream, Sink};
 use jsonrpc::futures::sync::{mpsc, oneshot};
-use jsonrpc::{FutureResult, Metadata, MetaIoHandler, Middleware, NoopMiddleware};
+use jsonrpc::{Encoding, FutureResult, Metadata, MetaIoHandler, Middleware, NoopMiddleware, StreamingResponse};
 
 use server_utils::tokio_core::reactor::Remote;
 use server_utils::tokio_io::AsyncRead;
 use server_utils::{reactor, session, codecs};
+use server_utils::outbound::OutboundRequests;
+use server_utils::session::{Execution, MessageQueue, OrderedCalls};
 
 use meta::{MetaExtractor, NoopExtractor, RequestContext};
 
@@ -16,12 +18,13 @@ use meta::{MetaExtractor, NoopExtractor, RequestContext};
 pub struct Service<M: Metadata = (), S: Middleware<M> = NoopMiddleware> {
 	handler: Arc<MetaIoHandler<M, S>>,
 	meta: M,
+	sender: mpsc::Sender<String>,
 }
 
 impl<M: Metadata, S: Middleware<M>> Service<M, S> {
-	/// Create new IPC server session with given handler and metadata.
-	pub fn new(handler: Arc<MetaIoHandler<M, S>>, meta: M) -> Self {
-		Service { handler: handler, meta: meta }
+	/// Create new IPC server session with given handler, metadata and channel to the client.
+	pub fn new(handler: Arc<MetaIoHandler<M, S>>, meta: M, sender: mpsc::Sender<String>) -> Self {
+		Service { handler: handler, meta: meta, sender: sender }
 	}
 }
 
@@ -31,11 +34,14 @@ impl<M: Metadata, S: Middleware<M>> tokio_service::Service for Service<M, S> {
 
 	type Error = ();
 
-	type Future = FutureResult<S::Future>;
+	type Future = future::Either<FutureResult<S::Future>, Box<Future<Item = Option<String>, Error = ()> + Send>>;
 
 	fn call(&self, req: Self::Request) -> Self::Future {
 		trace!(target: "ipc", "Received request: {}", req);
-		self.handler.handle_request(&req, self.meta.clone())
+		match self.handler.handle_streaming_request(&req, self.meta.clone()) {
+			StreamingResponse::Stream(messages) => future::Either::B(session::push_chunks(messages, self.sender.clone())),
+			StreamingResponse::Response(response) => future::Either::A(response),
+		}
 	}
 }
 
@@ -47,6 +53,9 @@ pub struct ServerBuilder<M: Metadata = (), S: Middleware<M> = NoopMiddleware> {
 	remote: reactor::UninitializedRemote,
 	incoming_separator: codecs::Separator,
 	outgoing_separator: codecs::Separator,
+	binary_encoding: Option<Arc<Encoding>>,
+	execution: Execution,
+	max_concurrent_requests: usize,
 }
 
 impl<M: Metadata + Default, S: Middleware<M>> ServerBuilder<M, S> {
@@ -71,6 +80,9 @@ impl<M: Metadata, S: Middleware<M>> ServerBuilder<M, S> {
 			remote: reactor::UninitializedRemote::Unspawned,
 			incoming_separator: codecs::Separator::Empty,
 			outgoing_separator: codecs::Separator::default(),
+			binary_encoding: None,
+			execution: Execution::Ordered,
+			max_concurrent_requests: 64,
 		}
 	}
 
@@ -101,6 +113,32 @@ impl<M: Metadata, S: Middleware<M>> ServerBuilder<M, S> {
 		self
 	}
 
+	/// Exchanges messages serialized with given encoding in length-prefixed frames
+	/// (see `codecs::LengthPrefixedCodec`) instead of JSON separated with request separators.
+	/// Default: None
+	pub fn binary_encoding<E: Encoding>(mut self, encoding: E) -> Self {
+		self.binary_encoding = Some(Arc::new(encoding));
+		self
+	}
+
+	/// Sets the order of execution of requests received on a single connection.
+	/// Default: `Execution::Ordered`
+	pub fn execution(mut self, execution: Execution) -> Self {
+		self.execution = execution;
+		self
+	}
+
+	/// Maximal number of requests processed at once (`Execution::Concurrent`)
+	/// or read ahead while the previous ones are processed (`Execution::Ordered`)
+	/// on a single connection. Further requests are not read from the connection
+	/// until some of the pending ones complete.
+	/// Limit of `0` is treated as `1`.
+	/// Default: 64
+	pub fn max_concurrent_requests(mut self, limit: usize) -> Self {
+		self.max_concurrent_requests = std::cmp::max(limit, 1);
+		self
+	}
+
 	/// Run server (in a separate thread)
 	pub fn start(self, path: &str) -> std::io::Result<Server> {
 		let remote = self.remote.initialize()?;
@@ -110,6 +148,9 @@ impl<M: Metadata, S: Middleware<M>> ServerBuilder<M, S> {
 		let session_stats = self.session_stats;
 		let incoming_separator = self.incoming_separator;
 		let outgoing_separator = self.outgoing_separator;
+		let binary_encoding = self.binary_encoding;
+		let execution = self.execution;
+		let max_concurrent_requests = self.max_concurrent_requests;
 		let (stop_signal, stop_receiver) = oneshot::channel();
 		let (start_signal, start_receiver) = oneshot::channel();
 
@@ -151,19 +192,26 @@ impl<M: Metadata, S: Middleware<M>> ServerBuilder<M, S> {
 				session_stats.as_ref().map(|stats| stats.open_session(session_id));
 
 				let (sender, receiver) = mpsc::channel(16);
+				let outbound = OutboundRequests::new(sender.clone());
 				let meta = meta_extractor.extract(&RequestContext {
 					endpoint_addr: &remote_id,
 					session_id,
-					sender,
+					sender: sender.clone(),
+					outbound: outbound.clone(),
 				});
-				let service = Service::new(rpc_handler.clone(), meta);
-				let (writer, reader) = io_stream.framed(
-					codecs::StreamCodec::new(
+				let service = Service::new(rpc_handler.clone(), meta, sender);
+				let codec = match binary_encoding {
+					Some(ref encoding) => codecs::MessageCodec::Encoded(Default::default(), encoding.clone()),
+					None => codecs::MessageCodec::Stream(codecs::StreamCodec::new(
 						incoming_separator.clone(),
 						outgoing_separator.clone(),
-					)
-				).split();
-				let responses = reader.and_then(move |req| {
+					)),
+				};
+				let (writer, reader) = io_stream.framed(codec).split();
+				// responses to requests sent to the client don't reach the handler
+				let pending = outbound.clone();
+				let reader = reader.filter(move |req| !pending.handle_message(req));
+				let call = move |req| {
 					service.call(req).then(move |response| match response {
 						Err(e) => {
 							warn!(target: "ipc", "Error while processing request: {:?}", e);
@@ -177,16 +225,19 @@ impl<M: Metadata, S: Middleware<M>> ServerBuilder<M, S> {
 							future::ok(Some(response_data))
 						}
 					})
-				})
-				.filter_map(|x| x)
-				.select(receiver.map_err(|e| {
-					warn!(target: "ipc", "Notification error: {:?}", e);
-					std::io::ErrorKind::Other.into()
-				}));
+				};
+				// In concurrent mode responses are written as soon as they are ready,
+				// clients match them with requests by id.
+				let responses: Box<Stream<Item = Option<String>, Error = std::io::Error> + Send> = match execution {
+					Execution::Ordered => Box::new(OrderedCalls::new(reader, max_concurrent_requests, call)),
+					Execution::Concurrent => Box::new(reader.map(call).buffer_unordered(max_concurrent_requests)),
+				};
+				let responses = MessageQueue::new(responses.filter_map(|x| x), receiver);
 
 				let writer = writer.send_all(responses).then(move |_| {
 					trace!(target: "ipc", "Peer: service finished");
 					session_stats.as_ref().map(|stats| stats.close_session(session_id));
+					outbound.close();
 					Ok(())
 				});
 
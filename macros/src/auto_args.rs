@@ -4,14 +4,15 @@
 
 ///! Automatically serialize and deserialize parameters around a strongly-typed function.
 
-use jsonrpc_core::{Error, Params, Value, Metadata, Result};
+use jsonrpc_core::{Error, Params, RawParams, Value, Metadata, Result, JsonSchema};
+use jsonrpc_core::serde_json;
 use jsonrpc_core::futures::{self, Future, IntoFuture};
 use jsonrpc_core::futures::future::{self, Either};
 use jsonrpc_pubsub::{PubSubMetadata, Subscriber};
 use pubsub;
 use serde::Serialize;
-use serde::de::DeserializeOwned;
-use util::{invalid_params, expect_no_params, to_value};
+use serde::de::{DeserializeOwned, IgnoredAny};
+use util::{invalid_params, expect_no_params, expect_no_raw_params, to_value};
 
 /// Auto-generates an RPC trait from trait definition.
 ///
@@ -40,6 +41,11 @@ use util::{invalid_params, expect_no_params, to_value};
 ///
 /// Anything else will be rejected by the code generator.
 ///
+/// Adding `schema` to the attribute (e.g. `#[rpc(name = "name_here", schema)]`)
+/// attaches JSON Schemas derived from parameter and output types to the method
+/// (all of them have to implement `JsonSchema`). Params are then validated
+/// before the function is invoked.
+///
 /// ## The #[pubsub] attribute
 ///
 /// Valid form:
@@ -173,13 +179,55 @@ macro_rules! build_rpc_trait {
 		}
 	};
 
+	( WRAP $del: expr =>
+		(name = $name: expr, schema $(, alias = [ $( $alias: expr, )+ ])*)
+		fn $method: ident (&self $(, $param: ty)*) -> $result: tt <$out: ty $(, $error: ty)* >
+	) => {
+		build_rpc_trait!(WRAP $del =>
+			(name = $name $(, alias = [ $( $alias, )+ ])*)
+			fn $method (&self $(, $param)*) -> $result <$out $(, $error)* >
+		);
+		build_rpc_trait!(SCHEMA $del => $name, ($($param,)*), $out);
+	};
+
+	( WRAP $del: expr =>
+		(meta, name = $name: expr, schema $(, alias = [ $( $alias: expr, )+ ])*)
+		fn $method: ident (&self, Self::Metadata $(, $param: ty)*) -> $result: tt <$out: ty $(, $error: ty)* >
+	) => {
+		build_rpc_trait!(WRAP $del =>
+			(meta, name = $name $(, alias = [ $( $alias, )+ ])*)
+			fn $method (&self, Self::Metadata $(, $param)*) -> $result <$out $(, $error)* >
+		);
+		build_rpc_trait!(SCHEMA $del => $name, ($($param,)*), $out);
+	};
+
+	( SCHEMA $del: expr => $name: expr, (), $out: ty ) => {
+		$del.set_params_schema($name, $crate::jsonrpc_core::schema::no_params())
+			.expect("Method is registered before its schema; qed");
+		$del.set_result_schema($name, <$out as $crate::jsonrpc_core::JsonSchema>::json_schema())
+			.expect("Method is registered before its schema; qed");
+	};
+
+	( SCHEMA $del: expr => $name: expr, $params: ty, $out: ty ) => {
+		$del.set_params_schema($name, <$params as $crate::jsonrpc_core::JsonSchema>::json_schema())
+			.expect("Method is registered before its schema; qed");
+		$del.set_result_schema($name, <$out as $crate::jsonrpc_core::JsonSchema>::json_schema())
+			.expect("Method is registered before its schema; qed");
+	};
+
 	( WRAP $del: expr =>
 		(name = $name: expr $(, alias = [ $( $alias: expr, )+ ])*)
 		fn $method: ident (&self $(, $param: ty)*) -> $result: tt <$out: ty $(, $error: ty)* >
 	) => {
-		$del.add_method($name, move |base, params| {
-			$crate::WrapAsync::wrap_rpc(&(Self::$method as fn(&_ $(, $param)*) -> $result <$out $(, $error)*>), base, params)
-		});
+		$del.add_method_with_raw_params(
+			$name,
+			move |base, params| {
+				$crate::WrapAsync::wrap_rpc(&(Self::$method as fn(&_ $(, $param)*) -> $result <$out $(, $error)*>), base, params)
+			},
+			move |base, params| {
+				$crate::WrapAsync::wrap_rpc_raw(&(Self::$method as fn(&_ $(, $param)*) -> $result <$out $(, $error)*>), base, params)
+			},
+		);
 		$(
 			$(
 				$del.add_alias($alias, $name);
@@ -191,9 +239,15 @@ macro_rules! build_rpc_trait {
 		(meta, name = $name: expr $(, alias = [ $( $alias: expr, )+ ])*)
 		fn $method: ident (&self, Self::Metadata $(, $param: ty)*) -> $result: tt <$out: ty $(, $error: ty)* >
 	) => {
-		$del.add_method_with_meta($name, move |base, params, meta| {
-			$crate::WrapMeta::wrap_rpc(&(Self::$method as fn(&_, Self::Metadata $(, $param)*) -> $result <$out $(, $error)* >), base, params, meta)
-		});
+		$del.add_method_with_meta_and_raw_params(
+			$name,
+			move |base, params, meta| {
+				$crate::WrapMeta::wrap_rpc(&(Self::$method as fn(&_, Self::Metadata $(, $param)*) -> $result <$out $(, $error)* >), base, params, meta)
+			},
+			move |base, params, meta| {
+				$crate::WrapMeta::wrap_rpc_raw(&(Self::$method as fn(&_, Self::Metadata $(, $param)*) -> $result <$out $(, $error)* >), base, params, meta)
+			},
+		);
 		$(
 			$(
 				$del.add_alias($alias, $name);
@@ -251,6 +305,16 @@ impl<T> Into<Option<T>> for Trailing<T> {
 	}
 }
 
+impl<T: JsonSchema> JsonSchema for Trailing<T> {
+	fn json_schema() -> Value {
+		T::json_schema()
+	}
+
+	fn is_optional() -> bool {
+		true
+	}
+}
+
 impl<T: DeserializeOwned> Trailing<T> {
 	/// Returns a underlying value if present or provided value.
 	pub fn unwrap_or(self, other: T) -> T {
@@ -297,6 +361,9 @@ pub trait WrapAsync<B> {
 
 	/// Invokes asynchronous RPC method.
 	fn wrap_rpc(&self, base: &B, params: Params) -> Self::Out;
+
+	/// Invokes asynchronous RPC method with params deserialized from raw JSON.
+	fn wrap_rpc_raw(&self, base: &B, params: RawParams) -> Self::Out;
 }
 
 /// Wrapper trait for meta RPC functions.
@@ -305,6 +372,9 @@ pub trait WrapMeta<B, M> {
 	type Out: IntoFuture<Item = Value, Error = Error>;
 	/// Invokes asynchronous RPC method with Metadata.
 	fn wrap_rpc(&self, base: &B, params: Params, meta: M) -> Self::Out;
+
+	/// Invokes asynchronous RPC method with Metadata and params deserialized from raw JSON.
+	fn wrap_rpc_raw(&self, base: &B, params: RawParams, meta: M) -> Self::Out;
 }
 
 /// Wrapper trait for subscribe RPC functions.
@@ -329,6 +399,13 @@ impl<B, OUT, E, F, I> WrapAsync<B> for fn(&B) -> I where
 			Err(e) => Either::B(futures::failed(e)),
 		}
 	}
+
+	fn wrap_rpc_raw(&self, base: &B, params: RawParams) -> Self::Out {
+		match expect_no_raw_params(params) {
+			Ok(()) => Either::A(as_future((self)(base))),
+			Err(e) => Either::B(futures::failed(e)),
+		}
+	}
 }
 
 impl<M, B, OUT, E, F, I> WrapMeta<B, M> for fn(&B, M) -> I where
@@ -347,6 +424,13 @@ impl<M, B, OUT, E, F, I> WrapMeta<B, M> for fn(&B, M) -> I where
 			Err(e) => Either::B(futures::failed(e)),
 		}
 	}
+
+	fn wrap_rpc_raw(&self, base: &B, params: RawParams, meta: M) -> Self::Out {
+		match expect_no_raw_params(params) {
+			Ok(()) => Either::A(as_future((self)(base, meta))),
+			Err(e) => Either::B(futures::failed(e)),
+		}
+	}
 }
 
 impl<M, B, OUT> WrapSubscribe<B, M> for fn(&B, M, pubsub::Subscriber<OUT>) where
@@ -385,6 +469,13 @@ macro_rules! wrap {
 					Err(e) => Either::B(futures::failed(e)),
 				}
 			}
+
+			fn wrap_rpc_raw(&self, base: &BASE, params: RawParams) -> Self::Out {
+				match params.parse::<($($x,)+)>() {
+					Ok(($($x,)+)) => Either::A(as_future((self)(base, $($x,)+))),
+					Err(e) => Either::B(futures::failed(e)),
+				}
+			}
 		}
 
 		// asynchronous implementation with meta
@@ -404,6 +495,13 @@ macro_rules! wrap {
 					Err(e) => Either::B(futures::failed(e)),
 				}
 			}
+
+			fn wrap_rpc_raw(&self, base: &BASE, params: RawParams, meta: META) -> Self::Out {
+				match params.parse::<($($x,)+)>() {
+					Ok(($($x,)+)) => Either::A(as_future((self)(base, meta, $($x,)+))),
+					Err(e) => Either::B(futures::failed(e)),
+				}
+			}
 		}
 
 		// subscribe implementation
@@ -452,6 +550,34 @@ fn parse_trailing_param<T: DeserializeOwned>(params: Params) -> Result<(Option<T
 	id
 }
 
+fn raw_params_len(params: &RawParams) -> Result<usize> {
+	let not_array = || invalid_params("`params` should be an array", "");
+	match params.get() {
+		_ if params.is_empty() => Ok(0),
+		// Elements are only counted here, they are deserialized into expected types later.
+		Some(json) if json.starts_with('[') => serde_json::from_str::<Vec<IgnoredAny>>(json)
+			.map(|elements| elements.len())
+			.map_err(|_| not_array()),
+		_ => Err(not_array()),
+	}
+}
+
+fn require_raw_len(params: &RawParams, required: usize) -> Result<usize> {
+	let len = raw_params_len(params)?;
+	if len < required {
+		return Err(invalid_params(&format!("`params` should have at least {} argument(s)", required), ""));
+	}
+	Ok(len)
+}
+
+fn parse_trailing_raw_param<T: DeserializeOwned>(params: RawParams) -> Result<(Option<T>, )> {
+	match raw_params_len(&params)? {
+		0 => Ok((None,)),
+		1 => params.parse::<(T,)>().map(|(x, )| (Some(x), )),
+		_ => Err(invalid_params("Expecting only one optional parameter.", "")),
+	}
+}
+
 // special impl for no parameters other than block parameter.
 impl<B, OUT, T, E, F, I> WrapAsync<B> for fn(&B, Trailing<T>) -> I where
 	B: Send + Sync + 'static,
@@ -470,6 +596,13 @@ impl<B, OUT, T, E, F, I> WrapAsync<B> for fn(&B, Trailing<T>) -> I where
 			Err(e) => Either::B(futures::failed(e)),
 		}
 	}
+
+	fn wrap_rpc_raw(&self, base: &B, params: RawParams) -> Self::Out {
+		match parse_trailing_raw_param(params) {
+			Ok((id,)) => Either::A(as_future((self)(base, Trailing(id)))),
+			Err(e) => Either::B(futures::failed(e)),
+		}
+	}
 }
 
 impl<M, B, OUT, T, E, F, I> WrapMeta<B, M> for fn(&B, M, Trailing<T>) -> I where
@@ -490,6 +623,13 @@ impl<M, B, OUT, T, E, F, I> WrapMeta<B, M> for fn(&B, M, Trailing<T>) -> I where
 			Err(e) => Either::B(futures::failed(e)),
 		}
 	}
+
+	fn wrap_rpc_raw(&self, base: &B, params: RawParams, meta: M) -> Self::Out {
+		match parse_trailing_raw_param(params) {
+			Ok((id,)) => Either::A(as_future((self)(base, meta, Trailing(id)))),
+			Err(e) => Either::B(futures::failed(e)),
+		}
+	}
 }
 
 impl<M, B, OUT, T> WrapSubscribe<B, M> for fn(&B, M, pubsub::Subscriber<OUT>, Trailing<T>) where
@@ -559,6 +699,26 @@ macro_rules! wrap_with_trailing {
 					Err(e) => Either::B(futures::failed(e)),
 				}
 			}
+
+			fn wrap_rpc_raw(&self, base: &BASE, params: RawParams) -> Self::Out {
+				let len = match require_raw_len(&params, $num) {
+					Ok(len) => len,
+					Err(e) => return Either::B(futures::failed(e)),
+				};
+
+				let params = match len - $num {
+					0 => params.parse::<($($x,)+)>()
+						.map(|($($x,)+)| ($($x,)+ None)),
+					1 => params.parse::<($($x,)+ TRAILING)>()
+						.map(|($($x,)+ id)| ($($x,)+ Some(id))),
+					_ => Err(invalid_params(&format!("Expected {} or {} parameters.", $num, $num + 1), format!("Got: {}", len))),
+				};
+
+				match params {
+					Ok(($($x,)+ id)) => Either::A(as_future((self)(base, $($x,)+ Trailing(id)))),
+					Err(e) => Either::B(futures::failed(e)),
+				}
+			}
 		}
 
 		// asynchronous implementation with meta
@@ -592,6 +752,26 @@ macro_rules! wrap_with_trailing {
 					Err(e) => Either::B(futures::failed(e)),
 				}
 			}
+
+			fn wrap_rpc_raw(&self, base: &BASE, params: RawParams, meta: META) -> Self::Out {
+				let len = match require_raw_len(&params, $num) {
+					Ok(len) => len,
+					Err(e) => return Either::B(futures::failed(e)),
+				};
+
+				let params = match len - $num {
+					0 => params.parse::<($($x,)+)>()
+						.map(|($($x,)+)| ($($x,)+ None)),
+					1 => params.parse::<($($x,)+ TRAILING)>()
+						.map(|($($x,)+ id)| ($($x,)+ Some(id))),
+					_ => Err(invalid_params(&format!("Expected {} or {} parameters.", $num, $num + 1), format!("Got: {}", len))),
+				};
+
+				match params {
+					Ok(($($x,)+ id)) => Either::A(as_future((self)(base, meta, $($x,)+ Trailing(id)))),
+					Err(e) => Either::B(futures::failed(e)),
+				}
+			}
 		}
 
 		// subscribe implementation
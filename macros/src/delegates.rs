@@ -1,7 +1,7 @@
 use std::sync::Arc;
 use std::collections::HashMap;
 
-use jsonrpc_core::{Params, Value, Error};
+use jsonrpc_core::{schema, Params, RawParams, Value, Error};
 use jsonrpc_core::{BoxFuture, Metadata, RemoteProcedure, RpcMethod, RpcNotification};
 use jsonrpc_core::futures::IntoFuture;
 
@@ -45,6 +45,33 @@ impl<T, M, F, I> RpcMethod<M> for DelegateMethodWithMeta<T, F> where
 	}
 }
 
+struct DelegateRawMethod<T, F, R> {
+	delegate: Arc<T>,
+	closure: F,
+	raw_closure: R,
+}
+
+impl<T, M, F, R, I> RpcMethod<M> for DelegateRawMethod<T, F, R> where
+	M: Metadata,
+	F: Fn(&T, Params, M) -> I,
+	R: Fn(&T, RawParams, M) -> I,
+	I: IntoFuture<Item = Value, Error = Error>,
+	T: Send + Sync + 'static,
+	F: Send + Sync + 'static,
+	R: Send + Sync + 'static,
+	I::Future: Send + 'static,
+{
+	fn call(&self, params: Params, meta: M) -> BoxFuture<Value> {
+		let closure = &self.closure;
+		Box::new(closure(&self.delegate, params, meta).into_future())
+	}
+
+	fn call_raw(&self, params: RawParams, meta: M) -> BoxFuture<Value> {
+		let closure = &self.raw_closure;
+		Box::new(closure(&self.delegate, params, meta).into_future())
+	}
+}
+
 struct DelegateNotification<T, F> {
 	delegate: Arc<T>,
 	closure: F,
@@ -125,6 +152,16 @@ impl<T, M> IoDelegate<T, M> where
 		self.methods.insert(from.into(), RemoteProcedure::Alias(to.into()));
 	}
 
+	/// Attaches JSON Schema of params to a method (or the method aliased by given name).
+	pub fn set_params_schema(&mut self, name: &str, schema: Value) -> Result<(), schema::UnknownMethod> {
+		schema::attach(&mut self.methods, name, Some(schema), None)
+	}
+
+	/// Attaches JSON Schema of the result to a method (or the method aliased by given name).
+	pub fn set_result_schema(&mut self, name: &str, schema: Value) -> Result<(), schema::UnknownMethod> {
+		schema::attach(&mut self.methods, name, None, Some(schema))
+	}
+
 	/// Adds async method to the delegate.
 	pub fn add_method<F, I>(&mut self, name: &str, method: F) where
 		F: Fn(&T, Params) -> I,
@@ -155,6 +192,42 @@ impl<T, M> IoDelegate<T, M> where
 		)));
 	}
 
+	/// Adds async method to the delegate, together with a variant
+	/// deserializing params directly from raw JSON of the request.
+	pub fn add_method_with_raw_params<F, R, I>(&mut self, name: &str, method: F, raw_method: R) where
+		F: Fn(&T, Params) -> I,
+		R: Fn(&T, RawParams) -> I,
+		I: IntoFuture<Item = Value, Error = Error>,
+		F: Send + Sync + 'static,
+		R: Send + Sync + 'static,
+		I::Future: Send + 'static,
+	{
+		self.add_method_with_meta_and_raw_params(
+			name,
+			move |base, params, _meta| method(base, params),
+			move |base, params, _meta| raw_method(base, params),
+		);
+	}
+
+	/// Adds async method with metadata to the delegate, together with a variant
+	/// deserializing params directly from raw JSON of the request.
+	pub fn add_method_with_meta_and_raw_params<F, R, I>(&mut self, name: &str, method: F, raw_method: R) where
+		F: Fn(&T, Params, M) -> I,
+		R: Fn(&T, RawParams, M) -> I,
+		I: IntoFuture<Item = Value, Error = Error>,
+		F: Send + Sync + 'static,
+		R: Send + Sync + 'static,
+		I::Future: Send + 'static,
+	{
+		self.methods.insert(name.into(), RemoteProcedure::Method(Arc::new(
+			DelegateRawMethod {
+				delegate: self.delegate.clone(),
+				closure: method,
+				raw_closure: raw_method,
+			}
+		)));
+	}
+
 	/// Adds notification to the delegate.
 	pub fn add_notification<F>(&mut self, name: &str, notification: F) where
 		F: Fn(&T, Params),
@@ -55,6 +55,7 @@ extern crate serde;
 
 mod auto_args;
 mod delegates;
+mod rpc_error;
 mod util;
 
 pub mod pubsub;
@@ -0,0 +1,99 @@
+/// Defines an enum of application errors and implements `IntoRpcError` for it.
+///
+/// Every variant has to be annotated with the error code and message.
+/// Variants may carry a single serializable value, which is returned as error `data`.
+///
+/// ```
+/// #[macro_use] extern crate jsonrpc_macros;
+/// extern crate jsonrpc_core;
+///
+/// use jsonrpc_core::{Error, IntoRpcError};
+///
+/// build_rpc_error! {
+/// 	/// Errors of the balances module.
+/// 	#[derive(Debug, Clone, PartialEq)]
+/// 	pub enum BalanceError {
+/// 		/// Not enough funds (missing amount is returned as data).
+/// 		#[rpc(code = -32010, message = "Insufficient balance")]
+/// 		Insufficient(u64),
+/// 		/// Account is locked.
+/// 		#[rpc(code = -32011, message = "Account is locked")]
+/// 		Locked,
+/// 	}
+/// }
+///
+/// fn main() {
+/// 	let error: Error = BalanceError::Insufficient(5).into();
+/// 	assert_eq!(error.code.code(), -32010);
+/// 	assert_eq!(error.data, Some(5.into()));
+/// 	assert_eq!(BalanceError::error_codes().len(), 2);
+/// }
+/// ```
+#[macro_export]
+macro_rules! build_rpc_error {
+	(
+		$(#[$t_attr: meta])*
+		pub enum $name: ident {
+			$(
+				$( #[doc=$v_doc:expr] )*
+				#[ rpc(code = $code: expr, message = $message: expr) ]
+				$variant: ident $( ( $data: ty ) )*,
+			)+
+		}
+	) => {
+		$(#[$t_attr])*
+		pub enum $name {
+			$(
+				$(#[doc=$v_doc])*
+				$variant $( ( $data ) )*,
+			)+
+		}
+
+		impl $crate::jsonrpc_core::IntoRpcError for $name {
+			fn code(&self) -> $crate::jsonrpc_core::ErrorCode {
+				match *self {
+					$(
+						build_rpc_error!(PATTERN $name::$variant $(, $data)*) => $crate::jsonrpc_core::ErrorCode::from($code),
+					)+
+				}
+			}
+
+			fn message(&self) -> String {
+				match *self {
+					$(
+						build_rpc_error!(PATTERN $name::$variant $(, $data)*) => $message.into(),
+					)+
+				}
+			}
+
+			fn data(&self) -> Option<$crate::jsonrpc_core::Value> {
+				match *self {
+					$(
+						build_rpc_error!(DATA_PATTERN data, $name::$variant $(, $data)*) => build_rpc_error!(DATA data $(, $data)*),
+					)+
+				}
+			}
+
+			fn error_codes() -> Vec<$crate::jsonrpc_core::ErrorCodeInfo> {
+				vec![$(
+					$crate::jsonrpc_core::ErrorCodeInfo::new($code, stringify!($variant), $message),
+				)+]
+			}
+		}
+
+		impl From<$name> for $crate::jsonrpc_core::Error {
+			fn from(error: $name) -> Self {
+				$crate::jsonrpc_core::IntoRpcError::into_rpc_error(error)
+			}
+		}
+	};
+
+	(PATTERN $variant: path) => { $variant };
+	(PATTERN $variant: path, $data: ty) => { $variant(..) };
+
+	(DATA_PATTERN $binding: ident, $variant: path) => { $variant };
+	(DATA_PATTERN $binding: ident, $variant: path, $data: ty) => { $variant(ref $binding) };
+
+	(DATA $binding: ident) => { None };
+	(DATA $binding: ident, $data: ty) => { Some($crate::to_value($binding)) };
+}
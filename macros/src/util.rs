@@ -1,7 +1,7 @@
 //! Param & Value utilities
 
 use std::fmt;
-use jsonrpc_core::{self as core, Error, Params, ErrorCode, Value};
+use jsonrpc_core::{self as core, Error, Params, RawParams, ErrorCode, Value};
 use serde;
 
 /// Returns an `InvalidParams` for given parameter.
@@ -17,10 +17,22 @@ pub fn invalid_params<T>(param: &str, details: T) -> Error where T: fmt::Debug {
 pub fn expect_no_params(params: Params) -> core::Result<()> {
 	match params {
 		Params::None => Ok(()),
+		Params::Array(ref vec) if vec.is_empty() => Ok(()),
+		Params::Map(ref map) if map.is_empty() => Ok(()),
 		p => Err(invalid_params("No parameters were expected", p)),
 	}
 }
 
+/// Validates if the method was invoked without any params (kept as raw JSON).
+pub fn expect_no_raw_params(params: RawParams) -> core::Result<()> {
+	if params.is_empty() {
+		return Ok(());
+	}
+
+	// Report the same error as `expect_no_params`.
+	params.to_params().and_then(expect_no_params)
+}
+
 /// Converts a serializable value into `Value`.
 pub fn to_value<T>(value: T) -> Value where T: serde::Serialize {
 	core::to_value(value).expect("Expected always-serializable type.")
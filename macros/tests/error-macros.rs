@@ -0,0 +1,81 @@
+extern crate serde_json;
+extern crate jsonrpc_core;
+#[macro_use]
+extern crate jsonrpc_macros;
+
+use jsonrpc_core::{Error, ErrorCode, ErrorCodeInfo, IntoRpcError, IoHandler, Result};
+
+build_rpc_error! {
+	/// Errors of the balances module.
+	#[derive(Debug, Clone, PartialEq)]
+	pub enum BalanceError {
+		/// Not enough funds.
+		#[rpc(code = -32010, message = "Insufficient balance")]
+		Insufficient(u64),
+		#[rpc(code = -32011, message = "Account is locked")]
+		Locked,
+	}
+}
+
+build_rpc_error! {
+	#[derive(Debug)]
+	pub enum AccountError {
+		#[rpc(code = -32011, message = "Unknown account")]
+		Unknown(String),
+	}
+}
+
+build_rpc_trait! {
+	pub trait Rpc {
+		/// Transfers funds
+		#[rpc(name = "transfer")]
+		fn transfer(&self, u64) -> Result<u64>;
+	}
+}
+
+struct RpcImpl;
+
+impl Rpc for RpcImpl {
+	fn transfer(&self, amount: u64) -> Result<u64> {
+		if amount > 10 {
+			Err(BalanceError::Insufficient(amount - 10).into())
+		} else {
+			Ok(10 - amount)
+		}
+	}
+}
+
+#[test]
+fn should_convert_errors() {
+	let error: Error = BalanceError::Insufficient(5).into();
+	assert_eq!(error, Error {
+		code: ErrorCode::ServerError(-32010),
+		message: "Insufficient balance".into(),
+		data: Some(5.into()),
+	});
+	assert_eq!(BalanceError::Locked.into_rpc_error().data, None);
+	assert_eq!(AccountError::Unknown("x".into()).data(), Some("x".into()));
+	assert_eq!(BalanceError::error_codes(), vec![
+		ErrorCodeInfo::new(-32010, "Insufficient", "Insufficient balance"),
+		ErrorCodeInfo::new(-32011, "Locked", "Account is locked"),
+	]);
+}
+
+#[test]
+fn should_register_errors_and_return_them_from_methods() {
+	// given
+	let mut io = IoHandler::new();
+	io.extend_with(RpcImpl.to_delegate());
+	io.register_errors::<BalanceError>("balances").unwrap();
+
+	// when
+	let duplicate = io.register_errors::<AccountError>("accounts");
+	let request = r#"{"jsonrpc":"2.0","method":"transfer","params":[15],"id":1}"#;
+	let result = io.handle_request_sync(request).unwrap();
+	let discovery = serde_json::to_string(&io.discover()["errors"]).unwrap();
+
+	// then
+	assert_eq!(duplicate.unwrap_err().registered_by, "balances");
+	assert_eq!(result, r#"{"jsonrpc":"2.0","error":{"code":-32010,"message":"Insufficient balance","data":5},"id":1}"#);
+	assert_eq!(discovery, r#"[{"code":-32011,"message":"Account is locked","module":"balances","name":"Locked"},{"code":-32010,"message":"Insufficient balance","module":"balances","name":"Insufficient"}]"#);
+}
@@ -0,0 +1,122 @@
+extern crate serde_json;
+extern crate jsonrpc_core;
+#[macro_use]
+extern crate jsonrpc_macros;
+
+use jsonrpc_core::{IoHandler, Result};
+use jsonrpc_macros::Trailing;
+
+build_rpc_trait! {
+	pub trait Rpc {
+		/// Adds two numbers
+		#[rpc(name = "add", schema, alias = ["plus", ])]
+		fn add(&self, u8, Trailing<u8>) -> Result<u8>;
+
+		/// Returns a protocol version
+		#[rpc(name = "version")]
+		fn version(&self) -> Result<String>;
+
+		/// Checks if the server is alive
+		#[rpc(name = "ping", schema)]
+		fn ping(&self) -> Result<bool>;
+	}
+}
+
+struct RpcImpl;
+
+impl Rpc for RpcImpl {
+	fn add(&self, a: u8, b: Trailing<u8>) -> Result<u8> {
+		Ok(a.saturating_add(b.unwrap_or(0)))
+	}
+
+	fn version(&self) -> Result<String> {
+		Ok("1".into())
+	}
+
+	fn ping(&self) -> Result<bool> {
+		Ok(true)
+	}
+}
+
+fn io() -> IoHandler {
+	let mut io = IoHandler::new();
+	io.extend_with(RpcImpl.to_delegate());
+	io
+}
+
+#[test]
+fn test_schema_params_validation() {
+	// given
+	let io = io();
+
+	// when
+	let req = r#"{"jsonrpc":"2.0","id":1,"method":"plus","params":[1, 300]}"#;
+	let res = io.handle_request_sync(req);
+
+	// then
+	let expected = r#"{
+		"jsonrpc": "2.0",
+		"error": {
+			"code": -32602,
+			"message": "Invalid params: expected value less than or equal to 255 at /1.",
+			"data": {
+				"pointer": "/1",
+				"message": "expected value less than or equal to 255"
+			}
+		},
+		"id": 1
+	}"#;
+	let expected: jsonrpc_core::Response = serde_json::from_str(expected).unwrap();
+	let result: jsonrpc_core::Response = serde_json::from_str(&res.unwrap()).unwrap();
+	assert_eq!(expected, result);
+}
+
+#[test]
+fn test_schema_accepts_trailing_params() {
+	// given
+	let io = io();
+
+	// when
+	let req = r#"{"jsonrpc":"2.0","id":1,"method":"add","params":[1]}"#;
+	let res = io.handle_request_sync(req);
+
+	// then
+	assert_eq!(res, Some(r#"{"jsonrpc":"2.0","result":1,"id":1}"#.into()));
+}
+
+#[test]
+fn test_schema_accepts_empty_params_without_arguments() {
+	// given
+	let io = io();
+
+	// when
+	let req1 = r#"{"jsonrpc":"2.0","id":1,"method":"ping","params":[]}"#;
+	let req2 = r#"{"jsonrpc":"2.0","id":1,"method":"ping"}"#;
+	let req3 = r#"{"jsonrpc":"2.0","id":1,"method":"ping","params":[1]}"#;
+	let res1 = io.handle_request_sync(req1);
+	let res2 = io.handle_request_sync(req2);
+	let res3 = io.handle_request_sync(req3);
+
+	// then
+	assert_eq!(res1, Some(r#"{"jsonrpc":"2.0","result":true,"id":1}"#.into()));
+	assert_eq!(res2, Some(r#"{"jsonrpc":"2.0","result":true,"id":1}"#.into()));
+	let result: jsonrpc_core::Response = serde_json::from_str(&res3.unwrap()).unwrap();
+	match result {
+		jsonrpc_core::Response::Single(jsonrpc_core::Output::Failure(ref failure)) => assert_eq!(failure.error.code, jsonrpc_core::ErrorCode::InvalidParams),
+		_ => panic!("Expected invalid params error."),
+	}
+}
+
+#[test]
+fn test_schema_in_discovery() {
+	// given
+	let io = io();
+
+	// when
+	let discovery = io.discover();
+
+	// then
+	assert_eq!(discovery["methods"]["add"]["params"]["minItems"], 1);
+	assert_eq!(discovery["methods"]["add"]["result"]["maximum"], 255);
+	assert_eq!(discovery["methods"]["version"].get("params"), None);
+}
@@ -0,0 +1,64 @@
+extern crate jsonrpc_core;
+#[macro_use]
+extern crate jsonrpc_macros;
+
+use jsonrpc_core::{IoHandler, Result};
+use jsonrpc_macros::Trailing;
+
+build_rpc_trait! {
+	pub trait Rpc {
+		/// Multiplies two numbers, the second one defaults to 2
+		#[rpc(name = "mul")]
+		fn mul(&self, u64, Trailing<u64>) -> Result<u64>;
+
+		/// Returns given number or 0
+		#[rpc(name = "number")]
+		fn number(&self, Trailing<u64>) -> Result<u64>;
+
+		/// Returns a protocol version
+		#[rpc(name = "version")]
+		fn version(&self) -> Result<String>;
+	}
+}
+
+struct RpcImpl;
+
+impl Rpc for RpcImpl {
+	fn mul(&self, a: u64, b: Trailing<u64>) -> Result<u64> {
+		Ok(a * b.unwrap_or(2))
+	}
+
+	fn number(&self, a: Trailing<u64>) -> Result<u64> {
+		Ok(a.unwrap_or(0))
+	}
+
+	fn version(&self) -> Result<String> {
+		Ok("1".into())
+	}
+}
+
+fn call(method: &str, params: &str) -> String {
+	let mut io = IoHandler::new();
+	io.extend_with(RpcImpl.to_delegate());
+	let request = format!(r#"{{"jsonrpc":"2.0","id":1,"method":"{}","params":{}}}"#, method, params);
+	io.handle_request_sync(&request).unwrap()
+}
+
+#[test]
+fn test_trailing_params() {
+	assert_eq!(call("mul", "[3]"), r#"{"jsonrpc":"2.0","result":6,"id":1}"#);
+	assert_eq!(call("mul", "[3, 5]"), r#"{"jsonrpc":"2.0","result":15,"id":1}"#);
+	assert_eq!(call("mul", "[]"), r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: `params` should have at least 1 argument(s)","data":"\"\""},"id":1}"#);
+	assert_eq!(call("mul", "[3, 5, 7]"), r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: Expected 1 or 2 parameters.","data":"\"Got: 3\""},"id":1}"#);
+	assert_eq!(call("mul", r#"{"a": 3}"#), r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: `params` should be an array","data":"\"\""},"id":1}"#);
+	assert_eq!(call("number", "[]"), r#"{"jsonrpc":"2.0","result":0,"id":1}"#);
+	assert_eq!(call("number", "[4]"), r#"{"jsonrpc":"2.0","result":4,"id":1}"#);
+	assert_eq!(call("number", "[4, 5]"), r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: Expecting only one optional parameter.","data":"\"\""},"id":1}"#);
+}
+
+#[test]
+fn test_no_params() {
+	assert_eq!(call("version", "[]"), r#"{"jsonrpc":"2.0","result":"1","id":1}"#);
+	assert_eq!(call("version", "{}"), r#"{"jsonrpc":"2.0","result":"1","id":1}"#);
+	assert_eq!(call("version", "[1]"), r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: No parameters were expected","data":"Array([Number(1)])"},"id":1}"#);
+}
@@ -78,6 +78,7 @@ pub struct ServerBuilder<M: jsonrpc::Metadata = (), S: jsonrpc::Middleware<M> =
 	jsonrpc_handler: Arc<MetaIoHandler<M, S>>,
 	meta_extractor: Arc<MetaExtractor<M>>,
 	cors_domains: Option<Vec<cors::AccessControlAllowOrigin>>,
+	cors_policy: cors::CorsPolicy,
 	allowed_hosts: Option<Vec<Host>>,
 	threads: usize,
 }
@@ -109,6 +110,7 @@ impl<M: jsonrpc::Metadata, S: jsonrpc::Middleware<M>> ServerBuilder<M, S> {
 			jsonrpc_handler: Arc::new(handler.into()),
 			meta_extractor: Arc::new(extractor),
 			cors_domains: None,
+			cors_policy: cors::CorsPolicy::default(),
 			allowed_hosts: None,
 			threads: 1,
 		}
@@ -128,6 +130,12 @@ impl<M: jsonrpc::Metadata, S: jsonrpc::Middleware<M>> ServerBuilder<M, S> {
 		self
 	}
 
+	/// Configures CORS headers sent to allowed origins, e.g. to allow `Authorization` header.
+	pub fn cors_policy(mut self, cors_policy: cors::CorsPolicy) -> Self {
+		self.cors_policy = cors_policy;
+		self
+	}
+
 	/// Configures metadata extractor
 	pub fn meta_extractor<T: MetaExtractor<M>>(mut self, extractor: T) -> Self {
 		self.meta_extractor = Arc::new(extractor);
@@ -149,6 +157,7 @@ impl<M: jsonrpc::Metadata, S: jsonrpc::Middleware<M>> ServerBuilder<M, S> {
 	/// Start this JSON-RPC HTTP server trying to bind to specified `SocketAddr`.
 	pub fn start_http(self, addr: &SocketAddr) -> io::Result<Server> {
 		let cors_domains = self.cors_domains;
+		let cors_policy = Arc::new(self.cors_policy);
 		let allowed_hosts = self.allowed_hosts;
 		let handler = self.jsonrpc_handler;
 		let meta_extractor = self.meta_extractor;
@@ -170,6 +179,7 @@ impl<M: jsonrpc::Metadata, S: jsonrpc::Middleware<M>> ServerBuilder<M, S> {
 					meta_extractor: meta_extractor.clone(),
 					hosts: hosts.read().clone(),
 					cors_domains: cors_domains.clone(),
+					cors_policy: cors_policy.clone(),
 				}))?;
 
 				let local_addr = server.local_addr()?;
@@ -217,6 +227,7 @@ pub struct RpcService<M: jsonrpc::Metadata, S: jsonrpc::Middleware<M>> {
 	meta_extractor: Arc<MetaExtractor<M>>,
 	hosts: Option<Vec<Host>>,
 	cors_domains: Option<Vec<cors::AccessControlAllowOrigin>>,
+	cors_policy: Arc<cors::CorsPolicy>,
 }
 
 fn is_json(content_type: Option<&str>) -> bool {
@@ -260,7 +271,8 @@ impl<M: jsonrpc::Metadata, S: jsonrpc::Middleware<M>> tokio_service::Service for
 
 		// Extract CORS headers
 		let origin = request.header("Origin");
-		let cors = cors::get_cors_header(origin, host, &self.cors_domains);
+		let cors = cors::get_cors_header(origin, host, &self.cors_domains)
+			.map(|allowed| self.cors_policy.allowed_origin(allowed, origin));
 
 		// Validate cors header
 		if let cors::CorsHeader::Invalid = cors {
@@ -269,10 +281,18 @@ impl<M: jsonrpc::Metadata, S: jsonrpc::Middleware<M>> tokio_service::Service for
 			));
 		}
 
+		let cors_headers = self.cors_policy.headers(is_options, request.header("Access-Control-Request-Headers"));
+		let cors: Option<_> = cors.into();
+		let cors = cors.map(|origin| res::Cors {
+			origin,
+			headers: cors_headers,
+			vary_request_headers: self.cors_policy.varies_with_request_headers(),
+		});
+
 		// Don't process data if it's OPTIONS
 		if is_options {
 			return Either::A(future::ok(
-				res::options(cors.into())
+				res::options(cors)
 			));
 		}
 
@@ -292,7 +312,7 @@ impl<M: jsonrpc::Metadata, S: jsonrpc::Middleware<M>> tokio_service::Service for
 		let future = self.handler.handle_request(data, metadata);
 		Either::B(RpcResponse {
 			future: future,
-			cors: cors.into(),
+			cors: cors,
 		})
 	}
 }
@@ -300,7 +320,7 @@ impl<M: jsonrpc::Metadata, S: jsonrpc::Middleware<M>> tokio_service::Service for
 /// RPC response wrapper
 pub struct RpcResponse<F: Future<Item = Option<Response>, Error = ()>> {
 	future: FutureResult<F>,
-	cors: Option<cors::AccessControlAllowOrigin>,
+	cors: Option<res::Cors>,
 }
 
 impl<F: Future<Item = Option<Response>, Error = ()>> Future for RpcResponse<F> {
@@ -5,7 +5,14 @@ use jsonrpc_server_utils::cors;
 
 const SERVER: &'static str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
 
-pub fn options(cors: Option<cors::AccessControlAllowOrigin>) -> Response {
+/// CORS headers of a response to allowed origin.
+pub struct Cors {
+	pub origin: cors::AccessControlAllowOrigin,
+	pub headers: Vec<(&'static str, String)>,
+	pub vary_request_headers: bool,
+}
+
+pub fn options(cors: Option<Cors>) -> Response {
 	let mut response = new("", cors);
 	response
 		.header("Allow", "OPTIONS, POST")
@@ -63,7 +70,7 @@ pub fn invalid_content_type() -> Response {
 	response
 }
 
-pub fn new(body: &str, cors: Option<cors::AccessControlAllowOrigin>) -> Response {
+pub fn new(body: &str, cors: Option<Cors>) -> Response {
 	let mut response = Response::new();
 	response
 		.header("Content-Type", "application/json")
@@ -73,13 +80,15 @@ pub fn new(body: &str, cors: Option<cors::AccessControlAllowOrigin>) -> Response
 	if let Some(cors) = cors {
 		response
 			.header("Access-Control-Allow-Methods", "OPTIONS, POST")
-			.header("Access-Control-Allow-Headers", "Origin, Content-Type, Accept")
-			.header("Access-Control-Allow-Origin", match cors {
+			.header("Access-Control-Allow-Origin", match cors.origin {
 				cors::AccessControlAllowOrigin::Null => "null",
 				cors::AccessControlAllowOrigin::Any => "*",
 				cors::AccessControlAllowOrigin::Value(ref val) => val,
 			})
-			.header("Vary", "Origin");
+			.header("Vary", if cors.vary_request_headers { "Origin, Access-Control-Request-Headers" } else { "Origin" });
+		for &(name, ref value) in &cors.headers {
+			response.header(name, value);
+		}
 	}
 	response
 }
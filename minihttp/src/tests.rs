@@ -274,6 +274,39 @@ fn should_add_cors_headers_for_options() {
 	);
 }
 
+#[test]
+fn should_add_headers_of_cors_policy() {
+	// given
+	let server = ServerBuilder::new(IoHandler::default())
+		.cors(hosts::DomainsValidation::AllowOnly(vec![cors::AccessControlAllowOrigin::Value("http://parity.io".into())]))
+		.cors_policy(cors::CorsPolicy::default().allow_headers(vec!["Authorization"]).max_age(600))
+		.start_http(&"127.0.0.1:0".parse().unwrap())
+		.unwrap();
+
+	// when
+	let response = request(server,
+		Method::Options,
+		{
+			let mut headers = content_type_json();
+			headers.set(header::Origin::new("http", "parity.io", None));
+			headers.set_raw("Access-Control-Request-Headers", "content-type, authorization");
+			headers
+		},
+		"",
+	);
+
+	// then
+	assert_eq!(response.status, StatusCode::Ok);
+	assert_eq!(
+		response.headers.get_raw("Access-Control-Allow-Headers").and_then(|raw| raw.one()),
+		Some(&b"Origin, Content-Type, Accept, Authorization"[..])
+	);
+	assert_eq!(
+		response.headers.get_raw("Access-Control-Max-Age").and_then(|raw| raw.one()),
+		Some(&b"600"[..])
+	);
+}
+
 #[test]
 fn should_not_process_request_with_invalid_cors() {
 	// given
@@ -1,7 +1,8 @@
 use core;
 use core::futures::{Future, IntoFuture};
 
-use types::{PubSubMetadata, SubscriptionId};
+use types::{PubSubMetadata, SubscriptionId, SubscriptionError};
+use polling::{Polling, new_polling_subscription};
 use subscription::{Subscriber, new_subscription};
 
 /// Subscribe handler
@@ -41,12 +42,14 @@ impl<F, I> UnsubscribeRpcMethod for F where
 /// Publish-Subscribe extension of `IoHandler`.
 pub struct PubSubHandler<T: PubSubMetadata, S: core::Middleware<T> = core::NoopMiddleware> {
 	handler: core::MetaIoHandler<T, S>,
+	polling: Option<Polling>,
 }
 
 impl<T: PubSubMetadata> Default for PubSubHandler<T> {
 	fn default() -> Self {
 		PubSubHandler {
 			handler: Default::default(),
+			polling: None,
 		}
 	}
 }
@@ -56,10 +59,22 @@ impl<T: PubSubMetadata, S: core::Middleware<T>> PubSubHandler<T, S> {
 	pub fn new(handler: core::MetaIoHandler<T, S>) -> Self {
 		PubSubHandler {
 			handler: handler,
+			polling: None,
+		}
+	}
+
+	/// Creates new `PubSubHandler` with subscriptions available to clients without a session
+	/// (e.g. over HTTP). Such clients receive notifications by calling `<notification>_poll`
+	/// method with the subscription id.
+	pub fn with_polling(handler: core::MetaIoHandler<T, S>, polling: Polling) -> Self {
+		PubSubHandler {
+			handler: handler,
+			polling: Some(polling),
 		}
 	}
 
 	/// Adds new subscription.
+	/// If polling is enabled, `<notification>_poll` method is added as well.
 	pub fn add_subscription<F, G>(
 		&mut self,
 		notification: &str,
@@ -69,9 +84,23 @@ impl<T: PubSubMetadata, S: core::Middleware<T>> PubSubHandler<T, S> {
 		F: SubscribeRpcMethod<T>,
 		G: UnsubscribeRpcMethod,
 	{
+		if let Err(err) = self.handler.register_errors::<SubscriptionError>("pubsub") {
+			warn!("Unable to register subscription errors: {}", err);
+		}
+
 		let (sub, unsub) = new_subscription(notification, subscribe.1, unsubscribe.1);
-		self.handler.add_method_with_meta(subscribe.0, sub);
-		self.handler.add_method_with_meta(unsubscribe.0, unsub);
+		match self.polling {
+			Some(ref polling) => {
+				let (sub, unsub, poll) = new_polling_subscription(polling, notification, sub, unsub);
+				self.handler.add_method_with_meta(subscribe.0, sub);
+				self.handler.add_method_with_meta(unsubscribe.0, unsub);
+				self.handler.add_method_with_meta(&format!("{}_poll", notification), poll);
+			},
+			None => {
+				self.handler.add_method_with_meta(subscribe.0, sub);
+				self.handler.add_method_with_meta(unsubscribe.0, unsub);
+			},
+		}
 	}
 }
 
@@ -104,7 +133,7 @@ mod tests {
 	use core::futures::future;
 	use core::futures::sync::mpsc;
 	use subscription::{Session, Subscriber};
-	use types::{PubSubMetadata, SubscriptionId};
+	use types::{PubSubMetadata, SubscriptionId, SubscriptionError};
 
 	use super::PubSubHandler;
 
@@ -147,6 +176,7 @@ mod tests {
 		let response = r#"{"jsonrpc":"2.0","result":5,"id":1}"#;
 		assert_eq!(res, Some(response.into()));
 		assert_eq!(called.load(Ordering::SeqCst), true);
+		assert_eq!(handler.error_registry().get(-32091).map(|(module, _)| module), Some("pubsub"));
 	}
 
 }
@@ -4,14 +4,17 @@
 
 extern crate jsonrpc_core as core;
 extern crate parking_lot;
+extern crate rand;
 
 #[macro_use]
 extern crate log;
 
 mod handler;
+mod polling;
 mod subscription;
 mod types;
 
 pub use self::handler::{PubSubHandler, SubscribeRpcMethod, UnsubscribeRpcMethod};
-pub use self::subscription::{Session, Sink, Subscriber, new_subscription};
-pub use self::types::{PubSubMetadata, SubscriptionId, TransportError, SinkResult};
+pub use self::polling::{Polling, PollingSubscribe, PollingUnsubscribe, PollSubscription, new_polling_subscription};
+pub use self::subscription::{Session, Sink, Subscriber, Subscribe, Unsubscribe, new_subscription};
+pub use self::types::{PubSubMetadata, SubscriptionId, SubscriptionError, TransportError, SinkResult};
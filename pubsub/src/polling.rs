@@ -0,0 +1,482 @@
+//! Subscriptions polled by clients of transports without sessions (e.g. HTTP).
+
+use std::cmp;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Weak};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use parking_lot::{Condvar, Mutex};
+use rand::{self, Rng};
+
+use core::{self, BoxFuture};
+use core::futures::{executor, future, Async, Future, Poll, Stream};
+use core::futures::sync::mpsc;
+
+use handler::{SubscribeRpcMethod, UnsubscribeRpcMethod};
+use subscription::{Session, Subscribe, Unsubscribe, parse_id};
+use types::{PubSubMetadata, SubscriptionId};
+
+struct Buffered {
+	notification: String,
+	// Id returned by the subscribe method.
+	id: SubscriptionId,
+	// Dropping the session unsubscribes.
+	session: Arc<Session>,
+	notifications: mpsc::Receiver<String>,
+	buffered: VecDeque<String>,
+	limit: usize,
+	last_poll: Instant,
+}
+
+impl Buffered {
+	// Moves received notifications to the buffer, dropping the oldest ones if it's full.
+	// The receiver notifies given wakeup about further notifications.
+	fn receive(&mut self, wakeup: &Arc<Wakeup>) {
+		let _ = executor::spawn(future::poll_fn(|| -> Poll<(), ()> {
+			let mut dropped = 0;
+			while let Ok(Async::Ready(Some(message))) = self.notifications.poll() {
+				self.buffered.push_back(message);
+				if self.buffered.len() > self.limit {
+					self.buffered.pop_front();
+					dropped += 1;
+				}
+			}
+			if dropped > 0 {
+				debug!("Dropped {} notifications of {} not polled in time.", dropped, self.notification);
+			}
+			Ok(Async::Ready(()))
+		})).poll_future_notify(wakeup, 0);
+	}
+}
+
+type Subscriptions = Mutex<HashMap<String, Buffered>>;
+
+// Wakes the thread receiving notifications and expiring subscriptions.
+#[derive(Default)]
+struct Wakeup {
+	woken: Mutex<bool>,
+	condvar: Condvar,
+}
+
+impl executor::Notify for Wakeup {
+	fn notify(&self, _id: usize) {
+		*self.woken.lock() = true;
+		self.condvar.notify_one();
+	}
+}
+
+impl Wakeup {
+	fn wait(&self, timeout: Duration) {
+		let mut woken = self.woken.lock();
+		if !*woken {
+			self.condvar.wait_for(&mut woken, timeout);
+		}
+		*woken = false;
+	}
+}
+
+/// Subscriptions buffering notifications until they are polled (see `PubSubHandler::with_polling`).
+///
+/// Subscriptions made by clients without a session are kept on the server
+/// and the client gets a random id of the subscription instead of the one assigned by the subscriber.
+/// Notifications are returned (as an array of their params) by `<notification>_poll` method
+/// called with that id. Subscriptions not polled within the TTL are cancelled by a background thread
+/// (checking them every half of the TTL), which also receives the notifications in the meantime.
+#[derive(Clone)]
+pub struct Polling {
+	ttl: Duration,
+	buffer: usize,
+	subscriptions: Arc<Subscriptions>,
+	wakeup: Arc<Wakeup>,
+}
+
+impl Polling {
+	/// Creates polling with subscriptions expiring if not polled for given time.
+	pub fn new(ttl: Duration) -> Self {
+		let subscriptions = Arc::new(Subscriptions::default());
+		let wakeup = Arc::new(Wakeup::default());
+		let (weak, thread_wakeup) = (Arc::downgrade(&subscriptions), wakeup.clone());
+		let spawned = thread::Builder::new()
+			.name("jsonrpc-polling".into())
+			.spawn(move || run(weak, thread_wakeup, ttl));
+		if let Err(err) = spawned {
+			warn!("Unable to spawn thread expiring polled subscriptions: {:?}", err);
+		}
+
+		Polling {
+			ttl,
+			buffer: 1024,
+			subscriptions,
+			wakeup,
+		}
+	}
+
+	/// Maximal number of notifications kept between polls (1024 by default).
+	/// When the buffer is full the oldest notifications are dropped.
+	pub fn buffer(mut self, buffer: usize) -> Self {
+		self.buffer = buffer;
+		self
+	}
+
+	// Returns the id to poll the subscription with.
+	fn insert(&self, notification: String, id: SubscriptionId, session: Arc<Session>, notifications: mpsc::Receiver<String>) -> String {
+		let poll_id = poll_id();
+		self.subscriptions.lock().insert(poll_id.clone(), Buffered {
+			notification,
+			id,
+			session,
+			notifications,
+			buffered: VecDeque::new(),
+			limit: self.buffer,
+			last_poll: Instant::now(),
+		});
+		// Let the thread receive notifications of the new subscription.
+		executor::Notify::notify(&*self.wakeup, 0);
+		poll_id
+	}
+
+	// Returns the id assigned by the subscriber and the session of removed subscription.
+	fn remove(&self, notification: &str, poll_id: &str) -> Option<(SubscriptionId, Arc<Session>)> {
+		let mut subscriptions = self.subscriptions.lock();
+		match subscriptions.get(poll_id) {
+			Some(buffered) if buffered.notification == notification => {},
+			_ => return None,
+		}
+		subscriptions.remove(poll_id).map(|buffered| (buffered.id, buffered.session))
+	}
+
+	// Returns params of buffered notifications.
+	fn drain(&self, notification: &str, poll_id: &str) -> Option<Vec<core::Value>> {
+		let mut subscriptions = self.subscriptions.lock();
+		let buffered = match subscriptions.get_mut(poll_id) {
+			Some(buffered) => buffered,
+			None => return None,
+		};
+		if buffered.notification != notification {
+			return None;
+		}
+		buffered.last_poll = Instant::now();
+		buffered.receive(&self.wakeup);
+
+		let params = buffered.buffered.drain(..).filter_map(|message| {
+			match core::serde_json::from_str::<core::Value>(&message) {
+				Ok(mut notification) => Some(notification["params"].take()),
+				Err(err) => {
+					warn!("Invalid notification buffered: {:?}", err);
+					None
+				},
+			}
+		}).collect();
+		Some(params)
+	}
+
+	/// Cancels subscriptions not polled within the TTL.
+	/// It's called periodically, transports don't need to call it.
+	pub fn expire(&self) {
+		expire(&self.subscriptions, self.ttl)
+	}
+}
+
+fn expire(subscriptions: &Subscriptions, ttl: Duration) {
+	let expired = {
+		let mut subscriptions = subscriptions.lock();
+		let keys = subscriptions.iter()
+			.filter(|&(_, buffered)| buffered.last_poll.elapsed() > ttl)
+			.map(|(key, _)| key.clone())
+			.collect::<Vec<_>>();
+		keys.into_iter().filter_map(|key| subscriptions.remove(&key)).collect::<Vec<_>>()
+	};
+	// Unsubscribe without holding the lock.
+	drop(expired);
+}
+
+// Receives notifications when woken and expires subscriptions periodically,
+// until `Polling` is dropped.
+fn run(subscriptions: Weak<Subscriptions>, wakeup: Arc<Wakeup>, ttl: Duration) {
+	let interval = cmp::max(ttl / 2, Duration::from_millis(1));
+	let mut last_expire = Instant::now();
+	loop {
+		wakeup.wait(interval);
+		let subscriptions = match subscriptions.upgrade() {
+			Some(subscriptions) => subscriptions,
+			None => return,
+		};
+
+		for buffered in subscriptions.lock().values_mut() {
+			buffered.receive(&wakeup);
+		}
+		if last_expire.elapsed() >= interval {
+			expire(&subscriptions, ttl);
+			last_expire = Instant::now();
+		}
+	}
+}
+
+/// Creates subscribe, unsubscribe and poll RPC methods falling back to polling
+/// if the client has no session.
+pub fn new_polling_subscription<F, G>(
+	polling: &Polling,
+	notification: &str,
+	subscribe: Subscribe<F, G>,
+	unsubscribe: Unsubscribe<G>,
+) -> (PollingSubscribe<F, G>, PollingUnsubscribe<G>, PollSubscription) {
+	(
+		PollingSubscribe {
+			notification: notification.into(),
+			subscribe,
+			polling: polling.clone(),
+		},
+		PollingUnsubscribe {
+			notification: notification.into(),
+			unsubscribe,
+			polling: polling.clone(),
+		},
+		PollSubscription {
+			notification: notification.into(),
+			polling: polling.clone(),
+		},
+	)
+}
+
+/// Subscribe RPC implementation buffering notifications if the client has no session.
+pub struct PollingSubscribe<F, G> {
+	notification: String,
+	subscribe: Subscribe<F, G>,
+	polling: Polling,
+}
+
+impl<M, F, G> core::RpcMethod<M> for PollingSubscribe<F, G> where
+	M: PubSubMetadata,
+	F: SubscribeRpcMethod<M>,
+	G: UnsubscribeRpcMethod,
+{
+	fn call(&self, params: core::Params, meta: M) -> BoxFuture<core::Value> {
+		if meta.session().is_some() {
+			return core::RpcMethod::call(&self.subscribe, params, meta);
+		}
+
+		let (sender, notifications) = mpsc::channel(self.polling.buffer);
+		let session = Arc::new(Session::new(sender));
+		let polling = self.polling.clone();
+		let notification = self.notification.clone();
+		Box::new(self.subscribe.call_with_session(params, meta, session.clone()).map(move |id| {
+			match SubscriptionId::parse_value(&id) {
+				Some(id) => core::Value::String(polling.insert(notification, id, session, notifications)),
+				None => id,
+			}
+		}))
+	}
+}
+
+/// Unsubscribe RPC implementation cancelling polled subscriptions if the client has no session.
+pub struct PollingUnsubscribe<G> {
+	notification: String,
+	unsubscribe: Unsubscribe<G>,
+	polling: Polling,
+}
+
+impl<M, G> core::RpcMethod<M> for PollingUnsubscribe<G> where
+	M: PubSubMetadata,
+	G: UnsubscribeRpcMethod,
+{
+	fn call(&self, params: core::Params, meta: M) -> BoxFuture<core::Value> {
+		if meta.session().is_some() {
+			return core::RpcMethod::call(&self.unsubscribe, params, meta);
+		}
+
+		match parse_poll_id(&params).and_then(|poll_id| self.polling.remove(&self.notification, &poll_id)) {
+			Some((id, session)) => self.unsubscribe.call_with_session(core::Params::Array(vec![id.into()]), session),
+			None => Box::new(future::err(unknown_subscription())),
+		}
+	}
+}
+
+/// Poll RPC implementation, returns params of notifications sent since the last poll.
+pub struct PollSubscription {
+	notification: String,
+	polling: Polling,
+}
+
+impl<M: PubSubMetadata> core::RpcMethod<M> for PollSubscription {
+	fn call(&self, params: core::Params, _meta: M) -> BoxFuture<core::Value> {
+		let poll_id = match parse_id(&params) {
+			Some(SubscriptionId::String(poll_id)) => poll_id,
+			Some(_) => return Box::new(future::err(unknown_subscription())),
+			None => return Box::new(future::err(core::Error::invalid_params("Expected subscription id."))),
+		};
+
+		Box::new(future::result(match self.polling.drain(&self.notification, &poll_id) {
+			Some(params) => Ok(core::Value::Array(params)),
+			None => Err(unknown_subscription()),
+		}))
+	}
+}
+
+fn parse_poll_id(params: &core::Params) -> Option<String> {
+	match parse_id(params) {
+		Some(SubscriptionId::String(poll_id)) => Some(poll_id),
+		_ => None,
+	}
+}
+
+// Random, so subscriptions of other clients can't be polled or cancelled.
+fn poll_id() -> String {
+	let bytes: [u8; 16] = rand::thread_rng().gen();
+	bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn unknown_subscription() -> core::Error {
+	core::Error::invalid_params("Unknown or expired subscription.")
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+	use std::sync::atomic::{AtomicUsize, Ordering};
+	use std::thread;
+	use std::time::Duration;
+
+	use core;
+	use core::futures::{future, Future};
+	use subscription::{Session, Subscriber};
+	use types::{PubSubMetadata, SubscriptionId};
+	use handler::PubSubHandler;
+
+	use super::Polling;
+
+	#[derive(Clone, Default)]
+	struct Metadata;
+	impl core::Metadata for Metadata {}
+	impl PubSubMetadata for Metadata {
+		fn session(&self) -> Option<Arc<Session>> {
+			None
+		}
+	}
+
+	fn handler(ttl: Duration, unsubscribed: Arc<AtomicUsize>) -> PubSubHandler<Metadata> {
+		let mut handler = PubSubHandler::with_polling(core::MetaIoHandler::default(), Polling::new(ttl));
+		handler.add_subscription(
+			"hello",
+			("subscribe_hello", |_params, _meta, subscriber: Subscriber| {
+				let sink = subscriber.assign_id(SubscriptionId::Number(5)).unwrap();
+				thread::spawn(move || {
+					sink.notify(core::Params::Array(vec![core::Value::Number(1.into())])).wait().unwrap();
+					sink.notify(core::Params::Array(vec![core::Value::Number(2.into())])).wait().unwrap();
+				}).join().unwrap();
+			}),
+			("unsubscribe_hello", move |id| {
+				assert_eq!(id, SubscriptionId::Number(5));
+				unsubscribed.fetch_add(1, Ordering::SeqCst);
+				future::ok(core::Value::Bool(true))
+			}),
+		);
+		handler
+	}
+
+	// Subscribes and returns the id to poll with.
+	fn subscribe(handler: &PubSubHandler<Metadata>) -> String {
+		let subscribe = r#"{"jsonrpc":"2.0","id":1,"method":"subscribe_hello","params":[]}"#;
+		let res = handler.handle_request_sync(subscribe, Metadata).unwrap();
+		let res: core::Value = core::serde_json::from_str(&res).unwrap();
+		res["result"].as_str().expect("Subscription id returned.").to_owned()
+	}
+
+	fn call(handler: &PubSubHandler<Metadata>, method: &str, id: &str) -> Option<String> {
+		let request = format!(r#"{{"jsonrpc":"2.0","id":2,"method":"{}","params":["{}"]}}"#, method, id);
+		handler.handle_request_sync(&request, Metadata)
+	}
+
+	#[test]
+	fn should_buffer_notifications_until_polled() {
+		// given
+		let unsubscribed = Arc::new(AtomicUsize::new(0));
+		let handler = handler(Duration::from_secs(60), unsubscribed.clone());
+
+		// when
+		let id = subscribe(&handler);
+		let res1 = call(&handler, "hello_poll", &id);
+		let res2 = call(&handler, "hello_poll", &id);
+		let res3 = call(&handler, "unsubscribe_hello", &id);
+		let res4 = call(&handler, "hello_poll", &id);
+
+		// then
+		assert_eq!(id.len(), 32);
+		assert_eq!(res1, Some(r#"{"jsonrpc":"2.0","result":[[1],[2]],"id":2}"#.into()));
+		assert_eq!(res2, Some(r#"{"jsonrpc":"2.0","result":[],"id":2}"#.into()));
+		assert_eq!(res3, Some(r#"{"jsonrpc":"2.0","result":true,"id":2}"#.into()));
+		assert_eq!(res4, Some(r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Unknown or expired subscription."},"id":2}"#.into()));
+		assert_eq!(unsubscribed.load(Ordering::SeqCst), 1);
+	}
+
+	#[test]
+	fn should_not_poll_subscriptions_by_id_assigned_by_subscriber() {
+		// given
+		let unsubscribed = Arc::new(AtomicUsize::new(0));
+		let handler = handler(Duration::from_secs(60), unsubscribed.clone());
+		let poll = r#"{"jsonrpc":"2.0","id":2,"method":"hello_poll","params":[5]}"#;
+		let unsubscribe = r#"{"jsonrpc":"2.0","id":2,"method":"unsubscribe_hello","params":[5]}"#;
+		let id = subscribe(&handler);
+
+		// when
+		let res1 = handler.handle_request_sync(poll, Metadata);
+		let res2 = handler.handle_request_sync(unsubscribe, Metadata);
+
+		// then
+		let unknown = Some(r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Unknown or expired subscription."},"id":2}"#.into());
+		assert_eq!(res1, unknown);
+		assert_eq!(res2, unknown);
+		assert_eq!(unsubscribed.load(Ordering::SeqCst), 0);
+		assert_eq!(call(&handler, "hello_poll", &id), Some(r#"{"jsonrpc":"2.0","result":[[1],[2]],"id":2}"#.into()));
+	}
+
+	#[test]
+	fn should_unsubscribe_when_subscription_expires() {
+		// given
+		let unsubscribed = Arc::new(AtomicUsize::new(0));
+		let handler = handler(Duration::from_millis(10), unsubscribed.clone());
+		let id = subscribe(&handler);
+
+		// when
+		thread::sleep(Duration::from_millis(50));
+		let unsubscribed = unsubscribed.load(Ordering::SeqCst);
+		let res = call(&handler, "hello_poll", &id);
+
+		// then
+		assert_eq!(unsubscribed, 1);
+		assert_eq!(res, Some(r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Unknown or expired subscription."},"id":2}"#.into()));
+	}
+
+	#[test]
+	fn should_drop_oldest_notifications_instead_of_blocking() {
+		use std::sync::mpsc;
+
+		// given
+		let (sent_tx, sent) = mpsc::channel();
+		let sent_tx = ::parking_lot::Mutex::new(sent_tx);
+		let mut handler = PubSubHandler::with_polling(core::MetaIoHandler::default(), Polling::new(Duration::from_secs(60)).buffer(2));
+		handler.add_subscription(
+			"hello",
+			("subscribe_hello", move |_params, _meta: Metadata, subscriber: Subscriber| {
+				let sink = subscriber.assign_id(SubscriptionId::Number(5)).unwrap();
+				let sent = sent_tx.lock().clone();
+				thread::spawn(move || {
+					for i in 0..10 {
+						sink.notify(core::Params::Array(vec![core::Value::Number(i.into())])).wait().unwrap();
+					}
+					sent.send(()).unwrap();
+				});
+			}),
+			("unsubscribe_hello", |_id| future::ok(core::Value::Bool(true))),
+		);
+		let id = subscribe(&handler);
+
+		// when
+		let sent = sent.recv_timeout(Duration::from_secs(5));
+		let res = call(&handler, "hello_poll", &id);
+
+		// then
+		assert_eq!(sent, Ok(()));
+		assert_eq!(res, Some(r#"{"jsonrpc":"2.0","result":[[8],[9]],"id":2}"#.into()));
+	}
+}
@@ -5,12 +5,12 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use parking_lot::Mutex;
 
-use core::{self, BoxFuture};
+use core::{self, BoxFuture, IntoRpcError};
 use core::futures::{self, future, Sink as FuturesSink, Future};
 use core::futures::sync::{mpsc, oneshot};
 
 use handler::{SubscribeRpcMethod, UnsubscribeRpcMethod};
-use types::{PubSubMetadata, SubscriptionId, TransportSender, TransportError, SinkResult};
+use types::{PubSubMetadata, SubscriptionId, SubscriptionError, TransportSender, TransportError, SinkResult};
 
 /// RPC client session
 /// Keeps track of active subscriptions and unsubscribes from them upon dropping.
@@ -201,19 +201,11 @@ pub fn new_subscription<M, F, G>(notification: &str, subscribe: F, unsubscribe:
 }
 
 fn subscription_rejected() -> core::Error {
-	core::Error {
-		code: core::ErrorCode::ServerError(-32091),
-		message: "Subscription rejected".into(),
-		data: None,
-	}
+	SubscriptionError::Rejected.into_rpc_error()
 }
 
 fn subscriptions_unavailable() -> core::Error {
-	core::Error {
-		code: core::ErrorCode::ServerError(-32090),
-		message: "Subscriptions are not available on this transport.".into(),
-		data: None,
-	}
+	SubscriptionError::Unavailable.into_rpc_error()
 }
 
 /// Subscribe RPC implementation.
@@ -223,6 +215,42 @@ pub struct Subscribe<F, G> {
 	unsubscribe: Arc<G>,
 }
 
+impl<F, G> Subscribe<F, G> {
+	/// Subscribes using given session instead of the one returned by metadata.
+	pub fn call_with_session<M>(&self, params: core::Params, meta: M, session: Arc<Session>) -> BoxFuture<core::Value> where
+		M: PubSubMetadata,
+		F: SubscribeRpcMethod<M>,
+		G: UnsubscribeRpcMethod,
+	{
+		let (tx, rx) = oneshot::channel();
+
+		// Register the subscription
+		let subscriber = Subscriber {
+			notification: self.notification.clone(),
+			transport: session.sender(),
+			sender: tx,
+		};
+		self.subscribe.call(params, meta, subscriber);
+
+		let unsub = self.unsubscribe.clone();
+		let notification = self.notification.clone();
+		let subscribe_future = rx
+			.map_err(|_| subscription_rejected())
+			.and_then(move |result| {
+				futures::done(match result {
+					Ok(id) => {
+						session.add_subscription(&notification, &id, move |id| {
+							let _ = unsub.call(id).wait();
+						});
+						Ok(id.into())
+					},
+					Err(e) => Err(e),
+				})
+			});
+		Box::new(subscribe_future)
+	}
+}
+
 impl<M, F, G> core::RpcMethod<M> for Subscribe<F, G> where
 	M: PubSubMetadata,
 	F: SubscribeRpcMethod<M>,
@@ -230,34 +258,7 @@ impl<M, F, G> core::RpcMethod<M> for Subscribe<F, G> where
 {
 	fn call(&self, params: core::Params, meta: M) -> BoxFuture<core::Value> {
 		match meta.session() {
-			Some(session) => {
-				let (tx, rx) = oneshot::channel();
-
-				// Register the subscription
-				let subscriber = Subscriber {
-					notification: self.notification.clone(),
-					transport: session.sender(),
-					sender: tx,
-				};
-				self.subscribe.call(params, meta, subscriber);
-
-				let unsub = self.unsubscribe.clone();
-				let notification = self.notification.clone();
-				let subscribe_future = rx
-					.map_err(|_| subscription_rejected())
-					.and_then(move |result| {
-						futures::done(match result {
-							Ok(id) => {
-								session.add_subscription(&notification, &id, move |id| {
-									let _ = unsub.call(id).wait();
-								});
-								Ok(id.into())
-							},
-							Err(e) => Err(e),
-						})
-					});
-				Box::new(subscribe_future)
-			},
+			Some(session) => self.call_with_session(params, meta, session),
 			None => Box::new(future::err(subscriptions_unavailable())),
 		}
 	}
@@ -269,28 +270,41 @@ pub struct Unsubscribe<G> {
 	unsubscribe: Arc<G>,
 }
 
+impl<G: UnsubscribeRpcMethod> Unsubscribe<G> {
+	/// Cancels the subscription of given session instead of the one returned by metadata.
+	pub fn call_with_session(&self, params: core::Params, session: Arc<Session>) -> BoxFuture<core::Value> {
+		match parse_id(&params) {
+			Some(id) => {
+				session.remove_subscription(&self.notification, &id);
+				Box::new(self.unsubscribe.call(id))
+			},
+			None => Box::new(future::err(core::Error::invalid_params("Expected subscription id."))),
+		}
+	}
+}
+
 impl<M, G> core::RpcMethod<M> for Unsubscribe<G> where
 	M: PubSubMetadata,
 	G: UnsubscribeRpcMethod,
 {
 	fn call(&self, params: core::Params, meta: M) -> BoxFuture<core::Value> {
-		let id = match params {
-			core::Params::Array(ref vec) if vec.len() == 1 => {
-				SubscriptionId::parse_value(&vec[0])
-			},
-			_ => None,
-		};
-		match (meta.session(), id) {
-			(Some(session), Some(id)) => {
-				session.remove_subscription(&self.notification, &id);
-				Box::new(self.unsubscribe.call(id))
-			},
-			(Some(_), None) => Box::new(future::err(core::Error::invalid_params("Expected subscription id."))),
-			_ => Box::new(future::err(subscriptions_unavailable())),
+		match meta.session() {
+			Some(session) => self.call_with_session(params, session),
+			None => Box::new(future::err(subscriptions_unavailable())),
 		}
 	}
 }
 
+/// Reads subscription id from params of unsubscribe or poll calls.
+pub fn parse_id(params: &core::Params) -> Option<SubscriptionId> {
+	match *params {
+		core::Params::Array(ref vec) if vec.len() == 1 => {
+			SubscriptionId::parse_value(&vec[0])
+		},
+		_ => None,
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use std::sync::Arc;
@@ -30,6 +30,37 @@ impl<T: PubSubMetadata> PubSubMetadata for Option<T> {
 	}
 }
 
+/// Errors returned by subscription methods.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubscriptionError {
+	/// Subscription was rejected by the handler.
+	Rejected,
+	/// Transport doesn't support sessions.
+	Unavailable,
+}
+
+impl core::IntoRpcError for SubscriptionError {
+	fn code(&self) -> core::ErrorCode {
+		match *self {
+			SubscriptionError::Rejected => core::ErrorCode::ServerError(-32091),
+			SubscriptionError::Unavailable => core::ErrorCode::ServerError(-32090),
+		}
+	}
+
+	fn message(&self) -> String {
+		match *self {
+			SubscriptionError::Rejected => "Subscription rejected".into(),
+			SubscriptionError::Unavailable => "Subscriptions are not available on this transport.".into(),
+		}
+	}
+
+	fn error_codes() -> Vec<core::ErrorCodeInfo> {
+		[SubscriptionError::Rejected, SubscriptionError::Unavailable].iter()
+			.map(|error| core::ErrorCodeInfo::new(error.code().code(), format!("{:?}", error), error.message()))
+			.collect()
+	}
+}
+
 /// Unique subscription id.
 /// NOTE Assigning same id to different requests will cause the previous request to be unsubscribed.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
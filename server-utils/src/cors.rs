@@ -209,10 +209,130 @@ pub fn get_cors_header(origin: Option<&str>, host: Option<&str>, allowed: &Optio
 }
 
 
+/// Headers which can be sent by clients of allowed origins.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AccessControlAllowHeaders {
+	/// Only headers in the list.
+	Only(Vec<String>),
+	/// Any headers, `Access-Control-Request-Headers` of the preflight request are echoed.
+	Any,
+}
+
+/// CORS headers (other than `Access-Control-Allow-Origin`) sent in responses to allowed origins.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CorsPolicy {
+	allowed_headers: AccessControlAllowHeaders,
+	exposed_headers: Vec<String>,
+	max_age: Option<u32>,
+	allow_credentials: bool,
+}
+
+impl Default for CorsPolicy {
+	fn default() -> Self {
+		CorsPolicy {
+			allowed_headers: AccessControlAllowHeaders::Only(vec![
+				"Origin".into(),
+				"Content-Type".into(),
+				"Accept".into(),
+			]),
+			exposed_headers: Vec::new(),
+			max_age: None,
+			allow_credentials: false,
+		}
+	}
+}
+
+impl CorsPolicy {
+	/// Allows clients to send given headers (e.g. `Authorization`)
+	/// in addition to `Origin`, `Content-Type` and `Accept`.
+	/// Has no effect if any headers are already allowed (see `allow_any_headers`).
+	pub fn allow_headers<T: Into<String>>(mut self, headers: Vec<T>) -> Self {
+		if let AccessControlAllowHeaders::Only(ref mut allowed) = self.allowed_headers {
+			for header in headers {
+				let header = header.into();
+				if !allowed.iter().any(|allowed| allowed.eq_ignore_ascii_case(&header)) {
+					allowed.push(header);
+				}
+			}
+		}
+		self
+	}
+
+	/// Allows clients to send any headers.
+	pub fn allow_any_headers(mut self) -> Self {
+		self.allowed_headers = AccessControlAllowHeaders::Any;
+		self
+	}
+
+	/// Allows clients to read given response headers (`Access-Control-Expose-Headers`).
+	pub fn expose_headers<T: Into<String>>(mut self, headers: Vec<T>) -> Self {
+		self.exposed_headers.extend(headers.into_iter().map(Into::into));
+		self
+	}
+
+	/// Allows clients to cache results of preflight requests for given number of seconds
+	/// (`Access-Control-Max-Age`).
+	pub fn max_age(mut self, seconds: u32) -> Self {
+		self.max_age = Some(seconds);
+		self
+	}
+
+	/// Allows requests with credentials, e.g. cookies (`Access-Control-Allow-Credentials`).
+	/// Browsers reject credentialed responses allowing any origin (`*`),
+	/// so the origin of the request is sent instead (see `CorsPolicy::allowed_origin`).
+	/// Disabled by default.
+	pub fn allow_credentials(mut self, allow: bool) -> Self {
+		self.allow_credentials = allow;
+		self
+	}
+
+	/// Returns the value of `Access-Control-Allow-Origin` header for allowed origin.
+	/// `origin` is the value of `Origin` header of the request.
+	pub fn allowed_origin(&self, allowed: AccessControlAllowOrigin, origin: Option<&str>) -> AccessControlAllowOrigin {
+		match (allowed, origin) {
+			(AccessControlAllowOrigin::Any, Some("null")) if self.allow_credentials => AccessControlAllowOrigin::Null,
+			(AccessControlAllowOrigin::Any, Some(origin)) if self.allow_credentials => {
+				AccessControlAllowOrigin::Value(Origin::parse(origin))
+			},
+			(allowed, _) => allowed,
+		}
+	}
+
+	/// Returns `true` if the headers depend on `Access-Control-Request-Headers` of the request,
+	/// so it should be listed in `Vary` header of the response.
+	pub fn varies_with_request_headers(&self) -> bool {
+		self.allowed_headers == AccessControlAllowHeaders::Any
+	}
+
+	/// Returns names and values of headers to add to the response.
+	/// `requested_headers` is the value of `Access-Control-Request-Headers` header of the request.
+	pub fn headers(&self, is_preflight: bool, requested_headers: Option<&str>) -> Vec<(&'static str, String)> {
+		let mut headers = Vec::new();
+		match self.allowed_headers {
+			AccessControlAllowHeaders::Only(ref allowed) => {
+				headers.push(("Access-Control-Allow-Headers", allowed.join(", ")));
+			},
+			AccessControlAllowHeaders::Any => if let Some(requested) = requested_headers {
+				headers.push(("Access-Control-Allow-Headers", requested.to_owned()));
+			},
+		}
+		if !self.exposed_headers.is_empty() && !is_preflight {
+			headers.push(("Access-Control-Expose-Headers", self.exposed_headers.join(", ")));
+		}
+		if let (Some(max_age), true) = (self.max_age, is_preflight) {
+			headers.push(("Access-Control-Max-Age", max_age.to_string()));
+		}
+		if self.allow_credentials {
+			headers.push(("Access-Control-Allow-Credentials", "true".into()));
+		}
+		headers
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use hosts::Host;
-	use super::{get_cors_header, CorsHeader, AccessControlAllowOrigin, Origin, OriginProtocol};
+	use super::{get_cors_header, CorsHeader, CorsPolicy, AccessControlAllowOrigin, Origin, OriginProtocol};
 
 	#[test]
 	fn should_parse_origin() {
@@ -420,4 +540,88 @@ mod tests {
 		assert_eq!(res2, CorsHeader::Invalid);
 		assert_eq!(res3, CorsHeader::Ok(AccessControlAllowOrigin::Value("chrome-extension://test".into())));
 	}
+
+	#[test]
+	fn should_return_default_cors_policy_headers() {
+		// given
+		let policy = CorsPolicy::default();
+
+		// when
+		let res = policy.headers(true, Some("Authorization"));
+
+		// then
+		assert_eq!(res, vec![("Access-Control-Allow-Headers", "Origin, Content-Type, Accept".to_owned())]);
+	}
+
+	#[test]
+	fn should_return_configured_cors_policy_headers() {
+		// given
+		let policy = CorsPolicy::default()
+			.allow_headers(vec!["Authorization", "content-type"])
+			.expose_headers(vec!["Warning"])
+			.max_age(600)
+			.allow_credentials(true);
+
+		// when
+		let preflight = policy.headers(true, None);
+		let res = policy.headers(false, None);
+
+		// then
+		assert_eq!(preflight, vec![
+			("Access-Control-Allow-Headers", "Origin, Content-Type, Accept, Authorization".to_owned()),
+			("Access-Control-Max-Age", "600".to_owned()),
+			("Access-Control-Allow-Credentials", "true".to_owned()),
+		]);
+		assert_eq!(res, vec![
+			("Access-Control-Allow-Headers", "Origin, Content-Type, Accept, Authorization".to_owned()),
+			("Access-Control-Expose-Headers", "Warning".to_owned()),
+			("Access-Control-Allow-Credentials", "true".to_owned()),
+		]);
+	}
+
+	#[test]
+	fn should_echo_requested_headers() {
+		// given
+		let policy = CorsPolicy::default().allow_any_headers();
+
+		// when
+		let res1 = policy.headers(true, Some("Authorization, X-Custom"));
+		let res2 = policy.headers(false, None);
+
+		// then
+		assert_eq!(res1, vec![("Access-Control-Allow-Headers", "Authorization, X-Custom".to_owned())]);
+		assert_eq!(res2, vec![]);
+		assert!(policy.varies_with_request_headers());
+	}
+
+	#[test]
+	fn should_keep_allowing_any_headers() {
+		// given
+		let policy = CorsPolicy::default().allow_any_headers().allow_headers(vec!["Authorization"]);
+
+		// when
+		let res = policy.headers(true, Some("X-Custom"));
+
+		// then
+		assert_eq!(res, vec![("Access-Control-Allow-Headers", "X-Custom".to_owned())]);
+	}
+
+	#[test]
+	fn should_send_origin_of_request_instead_of_any_if_credentials_are_allowed() {
+		// given
+		let policy = CorsPolicy::default();
+		let credentials = CorsPolicy::default().allow_credentials(true);
+
+		// when
+		let res1 = policy.allowed_origin(AccessControlAllowOrigin::Any, Some("http://parity.io"));
+		let res2 = credentials.allowed_origin(AccessControlAllowOrigin::Any, Some("http://parity.io"));
+		let res3 = credentials.allowed_origin(AccessControlAllowOrigin::Any, Some("null"));
+		let res4 = credentials.allowed_origin(AccessControlAllowOrigin::Value("http://parity.io".into()), Some("http://parity.io"));
+
+		// then
+		assert_eq!(res1, AccessControlAllowOrigin::Any);
+		assert_eq!(res2, AccessControlAllowOrigin::Value("http://parity.io".into()));
+		assert_eq!(res3, AccessControlAllowOrigin::Null);
+		assert_eq!(res4, AccessControlAllowOrigin::Value("http://parity.io".into()));
+	}
 }
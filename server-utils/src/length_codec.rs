@@ -0,0 +1,119 @@
+use std::io;
+use tokio_io::codec::{Decoder, Encoder};
+use bytes::{BytesMut, BufMut};
+
+/// Size of the frame header (big-endian `u32` length of the payload).
+const HEADER_LEN: usize = 4;
+
+/// Codec for binary encoded messages (e.g. CBOR or MessagePack)
+/// prefixed with 4-byte big-endian length of the payload.
+#[derive(Debug, Clone)]
+pub struct LengthPrefixedCodec {
+	max_frame_len: usize,
+}
+
+impl Default for LengthPrefixedCodec {
+	fn default() -> Self {
+		LengthPrefixedCodec::new(16 * 1024 * 1024)
+	}
+}
+
+impl LengthPrefixedCodec {
+	/// New codec rejecting frames larger than `max_frame_len` bytes.
+	pub fn new(max_frame_len: usize) -> Self {
+		LengthPrefixedCodec {
+			max_frame_len: max_frame_len,
+		}
+	}
+
+	fn too_large(&self, len: usize) -> io::Error {
+		io::Error::new(
+			io::ErrorKind::InvalidData,
+			format!("Frame of {} bytes exceeds the limit of {} bytes", len, self.max_frame_len),
+		)
+	}
+}
+
+impl Decoder for LengthPrefixedCodec {
+	type Item = Vec<u8>;
+	type Error = io::Error;
+
+	fn decode(&mut self, buf: &mut BytesMut) -> io::Result<Option<Self::Item>> {
+		if buf.len() < HEADER_LEN {
+			return Ok(None);
+		}
+
+		let len = buf[..HEADER_LEN].iter().fold(0usize, |len, byte| len << 8 | *byte as usize);
+		if len > self.max_frame_len {
+			return Err(self.too_large(len));
+		}
+		if buf.len() < HEADER_LEN + len {
+			buf.reserve(HEADER_LEN + len - buf.len());
+			return Ok(None);
+		}
+
+		buf.split_to(HEADER_LEN);
+		Ok(Some(buf.split_to(len).to_vec()))
+	}
+}
+
+impl Encoder for LengthPrefixedCodec {
+	type Item = Vec<u8>;
+	type Error = io::Error;
+
+	fn encode(&mut self, msg: Vec<u8>, buf: &mut BytesMut) -> io::Result<()> {
+		if msg.len() > self.max_frame_len || msg.len() > u32::max_value() as usize {
+			return Err(self.too_large(msg.len()));
+		}
+
+		buf.reserve(HEADER_LEN + msg.len());
+		buf.put_u32_be(msg.len() as u32);
+		buf.put_slice(&msg);
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::LengthPrefixedCodec;
+	use tokio_io::codec::{Decoder, Encoder};
+	use bytes::{BytesMut, BufMut};
+
+	#[test]
+	fn should_encode_and_decode_frames() {
+		let mut codec = LengthPrefixedCodec::default();
+		let mut buf = BytesMut::with_capacity(64);
+
+		codec.encode(vec![0xa1, 0x01], &mut buf).unwrap();
+		codec.encode(vec![], &mut buf).unwrap();
+		assert_eq!(&buf[..], &[0, 0, 0, 2, 0xa1, 0x01, 0, 0, 0, 0][..]);
+
+		assert_eq!(codec.decode(&mut buf).unwrap(), Some(vec![0xa1, 0x01]));
+		assert_eq!(codec.decode(&mut buf).unwrap(), Some(vec![]));
+		assert_eq!(codec.decode(&mut buf).unwrap(), None);
+	}
+
+	#[test]
+	fn should_wait_for_fragmented_frame() {
+		let mut codec = LengthPrefixedCodec::default();
+		let mut buf = BytesMut::with_capacity(64);
+
+		buf.put_slice(&[0, 0, 0]);
+		assert_eq!(codec.decode(&mut buf).unwrap(), None);
+		buf.put_slice(&[3, 1, 2]);
+		assert_eq!(codec.decode(&mut buf).unwrap(), None);
+		buf.put_slice(&[3, 4]);
+		assert_eq!(codec.decode(&mut buf).unwrap(), Some(vec![1, 2, 3]));
+		assert_eq!(&buf[..], &[4][..]);
+	}
+
+	#[test]
+	fn should_reject_too_large_frames() {
+		let mut codec = LengthPrefixedCodec::new(2);
+		let mut buf = BytesMut::with_capacity(64);
+
+		assert!(codec.encode(vec![1, 2, 3], &mut buf).is_err());
+		buf.put_slice(&[0, 0, 0, 3, 1, 2, 3]);
+		assert!(codec.decode(&mut buf).is_err());
+	}
+}
@@ -14,15 +14,21 @@ pub extern crate tokio_io;
 
 pub mod cors;
 pub mod hosts;
+pub mod outbound;
+pub mod progress;
 pub mod session;
 pub mod reactor;
+mod length_codec;
 mod matcher;
+mod message_codec;
 mod stream_codec;
 
 pub use matcher::Pattern;
 
 /// Codecs utilities
 pub mod codecs {
+    pub use length_codec::LengthPrefixedCodec;
+    pub use message_codec::MessageCodec;
     pub use stream_codec::{StreamCodec, Separator};
 }
 
@@ -0,0 +1,88 @@
+use std::io;
+use std::sync::Arc;
+use tokio_io::codec::{Decoder, Encoder};
+use bytes::BytesMut;
+
+use core::{self, Encoding};
+use length_codec::LengthPrefixedCodec;
+use stream_codec::StreamCodec;
+
+/// Codec for messages of streaming protocols (ipc, tcp).
+///
+/// Messages are always passed to and from the server as JSON strings,
+/// binary encoded ones are converted when decoded and encoded.
+#[derive(Debug)]
+pub enum MessageCodec {
+	/// JSON messages separated with `Separator`s.
+	Stream(StreamCodec),
+	/// Messages serialized with given `Encoding` in length-prefixed frames.
+	Encoded(LengthPrefixedCodec, Arc<Encoding>),
+}
+
+impl Decoder for MessageCodec {
+	type Item = String;
+	type Error = io::Error;
+
+	fn decode(&mut self, buf: &mut BytesMut) -> io::Result<Option<Self::Item>> {
+		match *self {
+			MessageCodec::Stream(ref mut codec) => codec.decode(buf),
+			MessageCodec::Encoded(ref mut codec, ref encoding) => Ok(codec.decode(buf)?.map(|frame| {
+				match encoding.decode_message(&frame) {
+					Ok(message) => core::serde_json::to_string(&message).expect("Value serialization is infallible; qed"),
+					Err(_) => {
+						debug!("Unable to decode a message with {:?}", encoding);
+						// Handler responds to an empty message with parse error.
+						String::new()
+					},
+				}
+			})),
+		}
+	}
+}
+
+impl Encoder for MessageCodec {
+	type Item = String;
+	type Error = io::Error;
+
+	fn encode(&mut self, msg: String, buf: &mut BytesMut) -> io::Result<()> {
+		match *self {
+			MessageCodec::Stream(ref mut codec) => codec.encode(msg, buf),
+			// Requests without response are written as empty messages, no frame is needed for them.
+			MessageCodec::Encoded(_, _) if msg.is_empty() => Ok(()),
+			MessageCodec::Encoded(ref mut codec, ref encoding) => {
+				let message: core::Value = core::serde_json::from_str(&msg)
+					.map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+				codec.encode(encoding.encode_message(&message), buf)
+			},
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+	use tokio_io::codec::{Decoder, Encoder};
+	use bytes::{BytesMut, BufMut};
+	use core::encoding::Json;
+	use length_codec::LengthPrefixedCodec;
+	use super::MessageCodec;
+
+	#[test]
+	fn should_convert_encoded_messages() {
+		// given
+		let mut codec = MessageCodec::Encoded(LengthPrefixedCodec::default(), Arc::new(Json));
+		let mut buf = BytesMut::with_capacity(64);
+		buf.put_slice(b"\0\0\0\x0b{ \"id\": 1 }\0\0\0\x01{");
+
+		// when
+		let message = codec.decode(&mut buf).unwrap();
+		let invalid = codec.decode(&mut buf).unwrap();
+		codec.encode(r#"{"id":2}"#.into(), &mut buf).unwrap();
+		codec.encode(String::new(), &mut buf).unwrap();
+
+		// then
+		assert_eq!(message, Some(r#"{"id":1}"#.into()));
+		assert_eq!(invalid, Some(String::new()));
+		assert_eq!(&buf[..], &b"\0\0\0\x08{\"id\":2}"[..]);
+	}
+}
@@ -0,0 +1,219 @@
+//! Requests sent by the server to the client (bidirectional RPC).
+//!
+//! Every session gets its own `OutboundRequests` (available in transport's `RequestContext`).
+//! Responses coming from the client are intercepted by the server before they reach `MetaIoHandler`.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{atomic, Arc, Mutex};
+
+use core::{self, serde_json};
+use core::futures::{future, Future, Sink};
+use core::futures::sync::{mpsc, oneshot};
+
+/// Error of outbound request.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CallError {
+	/// Client responded with an error.
+	Rpc(core::Error),
+	/// Session was closed before the response was received.
+	Closed,
+}
+
+/// Future resolving to the result of outbound request.
+pub type CallFuture = Box<Future<Item = core::Value, Error = CallError> + Send>;
+
+type Pending = HashMap<core::Id, oneshot::Sender<core::Output>>;
+
+struct Inner {
+	sender: mpsc::Sender<String>,
+	next_id: atomic::AtomicUsize,
+	pending: Mutex<Pending>,
+}
+
+/// Outbound requests of a single session.
+#[derive(Clone)]
+pub struct OutboundRequests {
+	inner: Arc<Inner>,
+}
+
+impl core::Metadata for OutboundRequests {}
+
+impl fmt::Debug for OutboundRequests {
+	fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+		fmt.debug_struct("OutboundRequests")
+			.field("pending", &self.inner.pending.lock().expect("Lock is never poisoned; qed").len())
+			.finish()
+	}
+}
+
+impl OutboundRequests {
+	/// Creates new outbound requests writing to given transport channel.
+	pub fn new(sender: mpsc::Sender<String>) -> Self {
+		OutboundRequests {
+			inner: Arc::new(Inner {
+				sender: sender,
+				next_id: atomic::AtomicUsize::new(1),
+				pending: Default::default(),
+			}),
+		}
+	}
+
+	fn pending(&self) -> ::std::sync::MutexGuard<Pending> {
+		self.inner.pending.lock().expect("Lock is never poisoned; qed")
+	}
+
+	/// Calls a method on the client.
+	/// Returned future resolves when matching response is received.
+	pub fn call(&self, method: &str, params: core::Params) -> CallFuture {
+		let id = core::Id::Num(self.inner.next_id.fetch_add(1, atomic::Ordering::SeqCst) as u64);
+		let request = core::MethodCall {
+			jsonrpc: Some(core::Version::V2),
+			method: method.into(),
+			params: Some(params),
+			id: id.clone(),
+		};
+		let request = serde_json::to_string(&request).expect("Request serialization is infallible; qed");
+
+		let this = self.clone();
+		Box::new(future::lazy(move || {
+			let (tx, rx) = oneshot::channel();
+			this.pending().insert(id.clone(), tx);
+			let entry = PendingEntry { requests: this.clone(), id: id };
+
+			this.inner.sender.clone().send(request)
+				.map_err(|_| CallError::Closed)
+				.and_then(|_| rx.map_err(|_| CallError::Closed))
+				.and_then(|output| future::result(Into::<core::Result<core::Value>>::into(output)).map_err(CallError::Rpc))
+				// the entry lives as long as the call, so dropped calls don't leave it behind.
+				.then(move |result| {
+					drop(entry);
+					result
+				})
+		}))
+	}
+
+	/// Passes a message received from the client.
+	///
+	/// Returns `true` if the message is a response to pending requests (it's consumed then),
+	/// `false` if it should be handled as a regular request.
+	pub fn handle_message(&self, message: &str) -> bool {
+		// Spare parsing of every request if there is nothing to match.
+		if self.pending().is_empty() {
+			return false;
+		}
+
+		let outputs = match serde_json::from_str(message) {
+			Ok(core::Response::Single(output)) => vec![output],
+			Ok(core::Response::Batch(outputs)) => outputs,
+			Err(_) => return false,
+		};
+
+		let mut pending = self.pending();
+		if outputs.is_empty() || !outputs.iter().all(|output| pending.contains_key(output.id())) {
+			return false;
+		}
+
+		for output in outputs {
+			let tx = pending.remove(output.id()).expect("All ids are checked above; qed");
+			// the caller is not interested in the result any more.
+			let _ = tx.send(output);
+		}
+		true
+	}
+
+	/// Cancels all pending requests (to be used when the session is closed).
+	pub fn close(&self) {
+		let pending = ::std::mem::replace(&mut *self.pending(), Default::default());
+		if !pending.is_empty() {
+			debug!("Session closed with {} pending outbound requests.", pending.len());
+		}
+	}
+}
+
+// Removes the request from pending ones when dropped.
+struct PendingEntry {
+	requests: OutboundRequests,
+	id: core::Id,
+}
+
+impl Drop for PendingEntry {
+	fn drop(&mut self) {
+		self.requests.pending().remove(&self.id);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::thread;
+	use core::{Params, Value, Error};
+	use core::futures::{future, Future, Stream};
+	use core::futures::sync::mpsc;
+	use super::{CallError, OutboundRequests};
+
+	#[test]
+	fn should_resolve_calls_with_responses() {
+		// given
+		let (sender, receiver) = mpsc::channel(4);
+		let requests = OutboundRequests::new(sender);
+		let first = requests.call("status", Params::Array(vec![Value::from(1)]));
+		let second = requests.call("status", Params::None);
+		let calls = thread::spawn(move || first.then(Ok::<_, ()>).join(second.then(Ok)).wait());
+
+		// when
+		let mut receiver = receiver.wait();
+		let first_req = receiver.next();
+		let second_req = receiver.next();
+		assert!(!requests.handle_message(r#"{"jsonrpc":"2.0","method":"status","id":2}"#));
+		assert!(!requests.handle_message(r#"{"jsonrpc":"2.0","result":"ok","id":3}"#));
+		assert!(!requests.handle_message("[]"));
+		assert!(requests.handle_message(r#"{"jsonrpc":"2.0","result":"ok","id":1}"#));
+		assert!(!requests.handle_message(r#"{"jsonrpc":"2.0","result":"ok","id":1}"#));
+		assert!(requests.handle_message(r#"[{"jsonrpc":"2.0","error":{"code":-32601,"message":"Method not found"},"id":2}]"#));
+
+		// then
+		assert_eq!(first_req, Some(Ok(r#"{"jsonrpc":"2.0","method":"status","params":[1],"id":1}"#.into())));
+		assert_eq!(second_req, Some(Ok(r#"{"jsonrpc":"2.0","method":"status","params":[],"id":2}"#.into())));
+		assert_eq!(calls.join().unwrap(), Ok((
+			Ok(Value::String("ok".into())),
+			Err(CallError::Rpc(Error::method_not_found())),
+		)));
+	}
+
+	#[test]
+	fn should_cancel_pending_calls_on_close() {
+		// given
+		let (sender, receiver) = mpsc::channel(4);
+		let requests = OutboundRequests::new(sender);
+		let call = requests.call("status", Params::None);
+		let call = thread::spawn(move || call.wait());
+		let _request = receiver.wait().next();
+
+		// when
+		requests.close();
+
+		// then
+		assert_eq!(call.join().unwrap(), Err(CallError::Closed));
+	}
+
+	#[test]
+	fn should_forget_dropped_calls() {
+		// given
+		let (sender, receiver) = mpsc::channel(4);
+		let requests = OutboundRequests::new(sender);
+		let call = match requests.call("status", Params::None).select2(future::ok::<(), ()>(())).wait() {
+			Ok(future::Either::B((_, call))) => call,
+			_ => panic!("Call is pending until the response is received."),
+		};
+		let _request = receiver.wait().next();
+		let pending = format!("{:?}", requests);
+
+		// when
+		drop(call);
+
+		// then
+		assert_eq!(pending, "OutboundRequests { pending: 1 }");
+		assert_eq!(format!("{:?}", requests), "OutboundRequests { pending: 0 }");
+		assert!(!requests.handle_message(r#"{"jsonrpc":"2.0","result":"ok","id":1}"#));
+	}
+}
@@ -0,0 +1,200 @@
+//! Progress reporting for long-running methods.
+//!
+//! Works like `$/progress` of the Language Server Protocol: the client passes a token
+//! in `workDoneToken` field of the params and the server sends notifications referencing it.
+//! Progress is not reported if the token is missing or the transport doesn't support sessions (HTTP).
+
+use std::sync::{Arc, Mutex};
+
+use core::{self, serde_json, BoxFuture, Params, Value};
+use core::futures::IntoFuture;
+use core::futures::sync::mpsc;
+
+/// Name of progress notifications.
+pub const PROGRESS_METHOD: &str = "$/progress";
+
+/// Name of the params field carrying the progress token.
+pub const PROGRESS_TOKEN_FIELD: &str = "workDoneToken";
+
+/// Metadata giving access to the session of the call.
+pub trait ProgressMetadata: core::Metadata {
+	/// Channel to send notifications to the client.
+	/// `None` if the transport doesn't support sessions.
+	fn progress_sender(&self) -> Option<mpsc::Sender<String>>;
+}
+
+impl<T: ProgressMetadata> ProgressMetadata for Option<T> {
+	fn progress_sender(&self) -> Option<mpsc::Sender<String>> {
+		self.as_ref().and_then(|meta| meta.progress_sender())
+	}
+}
+
+/// Reports progress of a single call.
+///
+/// Clones of the reporter share the same sender, so they don't get any additional
+/// space in the channel.
+#[derive(Debug, Clone)]
+pub struct ProgressReporter {
+	target: Option<(Arc<Mutex<mpsc::Sender<String>>>, Value)>,
+}
+
+impl ProgressReporter {
+	/// Creates a reporter sending notifications with given token.
+	pub fn new(sender: mpsc::Sender<String>, token: Value) -> Self {
+		ProgressReporter {
+			target: Some((Arc::new(Mutex::new(sender)), token)),
+		}
+	}
+
+	/// Creates a reporter ignoring all updates.
+	pub fn noop() -> Self {
+		ProgressReporter {
+			target: None,
+		}
+	}
+
+	/// Creates a reporter for the call with given params.
+	/// Updates are ignored if there is no token in the params or no session.
+	pub fn from_params(params: &Params, sender: Option<mpsc::Sender<String>>) -> Self {
+		let token = match *params {
+			Params::Map(ref map) => map.get(PROGRESS_TOKEN_FIELD).cloned(),
+			_ => None,
+		};
+
+		match (sender, token) {
+			(Some(sender), Some(token)) => ProgressReporter::new(sender, token),
+			_ => ProgressReporter::noop(),
+		}
+	}
+
+	/// Returns `true` if the updates are delivered to the client.
+	pub fn is_enabled(&self) -> bool {
+		self.target.is_some()
+	}
+
+	/// Sends progress update (e.g. `{"kind": "report", "percentage": 50}`).
+	/// Returns `false` if the update was ignored, the session is closed
+	/// or the client doesn't keep up with the updates (the channel is full and the update is dropped).
+	pub fn report(&self, value: Value) -> bool {
+		let (sender, token) = match self.target {
+			Some((ref sender, ref token)) => (sender, token),
+			None => return false,
+		};
+
+		let mut params = serde_json::Map::new();
+		params.insert("token".into(), token.clone());
+		params.insert("value".into(), value);
+		let notification = core::Notification {
+			jsonrpc: Some(core::Version::V2),
+			method: PROGRESS_METHOD.into(),
+			params: Some(Params::Map(params)),
+		};
+		let notification = serde_json::to_string(&notification).expect("Notification serialization is infallible; qed");
+
+		let mut sender = sender.lock().expect("Sender is never used while panicking; qed");
+		sender.try_send(notification).is_ok()
+	}
+}
+
+/// Method receiving a reporter of its progress.
+pub struct WithProgress<F> {
+	method: F,
+}
+
+impl<F> WithProgress<F> {
+	/// Wraps given closure.
+	pub fn new(method: F) -> Self {
+		WithProgress { method }
+	}
+}
+
+impl<M, F, I> core::RpcMethod<M> for WithProgress<F> where
+	M: ProgressMetadata,
+	F: Fn(Params, M, ProgressReporter) -> I + Send + Sync + 'static,
+	I: IntoFuture<Item = Value, Error = core::Error>,
+	I::Future: Send + 'static,
+{
+	fn call(&self, params: Params, meta: M) -> BoxFuture<Value> {
+		let progress = ProgressReporter::from_params(&params, meta.progress_sender());
+		Box::new((self.method)(params, meta, progress).into_future())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use core::{serde_json, MetaIoHandler, Metadata, Params, Value};
+	use core::futures::{Future, Stream};
+	use core::futures::sync::mpsc;
+	use super::{ProgressMetadata, ProgressReporter, WithProgress};
+
+	#[derive(Clone, Default)]
+	struct Session(Option<mpsc::Sender<String>>);
+	impl Metadata for Session {}
+	impl ProgressMetadata for Session {
+		fn progress_sender(&self) -> Option<mpsc::Sender<String>> {
+			self.0.clone()
+		}
+	}
+
+	fn io() -> MetaIoHandler<Session> {
+		let mut io = MetaIoHandler::default();
+		io.add_method_with_meta("reindex", WithProgress::new(|_params, _meta, progress: ProgressReporter| {
+			progress.report(serde_json::from_str(r#"{"kind":"begin","title":"Reindexing"}"#).unwrap());
+			progress.report(serde_json::from_str(r#"{"kind":"end"}"#).unwrap());
+			Ok(Value::Bool(progress.is_enabled()))
+		}));
+		io
+	}
+
+	#[test]
+	fn should_report_progress_with_client_token() {
+		// given
+		let (sender, receiver) = mpsc::channel(1);
+		let request = r#"{"jsonrpc":"2.0","method":"reindex","params":{"workDoneToken":"t1"},"id":1}"#;
+
+		// when
+		let response = io().handle_request_sync(request, Session(Some(sender)));
+
+		// then
+		assert_eq!(response, Some(r#"{"jsonrpc":"2.0","result":true,"id":1}"#.into()));
+		assert_eq!(receiver.collect().wait(), Ok(vec![
+			r#"{"jsonrpc":"2.0","method":"$/progress","params":{"token":"t1","value":{"kind":"begin","title":"Reindexing"}}}"#.to_owned(),
+			r#"{"jsonrpc":"2.0","method":"$/progress","params":{"token":"t1","value":{"kind":"end"}}}"#.to_owned(),
+		]));
+	}
+
+	#[test]
+	fn should_drop_updates_if_client_doesnt_keep_up() {
+		// given
+		let (sender, receiver) = mpsc::channel(1);
+		let mut io = MetaIoHandler::default();
+		io.add_method_with_meta("reindex", WithProgress::new(|_params, _meta, progress: ProgressReporter| {
+			let delivered = (0..5).filter(|_| progress.clone().report(Value::Null)).count();
+			Ok(Value::from(delivered))
+		}));
+		let request = r#"{"jsonrpc":"2.0","method":"reindex","params":{"workDoneToken":"t1"},"id":1}"#;
+
+		// when
+		let response = io.handle_request_sync(request, Session(Some(sender)));
+
+		// then
+		assert_eq!(response, Some(r#"{"jsonrpc":"2.0","result":2,"id":1}"#.into()));
+		assert_eq!(receiver.collect().wait().map(|updates| updates.len()), Ok(2));
+	}
+
+	#[test]
+	fn should_ignore_progress_without_token_or_session() {
+		// given
+		let (sender, receiver) = mpsc::channel(1);
+
+		// when
+		let no_token = io().handle_request_sync(r#"{"jsonrpc":"2.0","method":"reindex","params":{},"id":1}"#, Session(Some(sender)));
+		let no_session = io().handle_request_sync(r#"{"jsonrpc":"2.0","method":"reindex","params":{"workDoneToken":1},"id":1}"#, Session(None));
+
+		// then
+		assert_eq!(no_token, Some(r#"{"jsonrpc":"2.0","result":false,"id":1}"#.into()));
+		assert_eq!(no_session, Some(r#"{"jsonrpc":"2.0","result":false,"id":1}"#.into()));
+		assert_eq!(receiver.collect().wait(), Ok(vec![]));
+		assert!(!ProgressReporter::from_params(&Params::None, None).report(Value::Null));
+	}
+}
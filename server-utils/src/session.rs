@@ -1,4 +1,11 @@
-//! Session statistics.
+//! Session statistics, execution and output.
+
+use std::io;
+use std::collections::VecDeque;
+
+use core;
+use core::futures::{future, Async, Future, IntoFuture, Poll, Sink, Stream};
+use core::futures::sync::mpsc;
 
 /// Session id
 pub type SessionId = u64;
@@ -10,3 +17,187 @@ pub trait SessionStats: Send + Sync + 'static {
 	/// Executed when session is closed.
 	fn close_session(&self, id: SessionId);
 }
+
+/// Order of execution of requests received within a single session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Execution {
+	/// Requests are processed concurrently and responses are sent as soon as they are ready,
+	/// so responses (and side effects of the calls) may be reordered.
+	Concurrent,
+	/// Requests are processed one by one (strict FIFO): a request is dispatched only
+	/// after the response to the previous one is sent. Useful for stateful protocols.
+	Ordered,
+}
+
+/// Responses to requests merged with messages pushed to the session
+/// (notifications, chunks of streamed results, requests to the client).
+///
+/// Messages pushed before a response is ready are always sent first.
+/// The queue ends together with the responses (after sending the messages that are already queued).
+#[derive(Debug)]
+pub struct MessageQueue<S> {
+	responses: S,
+	messages: mpsc::Receiver<String>,
+	response: Option<String>,
+	done: bool,
+}
+
+impl<S> MessageQueue<S> {
+	/// Creates a new queue given stream of responses and receiver of session messages.
+	pub fn new(responses: S, messages: mpsc::Receiver<String>) -> Self {
+		MessageQueue {
+			responses: responses,
+			messages: messages,
+			response: None,
+			done: false,
+		}
+	}
+}
+
+impl<S: Stream<Item = String, Error = io::Error>> Stream for MessageQueue<S> {
+	type Item = String;
+	type Error = io::Error;
+
+	fn poll(&mut self) -> Poll<Option<String>, io::Error> {
+		if self.response.is_none() && !self.done {
+			match self.responses.poll()? {
+				Async::Ready(Some(response)) => self.response = Some(response),
+				Async::Ready(None) => self.done = true,
+				Async::NotReady => {},
+			}
+		}
+
+		// Receiver never fails, `Ready(None)` means there are no senders left.
+		if let Ok(Async::Ready(Some(message))) = self.messages.poll() {
+			return Ok(Async::Ready(Some(message)));
+		}
+
+		match self.response.take() {
+			Some(response) => Ok(Async::Ready(Some(response))),
+			None if self.done => Ok(Async::Ready(None)),
+			None => Ok(Async::NotReady),
+		}
+	}
+}
+
+/// Responses to requests processed one by one (see `Execution::Ordered`).
+///
+/// Up to `read_ahead` requests are read while the previous ones are processed,
+/// so that a call waiting for a response from the client (filtered out from the requests)
+/// does not block reading it.
+pub struct OrderedCalls<S: Stream, F, R: IntoFuture> {
+	requests: S,
+	call: F,
+	queue: VecDeque<S::Item>,
+	pending: Option<R::Future>,
+	read_ahead: usize,
+	done: bool,
+}
+
+impl<S: Stream, F, R: IntoFuture> OrderedCalls<S, F, R> {
+	/// Creates a new stream of responses given stream of requests and a function processing them.
+	pub fn new(requests: S, read_ahead: usize, call: F) -> Self {
+		OrderedCalls {
+			requests: requests,
+			call: call,
+			queue: VecDeque::new(),
+			pending: None,
+			read_ahead: read_ahead,
+			done: false,
+		}
+	}
+}
+
+impl<S, F, R> Stream for OrderedCalls<S, F, R> where
+	S: Stream,
+	F: FnMut(S::Item) -> R,
+	R: IntoFuture<Error = S::Error>,
+{
+	type Item = R::Item;
+	type Error = S::Error;
+
+	fn poll(&mut self) -> Poll<Option<R::Item>, S::Error> {
+		while !self.done && self.queue.len() < self.read_ahead {
+			match self.requests.poll()? {
+				Async::Ready(Some(request)) => self.queue.push_back(request),
+				Async::Ready(None) => self.done = true,
+				Async::NotReady => break,
+			}
+		}
+
+		if self.pending.is_none() {
+			match self.queue.pop_front() {
+				Some(request) => self.pending = Some((self.call)(request).into_future()),
+				None if self.done => return Ok(Async::Ready(None)),
+				None => return Ok(Async::NotReady),
+			}
+		}
+
+		match self.pending.as_mut().expect("Pending call is set above; qed").poll()? {
+			Async::Ready(response) => {
+				self.pending = None;
+				Ok(Async::Ready(Some(response)))
+			},
+			Async::NotReady => Ok(Async::NotReady),
+		}
+	}
+}
+
+/// Handles a call to a streaming method over a session.
+/// Chunks of results are pushed to the session (see `MessageQueue`)
+/// and the returned future resolves to the response.
+pub fn push_chunks(messages: core::ResponseStream, sender: mpsc::Sender<String>) -> Box<Future<Item = Option<String>, Error = ()> + Send> {
+	Box::new(messages.fold(None, move |previous, message| match previous {
+		Some(chunk) => future::Either::A(sender.clone().send(chunk).map(|_| Some(message)).map_err(|_| ())),
+		None => future::Either::B(future::ok(Some(message))),
+	}))
+}
+
+#[cfg(test)]
+mod tests {
+	use std::io;
+	use core::futures::{future, stream, Async, Future, Sink, Stream};
+	use core::futures::sync::{mpsc, oneshot};
+	use super::{push_chunks, MessageQueue, OrderedCalls};
+
+	#[test]
+	fn should_send_pushed_messages_before_responses() {
+		// given
+		let (sender, receiver) = mpsc::channel(4);
+		let chunks = stream::iter_ok(vec!["chunk1".to_owned(), "chunk2".to_owned(), "response".to_owned()]);
+		let response = push_chunks(Box::new(chunks), sender.clone()).wait().unwrap();
+		let _sender = sender.send("notification".into()).wait().unwrap();
+
+		// when
+		let responses = stream::iter_ok::<_, io::Error>(response.into_iter().chain(Some("other".to_owned())));
+		let messages = MessageQueue::new(responses, receiver).collect().wait().unwrap();
+
+		// then
+		assert_eq!(messages, vec!["chunk1", "chunk2", "notification", "response", "other"]);
+	}
+
+	#[test]
+	fn should_read_requests_ahead_of_pending_call() {
+		// given
+		let (tx, rx) = oneshot::channel::<String>();
+		let (requests, received) = mpsc::unbounded();
+		requests.unbounded_send("first").unwrap();
+		requests.unbounded_send("second").unwrap();
+		let mut rx = Some(rx);
+		let mut responses = OrderedCalls::new(received, 2, move |request: &'static str| match rx.take() {
+			Some(rx) => future::Either::A(rx.map_err(|_| ())),
+			None => future::Either::B(future::ok::<_, ()>(request.to_owned())),
+		});
+
+		// when
+		let pending = future::poll_fn(|| Ok::<_, ()>(Async::Ready(responses.poll()))).wait();
+		let read_ahead = responses.queue.len();
+		tx.send("response".into()).unwrap();
+		drop(requests);
+
+		// then
+		assert_eq!(pending, Ok(Ok(Async::NotReady)));
+		assert_eq!(read_ahead, 1);
+		assert_eq!(responses.collect().wait(), Ok(vec!["response".to_owned(), "second".to_owned()]));
+	}
+}
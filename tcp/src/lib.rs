@@ -47,3 +47,6 @@ pub use dispatch::{Dispatcher, PushMessageError};
 pub use meta::{MetaExtractor, RequestContext};
 pub use server::{ServerBuilder, Server};
 pub use self::server_utils::tokio_core;
+pub use self::server_utils::session::Execution;
+pub use self::server_utils::outbound::{OutboundRequests, CallError, CallFuture};
+pub use self::server_utils::progress::{ProgressMetadata, ProgressReporter, WithProgress};
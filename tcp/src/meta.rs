@@ -2,6 +2,7 @@ use std::net::SocketAddr;
 
 use jsonrpc::futures::sync::mpsc;
 use jsonrpc::Metadata;
+use server_utils::outbound::OutboundRequests;
 
 /// Request context
 pub struct RequestContext {
@@ -9,6 +10,8 @@ pub struct RequestContext {
 	pub peer_addr: SocketAddr,
 	/// Peer Sender channel
 	pub sender: mpsc::Sender<String>,
+	/// Requests to the peer (responses are matched by id)
+	pub outbound: OutboundRequests,
 }
 
 /// Metadata extractor (per session)
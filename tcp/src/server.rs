@@ -4,13 +4,15 @@ use std::sync::Arc;
 
 use tokio_service::Service as TokioService;
 
-use jsonrpc::{MetaIoHandler, Metadata, Middleware, NoopMiddleware};
+use jsonrpc::{Encoding, MetaIoHandler, Metadata, Middleware, NoopMiddleware};
 use jsonrpc::futures::{future, Future, Stream, Sink};
 use jsonrpc::futures::sync::{mpsc, oneshot};
 use server_utils::{reactor, tokio_core, codecs};
+use server_utils::outbound::OutboundRequests;
+use server_utils::session::{Execution, MessageQueue, OrderedCalls};
 use server_utils::tokio_io::AsyncRead;
 
-use dispatch::{Dispatcher, SenderChannels, PeerMessageQueue};
+use dispatch::{Dispatcher, SenderChannels};
 use meta::{MetaExtractor, RequestContext, NoopExtractor};
 use service::Service;
 
@@ -22,6 +24,9 @@ pub struct ServerBuilder<M: Metadata = (), S: Middleware<M> = NoopMiddleware> {
 	channels: Arc<SenderChannels>,
 	incoming_separator: codecs::Separator,
 	outgoing_separator: codecs::Separator,
+	binary_encoding: Option<Arc<Encoding>>,
+	execution: Execution,
+	max_concurrent_requests: usize,
 }
 
 impl<M: Metadata + Default, S: Middleware<M> + 'static> ServerBuilder<M, S> {
@@ -46,6 +51,9 @@ impl<M: Metadata, S: Middleware<M> + 'static> ServerBuilder<M, S> {
 			channels: Default::default(),
 			incoming_separator: Default::default(),
 			outgoing_separator: Default::default(),
+			binary_encoding: None,
+			execution: Execution::Ordered,
+			max_concurrent_requests: 64,
 		}
 	}
 
@@ -68,6 +76,32 @@ impl<M: Metadata, S: Middleware<M> + 'static> ServerBuilder<M, S> {
 		self
 	}
 
+	/// Exchanges messages serialized with given encoding in length-prefixed frames
+	/// (see `codecs::LengthPrefixedCodec`) instead of JSON separated with request separators.
+	/// Default: None
+	pub fn binary_encoding<E: Encoding>(mut self, encoding: E) -> Self {
+		self.binary_encoding = Some(Arc::new(encoding));
+		self
+	}
+
+	/// Sets the order of execution of requests received on a single connection.
+	/// Default: `Execution::Ordered`
+	pub fn execution(mut self, execution: Execution) -> Self {
+		self.execution = execution;
+		self
+	}
+
+	/// Maximal number of requests processed at once (`Execution::Concurrent`)
+	/// or read ahead while the previous ones are processed (`Execution::Ordered`)
+	/// on a single connection. Further requests are not read from the connection
+	/// until some of the pending ones complete.
+	/// Limit of `0` is treated as `1`.
+	/// Default: 64
+	pub fn max_concurrent_requests(mut self, limit: usize) -> Self {
+		self.max_concurrent_requests = std::cmp::max(limit, 1);
+		self
+	}
+
 	/// Starts a new server
 	pub fn start(self, addr: &SocketAddr) -> std::io::Result<Server> {
 		let meta_extractor = self.meta_extractor.clone();
@@ -75,6 +109,9 @@ impl<M: Metadata, S: Middleware<M> + 'static> ServerBuilder<M, S> {
 		let channels = self.channels.clone();
 		let incoming_separator = self.incoming_separator;
 		let outgoing_separator = self.outgoing_separator;
+		let binary_encoding = self.binary_encoding;
+		let execution = self.execution;
+		let max_concurrent_requests = self.max_concurrent_requests;
 		let address = addr.to_owned();
 		let (tx, rx) = std::sync::mpsc::channel();
 		let (signal, stop) = oneshot::channel();
@@ -84,52 +121,60 @@ impl<M: Metadata, S: Middleware<M> + 'static> ServerBuilder<M, S> {
 		remote.remote().spawn(move |handle| {
 			let start = move || {
 				let listener = tokio_core::net::TcpListener::bind(&address, handle)?;
+				let local_addr = listener.local_addr()?;
 				let connections = listener.incoming();
 				let remote = handle.remote().clone();
 				let server = connections.for_each(move |(socket, peer_addr)| {
 					trace!(target: "tcp", "Accepted incoming connection from {}", &peer_addr);
 					let (sender, receiver) = mpsc::channel(65536);
+					let outbound = OutboundRequests::new(sender.clone());
 
 					let context = RequestContext {
 						peer_addr: peer_addr,
 						sender: sender.clone(),
+						outbound: outbound.clone(),
 					};
 
 					let meta = meta_extractor.extract(&context);
-					let service = Service::new(peer_addr, rpc_handler.clone(), meta);
-					let (writer, reader) = socket.framed(
-						codecs::StreamCodec::new(
+					let service = Service::new(peer_addr, rpc_handler.clone(), meta, sender.clone());
+					let codec = match binary_encoding {
+						Some(ref encoding) => codecs::MessageCodec::Encoded(Default::default(), encoding.clone()),
+						None => codecs::MessageCodec::Stream(codecs::StreamCodec::new(
 							incoming_separator.clone(),
 							outgoing_separator.clone(),
-						)
-					).split();
-
-					let responses = reader.and_then(
-						move |req| service.call(req).then(|response| match response {
-							Err(e) => {
-								warn!(target: "tcp", "Error while processing request: {:?}", e);
-								future::ok(String::new())
-							},
-							Ok(None) => {
-								trace!(target: "tcp", "JSON RPC request produced no response");
-								future::ok(String::new())
-							},
-							Ok(Some(response_data)) => {
-								trace!(target: "tcp", "Sent response: {}", &response_data);
-								future::ok(response_data)
-							}
-						})
-					);
+						)),
+					};
+					let (writer, reader) = socket.framed(codec).split();
+					// responses to requests sent to the peer don't reach the handler
+					let pending = outbound.clone();
+					let reader = reader.filter(move |req| !pending.handle_message(req));
+
+					let call = move |req| service.call(req).then(|response| match response {
+						Err(e) => {
+							warn!(target: "tcp", "Error while processing request: {:?}", e);
+							future::ok(String::new())
+						},
+						Ok(None) => {
+							trace!(target: "tcp", "JSON RPC request produced no response");
+							future::ok(String::new())
+						},
+						Ok(Some(response_data)) => {
+							trace!(target: "tcp", "Sent response: {}", &response_data);
+							future::ok(response_data)
+						}
+					});
+					// In concurrent mode responses are written as soon as they are ready,
+					// clients match them with requests by id.
+					let responses: Box<Stream<Item = String, Error = std::io::Error> + Send> = match execution {
+						Execution::Ordered => Box::new(OrderedCalls::new(reader, max_concurrent_requests, call)),
+						Execution::Concurrent => Box::new(reader.map(call).buffer_unordered(max_concurrent_requests)),
+					};
 
 					let peer_message_queue = {
 						let mut channels = channels.lock();
 						channels.insert(peer_addr.clone(), sender.clone());
 
-						PeerMessageQueue::new(
-							responses,
-							receiver,
-							peer_addr.clone(),
-						)
+						MessageQueue::new(responses, receiver)
 					};
 
 					let shared_channels = channels.clone();
@@ -137,6 +182,7 @@ impl<M: Metadata, S: Middleware<M> + 'static> ServerBuilder<M, S> {
 						trace!(target: "tcp", "Peer {}: service finished", peer_addr);
 						let mut channels = shared_channels.lock();
 						channels.remove(&peer_addr);
+						outbound.close();
 						Ok(())
 					});
 
@@ -145,13 +191,13 @@ impl<M: Metadata, S: Middleware<M> + 'static> ServerBuilder<M, S> {
 					Ok(())
 				});
 
-				Ok(server)
+				Ok((server, local_addr))
 			};
 
 			let stop = stop.map_err(|_| std::io::ErrorKind::Interrupted.into());
 			match start() {
-				Ok(server) => {
-					tx.send(Ok(())).expect("Rx is blocking parent thread.");
+				Ok((server, local_addr)) => {
+					tx.send(Ok(local_addr)).expect("Rx is blocking parent thread.");
 					future::Either::A(server.select(stop)
 						.map(|_| ())
 						.map_err(|(e, _)| {
@@ -170,7 +216,8 @@ impl<M: Metadata, S: Middleware<M> + 'static> ServerBuilder<M, S> {
 
 		let res = rx.recv().expect("Response is always sent before tx is dropped.");
 
-		res.map(|_| Server {
+		res.map(|address| Server {
+			address,
 			remote: Some(remote),
 			stop: Some(signal),
 		})
@@ -184,11 +231,17 @@ impl<M: Metadata, S: Middleware<M> + 'static> ServerBuilder<M, S> {
 
 /// TCP Server handle
 pub struct Server {
+	address: SocketAddr,
 	remote: Option<reactor::Remote>,
 	stop: Option<oneshot::Sender<()>>,
 }
 
 impl Server {
+	/// Returns address of this server
+	pub fn address(&self) -> &SocketAddr {
+		&self.address
+	}
+
 	/// Closes the server (waits for finish)
 	pub fn close(mut self) {
 		let _ = self.stop.take().map(|sg| sg.send(()));
@@ -3,17 +3,21 @@ use std::net::SocketAddr;
 
 use tokio_service;
 
-use jsonrpc::{FutureResult, Metadata, MetaIoHandler, Middleware, NoopMiddleware};
+use jsonrpc::{FutureResult, Metadata, MetaIoHandler, Middleware, NoopMiddleware, StreamingResponse};
+use jsonrpc::futures::{future, Future};
+use jsonrpc::futures::sync::mpsc;
+use server_utils::session;
 
 pub struct Service<M: Metadata = (), S: Middleware<M> = NoopMiddleware> {
 	handler: Arc<MetaIoHandler<M, S>>,
 	peer_addr: SocketAddr,
 	meta: M,
+	sender: mpsc::Sender<String>,
 }
 
 impl<M: Metadata, S: Middleware<M>> Service<M, S> {
-	pub fn new(peer_addr: SocketAddr, handler: Arc<MetaIoHandler<M, S>>, meta: M) -> Self {
-		Service { peer_addr: peer_addr, handler: handler, meta: meta }
+	pub fn new(peer_addr: SocketAddr, handler: Arc<MetaIoHandler<M, S>>, meta: M, sender: mpsc::Sender<String>) -> Self {
+		Service { peer_addr: peer_addr, handler: handler, meta: meta, sender: sender }
 	}
 }
 
@@ -26,11 +30,14 @@ impl<M: Metadata, S: Middleware<M>> tokio_service::Service for Service<M, S> {
 	type Error = ();
 
 	// The future for computing the response; box it for simplicity.
-	type Future = FutureResult<S::Future>;
+	type Future = future::Either<FutureResult<S::Future>, Box<Future<Item = Option<String>, Error = ()> + Send>>;
 
 	// Produce a future for computing a response from a request.
 	fn call(&self, req: Self::Request) -> Self::Future {
 		trace!(target: "tcp", "Accepted request from peer {}: {}", &self.peer_addr, req);
-		self.handler.handle_request(&req, self.meta.clone())
+		match self.handler.handle_streaming_request(&req, self.meta.clone()) {
+			StreamingResponse::Stream(messages) => future::Either::B(session::push_chunks(messages, self.sender.clone())),
+			StreamingResponse::Response(response) => future::Either::A(response),
+		}
 	}
 }
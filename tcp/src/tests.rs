@@ -13,6 +13,7 @@ use server_utils::tokio_core::reactor::{Core, Timeout};
 use parking_lot::Mutex;
 
 use ServerBuilder;
+use Execution;
 use MetaExtractor;
 use RequestContext;
 
@@ -306,3 +307,167 @@ fn message() {
 	assert!(*executed_dispatch.borrow_mut());
 	assert!(*executed_request.borrow_mut());
 }
+
+fn slow_and_fast_server() -> ServerBuilder {
+	let mut io = MetaIoHandler::<()>::default();
+	io.add_method("slow", |_params| {
+		let (tx, rx) = ::jsonrpc::futures::oneshot();
+		::std::thread::spawn(move || {
+			::std::thread::sleep(::std::time::Duration::from_millis(100));
+			let _ = tx.send(Value::String("slow".into()));
+		});
+		rx.map_err(|_| ::jsonrpc::Error::internal_error())
+	});
+	io.add_method("fast", |_params| Ok(Value::String("fast".into())));
+	ServerBuilder::new(io)
+}
+
+fn slow_and_fast_requests(addr: &SocketAddr) -> Vec<String> {
+	use std::io::{BufRead, BufReader, Write};
+
+	let mut stream = ::std::net::TcpStream::connect(addr).unwrap();
+	stream.write_all(b"{\"jsonrpc\":\"2.0\",\"method\":\"slow\",\"id\":1}\n{\"jsonrpc\":\"2.0\",\"method\":\"fast\",\"id\":2}\n").unwrap();
+	let mut reader = BufReader::new(stream);
+	(0..2).map(|_| {
+		let mut line = String::new();
+		reader.read_line(&mut line).unwrap();
+		line
+	}).collect()
+}
+
+#[test]
+fn should_process_requests_in_order() {
+	::logger::init_log();
+	let addr: SocketAddr = "127.0.0.1:17792".parse().unwrap();
+	let _server = slow_and_fast_server().start(&addr).expect("Server must run with no issues");
+
+	assert_eq!(slow_and_fast_requests(&addr), vec![
+		"{\"jsonrpc\":\"2.0\",\"result\":\"slow\",\"id\":1}\n".to_owned(),
+		"{\"jsonrpc\":\"2.0\",\"result\":\"fast\",\"id\":2}\n".to_owned(),
+	]);
+}
+
+#[test]
+fn should_process_requests_concurrently() {
+	::logger::init_log();
+	let addr: SocketAddr = "127.0.0.1:17793".parse().unwrap();
+	let _server = slow_and_fast_server().execution(Execution::Concurrent).start(&addr).expect("Server must run with no issues");
+
+	assert_eq!(slow_and_fast_requests(&addr), vec![
+		"{\"jsonrpc\":\"2.0\",\"result\":\"fast\",\"id\":2}\n".to_owned(),
+		"{\"jsonrpc\":\"2.0\",\"result\":\"slow\",\"id\":1}\n".to_owned(),
+	]);
+}
+
+#[test]
+fn should_limit_concurrent_requests() {
+	::logger::init_log();
+	let addr: SocketAddr = "127.0.0.1:17794".parse().unwrap();
+	let _server = slow_and_fast_server()
+		.execution(Execution::Concurrent)
+		.max_concurrent_requests(0)
+		.start(&addr)
+		.expect("Server must run with no issues");
+
+	assert_eq!(slow_and_fast_requests(&addr), vec![
+		"{\"jsonrpc\":\"2.0\",\"result\":\"slow\",\"id\":1}\n".to_owned(),
+		"{\"jsonrpc\":\"2.0\",\"result\":\"fast\",\"id\":2}\n".to_owned(),
+	]);
+}
+
+fn call_peer_version(addr: &SocketAddr, execution: Execution) -> (String, String) {
+	use std::io::{BufRead, BufReader, Write};
+	use jsonrpc::{Error, Params};
+	use OutboundRequests;
+
+	let mut io = MetaIoHandler::<OutboundRequests>::default();
+	io.add_method_with_meta("peer_version", |_params, outbound: OutboundRequests| {
+		outbound.call("version", Params::None).map_err(|_| Error::internal_error())
+	});
+	let server = ServerBuilder::with_meta_extractor(io, |context: &RequestContext| context.outbound.clone());
+	let _server = server.execution(execution).start(addr).expect("Server must run with no issues");
+
+	let mut stream = ::std::net::TcpStream::connect(addr).unwrap();
+	stream.write_all(b"{\"jsonrpc\":\"2.0\",\"method\":\"peer_version\",\"id\":1}\n").unwrap();
+	let mut reader = BufReader::new(stream.try_clone().unwrap());
+	let mut request = String::new();
+	reader.read_line(&mut request).unwrap();
+	stream.write_all(b"{\"jsonrpc\":\"2.0\",\"result\":\"1.0\",\"id\":1}\n").unwrap();
+	let mut response = String::new();
+	reader.read_line(&mut response).unwrap();
+	(request, response)
+}
+
+#[test]
+fn should_call_methods_on_the_peer() {
+	::logger::init_log();
+	let addr: SocketAddr = "127.0.0.1:17795".parse().unwrap();
+
+	let (request, response) = call_peer_version(&addr, Execution::Concurrent);
+
+	assert_eq!(request, "{\"jsonrpc\":\"2.0\",\"method\":\"version\",\"params\":[],\"id\":1}\n");
+	assert_eq!(response, "{\"jsonrpc\":\"2.0\",\"result\":\"1.0\",\"id\":1}\n");
+}
+
+#[test]
+fn should_call_methods_on_the_peer_while_processing_requests_in_order() {
+	::logger::init_log();
+	let addr: SocketAddr = "127.0.0.1:17798".parse().unwrap();
+
+	let (request, response) = call_peer_version(&addr, Execution::Ordered);
+
+	assert_eq!(request, "{\"jsonrpc\":\"2.0\",\"method\":\"version\",\"params\":[],\"id\":1}\n");
+	assert_eq!(response, "{\"jsonrpc\":\"2.0\",\"result\":\"1.0\",\"id\":1}\n");
+}
+
+#[test]
+fn should_stream_results_before_response() {
+	use std::io::{BufRead, BufReader, Write};
+	use jsonrpc::futures::stream;
+
+	// given
+	::logger::init_log();
+	let addr: SocketAddr = "127.0.0.1:17796".parse().unwrap();
+	let mut io = MetaIoHandler::<()>::default();
+	io.add_streaming_method("logs", |_params, _meta| stream::iter_ok((0..3).map(Value::from)));
+	let _server = ServerBuilder::new(io).start(&addr).expect("Server must run with no issues");
+
+	// when
+	let mut stream = ::std::net::TcpStream::connect(&addr).unwrap();
+	stream.write_all(b"{\"jsonrpc\":\"2.0\",\"method\":\"logs\",\"id\":1}\n").unwrap();
+	let reader = BufReader::new(stream);
+	let messages: Vec<_> = reader.lines().take(4).map(Result::unwrap).collect();
+
+	// then
+	assert_eq!(messages, vec![
+		"{\"jsonrpc\":\"2.0\",\"method\":\"rpc.chunk\",\"params\":{\"id\":1,\"result\":0}}",
+		"{\"jsonrpc\":\"2.0\",\"method\":\"rpc.chunk\",\"params\":{\"id\":1,\"result\":1}}",
+		"{\"jsonrpc\":\"2.0\",\"method\":\"rpc.chunk\",\"params\":{\"id\":1,\"result\":2}}",
+		"{\"jsonrpc\":\"2.0\",\"result\":null,\"id\":1}",
+	]);
+}
+
+#[test]
+fn should_exchange_length_prefixed_messages() {
+	use std::io::{Read, Write};
+	use jsonrpc::encoding::Json;
+
+	// given
+	::logger::init_log();
+	let addr: SocketAddr = "127.0.0.1:17797".parse().unwrap();
+	let _server = casual_server().binary_encoding(Json).start(&addr).expect("Server must run with no issues");
+
+	// when
+	let request = b"{\"jsonrpc\":\"2.0\",\"method\":\"say_hello\",\"id\":1}";
+	let mut stream = ::std::net::TcpStream::connect(&addr).unwrap();
+	stream.write_all(&[0, 0, 0, request.len() as u8]).unwrap();
+	stream.write_all(request).unwrap();
+	let mut header = [0u8; 4];
+	stream.read_exact(&mut header).unwrap();
+	let mut response = vec![0u8; header[3] as usize];
+	stream.read_exact(&mut response).unwrap();
+
+	// then
+	assert_eq!(&header[..3], &[0, 0, 0]);
+	assert_eq!(response, b"{\"id\":1,\"jsonrpc\":\"2.0\",\"result\":\"hello\"}".to_vec());
+}
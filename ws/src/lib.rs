@@ -32,4 +32,6 @@ pub use self::server_builder::ServerBuilder;
 pub use self::server_utils::cors::Origin;
 pub use self::server_utils::hosts::{Host, DomainsValidation};
 pub use self::server_utils::tokio_core;
-pub use self::server_utils::session::{SessionId, SessionStats};
+pub use self::server_utils::session::{Execution, SessionId, SessionStats};
+pub use self::server_utils::outbound::{OutboundRequests, CallError, CallFuture};
+pub use self::server_utils::progress::{ProgressMetadata, ProgressReporter, WithProgress};
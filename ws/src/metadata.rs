@@ -4,6 +4,7 @@ use std::sync::{atomic, Arc};
 use core::{self, futures};
 use core::futures::sync::mpsc;
 use server_utils::tokio_core::reactor::Remote;
+use server_utils::outbound::OutboundRequests;
 use server_utils::session;
 use ws;
 
@@ -128,19 +129,26 @@ pub struct RequestContext {
 	pub out: Sender,
 	/// Remote to underlying event loop.
 	pub remote: Remote,
+	/// Requests to the client (responses are matched by id)
+	pub outbound: OutboundRequests,
 }
 
 impl RequestContext {
 	/// Get this session as a `Sink` spawning a new future
 	/// in the underlying event loop.
 	pub fn sender(&self) -> mpsc::Sender<String> {
-		let out = self.out.clone();
-		let (sender, receiver) = mpsc::channel(1);
-		self.remote.spawn(move |_| SenderFuture(out, receiver));
-		sender
+		spawn_sender(&self.out, &self.remote)
 	}
 }
 
+/// Creates a channel forwarding messages to the connection.
+pub fn spawn_sender(out: &Sender, remote: &Remote) -> mpsc::Sender<String> {
+	let out = out.clone();
+	let (sender, receiver) = mpsc::channel(1);
+	remote.spawn(move |_| SenderFuture(out, receiver));
+	sender
+}
+
 impl fmt::Debug for RequestContext {
 	fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
 		fmt.debug_struct("RequestContext")
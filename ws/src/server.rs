@@ -7,7 +7,7 @@ use core;
 use server_utils::cors::Origin;
 use server_utils::hosts::{self, Host};
 use server_utils::reactor::{UninitializedRemote, Remote};
-use server_utils::session::SessionStats;
+use server_utils::session::{Execution, SessionStats};
 use ws;
 
 use error::{Error, Result};
@@ -50,6 +50,8 @@ impl Server {
 		stats: Option<Arc<SessionStats>>,
 		remote: UninitializedRemote,
 		max_connections: usize,
+		binary_encoding: Option<Arc<core::Encoding>>,
+		execution: Execution,
 	) -> Result<Server> {
 		let config = {
 			let mut config = ws::Settings::default();
@@ -76,7 +78,7 @@ impl Server {
 
 		// Create WebSocket
 		let ws = ws::Builder::new().with_settings(config).build(session::Factory::new(
-			handler, meta_extractor, allowed_origins, allowed_hosts, request_middleware, stats, remote
+			handler, meta_extractor, allowed_origins, allowed_hosts, request_middleware, stats, remote, binary_encoding, execution
 		))?;
 		let broadcaster = ws.broadcaster();
 
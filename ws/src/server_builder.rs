@@ -6,7 +6,7 @@ use server_utils;
 use server_utils::cors::Origin;
 use server_utils::hosts::{Host, DomainsValidation};
 use server_utils::reactor::UninitializedRemote;
-use server_utils::session::SessionStats;
+use server_utils::session::{Execution, SessionStats};
 
 use error::Result;
 use metadata::{MetaExtractor, NoopExtractor};
@@ -23,6 +23,8 @@ pub struct ServerBuilder<M: core::Metadata, S: core::Middleware<M>> {
 	session_stats: Option<Arc<SessionStats>>,
 	remote: UninitializedRemote,
 	max_connections: usize,
+	binary_encoding: Option<Arc<core::Encoding>>,
+	execution: Execution,
 }
 
 impl<M: core::Metadata + Default, S: core::Middleware<M>> ServerBuilder<M, S> {
@@ -49,6 +51,8 @@ impl<M: core::Metadata, S: core::Middleware<M>> ServerBuilder<M, S> {
 			session_stats: None,
 			remote: UninitializedRemote::Unspawned,
 			max_connections: 100,
+			binary_encoding: None,
+			execution: Execution::Concurrent,
 		}
 	}
 
@@ -96,6 +100,21 @@ impl<M: core::Metadata, S: core::Middleware<M>> ServerBuilder<M, S> {
 		self
 	}
 
+	/// Accepts requests sent as binary messages and serialized with given encoding.
+	/// Responses to such requests are sent as binary messages serialized with the same encoding.
+	/// By default binary messages are rejected.
+	pub fn binary_encoding<E: core::Encoding>(mut self, encoding: E) -> Self {
+		self.binary_encoding = Some(Arc::new(encoding));
+		self
+	}
+
+	/// Sets the order of execution of requests received within a single session.
+	/// Default: `Execution::Concurrent`
+	pub fn execution(mut self, execution: Execution) -> Self {
+		self.execution = execution;
+		self
+	}
+
 	/// Starts a new `WebSocket` server in separate thread.
 	/// Returns a `Server` handle which closes the server when droped.
 	pub fn start(self, addr: &SocketAddr) -> Result<Server> {
@@ -109,6 +128,8 @@ impl<M: core::Metadata, S: core::Middleware<M>> ServerBuilder<M, S> {
 			self.session_stats,
 			self.remote,
 			self.max_connections,
+			self.binary_encoding,
+			self.execution,
 		)
 	}
 
@@ -2,8 +2,8 @@ use std;
 use std::sync::{atomic, Arc};
 
 use core;
-use core::futures::{Async, Future, Poll};
-use core::futures::sync::oneshot;
+use core::futures::{future, Async, Future, Poll, Stream};
+use core::futures::sync::{mpsc, oneshot};
 
 use parking_lot::Mutex;
 use slab::Slab;
@@ -11,8 +11,9 @@ use slab::Slab;
 use server_utils::Pattern;
 use server_utils::cors::Origin;
 use server_utils::hosts::Host;
+use server_utils::outbound::OutboundRequests;
 use server_utils::tokio_core::reactor::Remote;
-use server_utils::session::{SessionId, SessionStats};
+use server_utils::session::{Execution, SessionId, SessionStats};
 use ws;
 
 use error;
@@ -79,6 +80,14 @@ impl From<Option<ws::Response>> for MiddlewareAction {
 	}
 }
 
+type ResponseFuture = Box<Future<Item = Option<ws::Message>, Error = ()> + Send>;
+type Task = Box<Future<Item = (), Error = ()> + Send>;
+
+enum Payload {
+	Text(String),
+	Encoded(Vec<u8>, Arc<core::Encoding>),
+}
+
 // the slab is only inserted into when live.
 type TaskSlab = Mutex<Slab<Option<oneshot::Sender<()>>>>;
 
@@ -147,12 +156,17 @@ pub struct Session<M: core::Metadata, S: core::Middleware<M>> {
 	metadata: Option<M>,
 	remote: Remote,
 	task_slab: Arc<TaskSlab>,
+	binary_encoding: Option<Arc<core::Encoding>>,
+	execution: Execution,
+	// tasks of ordered session, executed one by one
+	queue: Option<mpsc::UnboundedSender<Task>>,
 }
 
 impl<M: core::Metadata, S: core::Middleware<M>> Drop for Session<M, S> {
 	fn drop(&mut self) {
 		self.active.store(false, atomic::Ordering::SeqCst);
 		self.stats.as_ref().map(|stats| stats.close_session(self.context.session_id));
+		self.context.outbound.close();
 
 		// signal to all still-live tasks that the session has been dropped.
 		for (_index, task) in self.task_slab.lock().iter_mut() {
@@ -192,6 +206,19 @@ impl<M: core::Metadata, S: core::Middleware<M>> Session<M, S> {
 			None
 		}
 	}
+
+	fn enqueue(&mut self, task: Task) {
+		if self.queue.is_none() {
+			let (sender, receiver) = mpsc::unbounded();
+			self.remote.spawn(|_| receiver.for_each(|task| task));
+			self.queue = Some(sender);
+		}
+
+		let queue = self.queue.as_ref().expect("Queue is initialized above; qed");
+		if queue.unbounded_send(task).is_err() {
+			warn!("Session queue closed, dropping request.");
+		}
+	}
 }
 
 impl<M: core::Metadata, S: core::Middleware<M>> ws::Handler for Session<M, S> {
@@ -236,9 +263,46 @@ impl<M: core::Metadata, S: core::Middleware<M>> ws::Handler for Session<M, S> {
 	}
 
 	fn on_message(&mut self, msg: ws::Message) -> ws::Result<()> {
-		let req = msg.as_text()?;
-		let out = self.context.out.clone();
 		let metadata = self.metadata.clone().expect("Metadata is always set in on_request; qed");
+		let payload = match (msg, self.binary_encoding.clone()) {
+			(ws::Message::Binary(req), Some(encoding)) => Payload::Encoded(req, encoding),
+			(msg, _) => Payload::Text(msg.into_text()?),
+		};
+		// responses to requests sent to the client don't reach the handler
+		if let Payload::Text(ref msg) = payload {
+			if self.context.outbound.handle_message(msg) {
+				return Ok(());
+			}
+		}
+		let handler = self.handler.clone();
+		let out = self.context.out.clone();
+		let chunks_out = out.clone();
+		let call = move || -> ResponseFuture {
+			match payload {
+				Payload::Text(req) => match handler.handle_streaming_request(&req, metadata) {
+					// Chunks of results are sent right away, the last message is the response.
+					core::StreamingResponse::Stream(messages) => Box::new(messages.fold(None, move |previous, message| {
+						if let Some(chunk) = previous {
+							chunks_out.send(ws::Message::Text(chunk)).map_err(|e| {
+								warn!("Error while sending streamed result: {:?}", e);
+							})?;
+						}
+						Ok(Some(message))
+					}).map(|res| res.map(ws::Message::Text))),
+					core::StreamingResponse::Response(response) => Box::new(
+						response.map(|res| res.map(ws::Message::Text))
+					),
+				},
+				Payload::Encoded(req, encoding) => Box::new(
+					handler.handle_encoded_request(&req, encoding, metadata).map(|res| res.map(ws::Message::Binary))
+				),
+			}
+		};
+		// In ordered mode the call is dispatched only after all previous tasks are done.
+		let response: ResponseFuture = match self.execution {
+			Execution::Concurrent => call(),
+			Execution::Ordered => Box::new(future::lazy(call)),
+		};
 
 		// TODO: creation requires allocating a `oneshot` channel and acquiring a
 		// mutex. we could alternatively do this lazily upon first poll if
@@ -246,7 +310,7 @@ impl<M: core::Metadata, S: core::Middleware<M>> ws::Handler for Session<M, S> {
 		let poll_liveness = LivenessPoll::create(self.task_slab.clone());
 
 		let active_lock = self.active.clone();
-		let future = self.handler.handle_request(req, metadata)
+		let future = response
 			.map(move |response| {
 				if !active_lock.load(atomic::Ordering::SeqCst) {
 					return;
@@ -268,7 +332,10 @@ impl<M: core::Metadata, S: core::Middleware<M>> ws::Handler for Session<M, S> {
 			.map(|_| ())
 			.map_err(|_| ());
 
-		self.remote.spawn(|_| future);
+		match self.execution {
+			Execution::Concurrent => self.remote.spawn(|_| future),
+			Execution::Ordered => self.enqueue(Box::new(future)),
+		}
 
 		Ok(())
 	}
@@ -283,6 +350,8 @@ pub struct Factory<M: core::Metadata, S: core::Middleware<M>> {
 	request_middleware: Option<Arc<RequestMiddleware>>,
 	stats: Option<Arc<SessionStats>>,
 	remote: Remote,
+	binary_encoding: Option<Arc<core::Encoding>>,
+	execution: Execution,
 }
 
 impl<M: core::Metadata, S: core::Middleware<M>> Factory<M, S> {
@@ -294,6 +363,8 @@ impl<M: core::Metadata, S: core::Middleware<M>> Factory<M, S> {
 		request_middleware: Option<Arc<RequestMiddleware>>,
 		stats: Option<Arc<SessionStats>>,
 		remote: Remote,
+		binary_encoding: Option<Arc<core::Encoding>>,
+		execution: Execution,
 	) -> Self {
 		Factory {
 			session_id: 0,
@@ -304,6 +375,8 @@ impl<M: core::Metadata, S: core::Middleware<M>> Factory<M, S> {
 			request_middleware: request_middleware,
 			stats: stats,
 			remote: remote,
+			binary_encoding: binary_encoding,
+			execution: execution,
 		}
 	}
 }
@@ -315,15 +388,18 @@ impl<M: core::Metadata, S: core::Middleware<M>> ws::Factory for Factory<M, S> {
 		self.session_id += 1;
 		self.stats.as_ref().map(|stats| stats.open_session(self.session_id));
 		let active = Arc::new(atomic::AtomicBool::new(true));
+		let out = metadata::Sender::new(sender, active.clone());
+		let outbound = OutboundRequests::new(metadata::spawn_sender(&out, &self.remote));
 
 		Session {
-			active: active.clone(),
+			active: active,
 			context: metadata::RequestContext {
 				session_id: self.session_id,
 				origin: None,
 				protocols: Vec::new(),
-				out: metadata::Sender::new(sender, active),
+				out: out,
 				remote: self.remote.clone(),
+				outbound: outbound,
 			},
 			handler: self.handler.clone(),
 			meta_extractor: self.meta_extractor.clone(),
@@ -334,6 +410,9 @@ impl<M: core::Metadata, S: core::Middleware<M>> ws::Factory for Factory<M, S> {
 			metadata: None,
 			remote: self.remote.clone(),
 			task_slab: Arc::new(Mutex::new(Slab::with_capacity(0))),
+			binary_encoding: self.binary_encoding.clone(),
+			execution: self.execution,
+			queue: None,
 		}
 	}
 }